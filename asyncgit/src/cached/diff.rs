@@ -0,0 +1,253 @@
+use crate::{
+	diff::{DiffParams, DiffType},
+	error::Result,
+	sync::{repo, utils::get_head_repo, CommitId, RepoPath},
+	FileDiff,
+};
+use scopetime::scope_time;
+use std::{
+	collections::HashMap,
+	fs,
+	path::Path,
+	time::{Duration, SystemTime},
+};
+
+/// number of diffs kept before the cache starts over
+const MAX_ENTRIES: usize = 32;
+
+/// a file modified this recently can be rewritten with the same size
+/// without its mtime changing (mtime granularity is 2s on FAT)
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// everything a diff of the workdir or stage depends on,
+/// as long as it is unchanged so is the diff
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiffInputs {
+	file: Option<(SystemTime, u64)>,
+	index: Option<(SystemTime, u64)>,
+	head: Option<CommitId>,
+	/// file or index were modified within `RACY_WINDOW`, their stat
+	/// might not change on the next write so the diff is not cached
+	racy: bool,
+}
+
+impl DiffInputs {
+	/// stats the file of `params`, the index and resolves HEAD.
+	/// diffs of commits never change, their inputs are always equal.
+	pub fn new(
+		repo_path: &RepoPath,
+		params: &DiffParams,
+	) -> Result<Self> {
+		Self::at(repo_path, params, SystemTime::now())
+	}
+
+	fn at(
+		repo_path: &RepoPath,
+		params: &DiffParams,
+		now: SystemTime,
+	) -> Result<Self> {
+		scope_time!("DiffInputs::new");
+
+		if matches!(
+			params.diff_type,
+			DiffType::Commit(_) | DiffType::Commits(_)
+		) {
+			return Ok(Self::default());
+		}
+
+		let repo = repo(repo_path)?;
+
+		let file = repo
+			.workdir()
+			.and_then(|dir| stat(&dir.join(&params.path)));
+		let index = stat(&repo.path().join("index"));
+		let racy =
+			file.iter().chain(index.iter()).any(|(mtime, _)| {
+				!now.duration_since(*mtime)
+					.is_ok_and(|age| age >= RACY_WINDOW)
+			});
+
+		Ok(Self {
+			file,
+			index,
+			head: get_head_repo(&repo).ok(),
+			racy,
+		})
+	}
+}
+
+fn stat(path: &Path) -> Option<(SystemTime, u64)> {
+	let meta = fs::symlink_metadata(path).ok()?;
+	Some((meta.modified().ok()?, meta.len()))
+}
+
+/// caches diffs as long as their `DiffInputs` are unchanged, racy
+/// inputs (see `RACY_WINDOW`) are never cached.
+/// a hit costs a few `stat` calls (`DiffInputs::new` in the
+/// `scope_time` output) instead of a full diff (`get_diff`).
+#[derive(Default)]
+pub struct DiffCache {
+	entries: HashMap<DiffParams, (DiffInputs, FileDiff)>,
+}
+
+impl DiffCache {
+	///
+	pub fn get(
+		&self,
+		params: &DiffParams,
+		inputs: &DiffInputs,
+	) -> Option<FileDiff> {
+		self.entries
+			.get(params)
+			.filter(|(cached, _)| cached == inputs)
+			.map(|(_, diff)| diff.clone())
+	}
+
+	///
+	pub fn insert(
+		&mut self,
+		params: DiffParams,
+		inputs: DiffInputs,
+		diff: FileDiff,
+	) {
+		if inputs.racy {
+			return;
+		}
+
+		if self.entries.len() >= MAX_ENTRIES
+			&& !self.entries.contains_key(&params)
+		{
+			self.entries.clear();
+		}
+
+		self.entries.insert(params, (inputs, diff));
+	}
+
+	/// drops all entries (e.g. after rewriting HEAD)
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		hash,
+		sync::{
+			diff::{get_diff, DiffOptions},
+			stage_add_file,
+			tests::{repo_init, write_commit_file},
+		},
+	};
+
+	/// inputs as seen once the racy window passed
+	fn settled(
+		repo_path: &RepoPath,
+		params: &DiffParams,
+	) -> DiffInputs {
+		DiffInputs::at(
+			repo_path,
+			params,
+			SystemTime::now() + RACY_WINDOW,
+		)
+		.unwrap()
+	}
+
+	fn params(diff_type: DiffType) -> DiffParams {
+		DiffParams {
+			path: String::from("foo.txt"),
+			diff_type,
+			options: DiffOptions::default(),
+		}
+	}
+
+	#[test]
+	fn test_hit() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+
+		let params = params(DiffType::WorkDir);
+		let mut cache = DiffCache::default();
+
+		let inputs = settled(repo_path, &params);
+		assert!(cache.get(&params, &inputs).is_none());
+
+		let diff =
+			get_diff(repo_path, "foo.txt", false, None).unwrap();
+		cache.insert(params.clone(), inputs, diff.clone());
+
+		let inputs = settled(repo_path, &params);
+		let cached = cache.get(&params, &inputs).unwrap();
+		assert_eq!(hash(&cached), hash(&diff));
+
+		cache.clear();
+		assert!(cache.get(&params, &inputs).is_none());
+	}
+
+	#[test]
+	fn test_modify_and_stage() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+
+		let workdir = params(DiffType::WorkDir);
+		let stage = params(DiffType::Stage);
+		let mut cache = DiffCache::default();
+
+		let inputs = settled(repo_path, &workdir);
+		cache.insert(workdir.clone(), inputs, FileDiff::default());
+
+		fs::write(root.join("foo.txt"), "modified\n").unwrap();
+
+		let inputs = settled(repo_path, &workdir);
+		assert!(cache.get(&workdir, &inputs).is_none());
+
+		let inputs = settled(repo_path, &stage);
+		cache.insert(
+			stage.clone(),
+			inputs.clone(),
+			FileDiff::default(),
+		);
+		assert!(cache.get(&stage, &inputs).is_some());
+
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+
+		let inputs = settled(repo_path, &stage);
+		assert!(cache.get(&stage, &inputs).is_none());
+	}
+
+	#[test]
+	fn test_racy_rewrite() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+
+		let params = params(DiffType::WorkDir);
+		let mut cache = DiffCache::default();
+
+		let inputs = DiffInputs::new(repo_path, &params).unwrap();
+		let diff =
+			get_diff(repo_path, "foo.txt", false, None).unwrap();
+		cache.insert(params.clone(), inputs, diff);
+
+		// same size, likely within the same mtime tick
+		fs::write(root.join("foo.txt"), "c\n").unwrap();
+
+		let inputs = DiffInputs::new(repo_path, &params).unwrap();
+		assert!(cache.get(&params, &inputs).is_none());
+	}
+}
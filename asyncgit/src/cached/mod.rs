@@ -3,5 +3,7 @@
 //! to compute but change seldom so doing them async might be overkill
 
 mod branchname;
+mod diff;
 
 pub use branchname::BranchName;
+pub use diff::{DiffCache, DiffInputs};
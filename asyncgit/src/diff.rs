@@ -1,4 +1,5 @@
 use crate::{
+	cached::{DiffCache, DiffInputs},
 	error::Result,
 	hash,
 	sync::{
@@ -27,6 +28,9 @@ pub enum DiffType {
 	Stage,
 	/// diff against file in workdir
 	WorkDir,
+	/// diff of head against file in workdir, hunks tagged
+	/// with their staged state
+	Both,
 }
 
 ///
@@ -52,6 +56,8 @@ struct LastResult<P, R> {
 pub struct AsyncDiff {
 	current: Arc<Mutex<Request<u64, FileDiff>>>,
 	last: Arc<Mutex<Option<LastResult<DiffParams, FileDiff>>>>,
+	last_error: Arc<Mutex<Option<String>>>,
+	cache: Arc<Mutex<DiffCache>>,
 	sender: Sender<AsyncGitNotification>,
 	pending: Arc<AtomicUsize>,
 	repo: RepoPath,
@@ -67,6 +73,8 @@ impl AsyncDiff {
 			repo,
 			current: Arc::new(Mutex::new(Request(0, None))),
 			last: Arc::new(Mutex::new(None)),
+			last_error: Arc::new(Mutex::new(None)),
+			cache: Arc::new(Mutex::new(DiffCache::default())),
 			sender: sender.clone(),
 			pending: Arc::new(AtomicUsize::new(0)),
 		}
@@ -79,6 +87,11 @@ impl AsyncDiff {
 		Ok(last.clone().map(|res| (res.params, res.result)))
 	}
 
+	/// returns (and clears) the error of the last failed request
+	pub fn take_last_error(&self) -> Result<Option<String>> {
+		Ok(self.last_error.lock()?.take())
+	}
+
 	///
 	pub fn refresh(&self) -> Result<()> {
 		if let Ok(Some(param)) = self.get_last_param() {
@@ -88,6 +101,12 @@ impl AsyncDiff {
 		Ok(())
 	}
 
+	/// forgets all cached diffs
+	pub fn clear_cache(&self) -> Result<()> {
+		self.cache.lock()?.clear();
+		Ok(())
+	}
+
 	///
 	pub fn is_pending(&self) -> bool {
 		self.pending.load(Ordering::Relaxed) > 0
@@ -115,6 +134,8 @@ impl AsyncDiff {
 
 		let arc_current = Arc::clone(&self.current);
 		let arc_last = Arc::clone(&self.last);
+		let arc_error = Arc::clone(&self.last_error);
+		let arc_cache = Arc::clone(&self.cache);
 		let sender = self.sender.clone();
 		let arc_pending = Arc::clone(&self.pending);
 		let repo = self.repo.clone();
@@ -127,12 +148,16 @@ impl AsyncDiff {
 				params,
 				&arc_last,
 				&arc_current,
+				&arc_cache,
 				hash,
 			);
 
 			let notify = match notify {
 				Err(err) => {
 					log::error!("get_diff_helper error: {}", err);
+					if let Ok(mut last_error) = arc_error.lock() {
+						*last_error = Some(err.to_string());
+					}
 					true
 				}
 				Ok(notify) => notify,
@@ -159,8 +184,48 @@ impl AsyncDiff {
 			Mutex<Option<LastResult<DiffParams, FileDiff>>>,
 		>,
 		arc_current: &Arc<Mutex<Request<u64, FileDiff>>>,
+		arc_cache: &Arc<Mutex<DiffCache>>,
 		hash: u64,
 	) -> Result<bool> {
+		let inputs = DiffInputs::new(repo_path, &params)?;
+		let cached = arc_cache.lock()?.get(&params, &inputs);
+
+		let res = if let Some(cached) = cached {
+			cached
+		} else {
+			let res = Self::get_diff(repo_path, &params)?;
+			arc_cache.lock()?.insert(
+				params.clone(),
+				inputs,
+				res.clone(),
+			);
+			res
+		};
+
+		let mut notify = false;
+		{
+			let mut current = arc_current.lock()?;
+			if current.0 == hash {
+				current.1 = Some(res.clone());
+				notify = true;
+			}
+		}
+
+		{
+			let mut last = arc_last.lock()?;
+			*last = Some(LastResult {
+				result: res,
+				params,
+			});
+		}
+
+		Ok(notify)
+	}
+
+	fn get_diff(
+		repo_path: &RepoPath,
+		params: &DiffParams,
+	) -> Result<FileDiff> {
 		let res = match params.diff_type {
 			DiffType::Stage => sync::diff::get_diff(
 				repo_path,
@@ -174,6 +239,11 @@ impl AsyncDiff {
 				false,
 				Some(params.options),
 			)?,
+			DiffType::Both => sync::diff::get_diff_combined(
+				repo_path,
+				&params.path,
+				Some(params.options),
+			)?,
 			DiffType::Commit(id) => sync::diff::get_diff_commit(
 				repo_path,
 				id,
@@ -188,24 +258,7 @@ impl AsyncDiff {
 			)?,
 		};
 
-		let mut notify = false;
-		{
-			let mut current = arc_current.lock()?;
-			if current.0 == hash {
-				current.1 = Some(res.clone());
-				notify = true;
-			}
-		}
-
-		{
-			let mut last = arc_last.lock()?;
-			*last = Some(LastResult {
-				result: res,
-				params,
-			});
-		}
-
-		Ok(notify)
+		Ok(res)
 	}
 
 	fn get_last_param(&self) -> Result<Option<DiffParams>> {
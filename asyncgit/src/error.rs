@@ -77,6 +77,78 @@ pub enum Error {
 	#[error("not on a branch")]
 	NoBranch,
 
+	///
+	#[error("invalid branch name: `{0}`")]
+	InvalidBranchName(String),
+
+	///
+	#[error("a branch named `{0}` already exists")]
+	BranchAlreadyExists(String),
+
+	///
+	#[error("cannot delete the checked out branch `{0}`")]
+	DeleteCheckedOutBranch(String),
+
+	///
+	#[error("branch `{0}` is not fully merged")]
+	BranchNotMerged(String),
+
+	///
+	#[error("no common history with `{0}`")]
+	NoMergeBase(String),
+
+	///
+	#[error("invalid tag name: `{0}`")]
+	InvalidTagName(String),
+
+	///
+	#[error("a tag named `{0}` already exists")]
+	TagAlreadyExists(String),
+
+	///
+	#[error("no tag named `{0}`")]
+	TagNotFound(String),
+
+	/// the remote refused to update the ref (first field) with a reason
+	#[error("git: push to `{0}` rejected: {1}")]
+	PushRejected(String, String),
+
+	///
+	#[error("git: not possible to fast-forward")]
+	NotFastForward,
+
+	///
+	#[error("git: reverting merge commit {0} is not supported")]
+	RevertMergeCommit(crate::sync::CommitId),
+
+	/// the remote branch moved since it was last fetched, a force push
+	/// would drop the commits pushed in between
+	#[error(
+		"git: `{reference}` is at {} on the remote but was last fetched at {}, fetch first",
+		.remote.as_ref().map_or_else(|| String::from("none"), crate::sync::CommitId::get_short_string),
+		.fetched.as_ref().map_or_else(|| String::from("none"), crate::sync::CommitId::get_short_string)
+	)]
+	StaleRemoteRef {
+		///
+		reference: String,
+		/// remote tracking branch
+		fetched: Option<crate::sync::CommitId>,
+		/// what the remote reports
+		remote: Option<crate::sync::CommitId>,
+	},
+
+	///
+	#[error("invalid remote name: `{0}`")]
+	InvalidRemoteName(String),
+
+	///
+	#[error("a remote named `{0}` already exists")]
+	RemoteAlreadyExists(String),
+
+	///
+	#[error("invalid remote url: `{0}`")]
+	InvalidRemoteUrl(String),
+
 	///
 	#[error("rayon error: {0}")]
 	ThreadPool(#[from] rayon_core::ThreadPoolBuildError),
@@ -111,6 +183,36 @@ pub enum Error {
 	#[error("gix::revision::walk error: {0}")]
 	GixRevisionWalk(#[from] gix::revision::walk::Error),
 
+	///
+	#[error("git: patch does not apply: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+	PatchDoesNotApply(Vec<crate::sync::diff::FailedHunk>),
+
+	///
+	#[error("git: nothing to stage: `{0}`")]
+	NothingToStage(String),
+
+	///
+	#[error("git: empty commit message")]
+	EmptyCommitMessage,
+
+	///
+	#[error("git: nothing to commit")]
+	NothingToCommit,
+
+	///
+	#[error("git: no local changes to stash")]
+	NothingToStash,
+
+	///
+	#[error("git: local changes would be overwritten: {}", .0.join(", "))]
+	LocalChangesConflict(Vec<String>),
+
+	///
+	#[error(
+		"git: no identity, configure `user.email` (and `user.name`)"
+	)]
+	NoSignature,
+
 	///
 	#[error("amend error: config commit.gpgsign=true detected.\ngpg signing is not supported for amending non-last commits")]
 	SignAmendNonLastCommit,
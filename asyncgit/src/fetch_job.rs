@@ -3,12 +3,20 @@
 use crate::{
 	asyncjob::{AsyncJob, RunParams},
 	error::Result,
-	sync::remotes::fetch_all,
+	remote_progress::{
+		next_operation_id, OperationId, ProgressEvent,
+		ProgressThrottle,
+	},
+	sync::remotes::{fetch_remotes, push::AsyncProgress},
 	sync::{cred::BasicAuthCredential, RepoPath},
 	AsyncGitNotification, ProgressPercent,
 };
-
-use std::sync::{Arc, Mutex};
+use crossbeam_channel::unbounded;
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::Instant,
+};
 
 enum JobState {
 	Request(Option<BasicAuthCredential>),
@@ -20,6 +28,7 @@ enum JobState {
 pub struct AsyncFetchJob {
 	state: Arc<Mutex<Option<JobState>>>,
 	repo: RepoPath,
+	operation: OperationId,
 }
 
 ///
@@ -34,6 +43,41 @@ impl AsyncFetchJob {
 			state: Arc::new(Mutex::new(Some(JobState::Request(
 				basic_credential,
 			)))),
+			operation: next_operation_id(),
+		}
+	}
+
+	/// id of this fetch, see `ProgressEvent`
+	pub const fn operation(&self) -> OperationId {
+		self.operation
+	}
+
+	fn update_progress(
+		params: &RunParams<AsyncGitNotification, ProgressPercent>,
+		new_progress: ProgressPercent,
+	) {
+		match params.set_progress(new_progress) {
+			Err(e) => log::error!("progress error: {e}"),
+			Ok(result) if result => {
+				if let Err(e) =
+					params.send(AsyncGitNotification::Fetch)
+				{
+					log::error!("send error: {e}");
+				}
+			}
+			_ => (),
+		}
+	}
+
+	fn send_event(
+		params: &RunParams<AsyncGitNotification, ProgressPercent>,
+		operation: OperationId,
+		event: ProgressEvent,
+	) {
+		if let Err(e) = params.send(
+			AsyncGitNotification::RemoteProgress(operation, event),
+		) {
+			log::error!("send error: {e}");
 		}
 	}
 }
@@ -44,16 +88,53 @@ impl AsyncJob for AsyncFetchJob {
 
 	fn run(
 		&mut self,
-		_params: RunParams<Self::Notification, Self::Progress>,
+		params: RunParams<Self::Notification, Self::Progress>,
 	) -> Result<Self::Notification> {
 		if let Ok(mut state) = self.state.lock() {
 			*state = state.take().map(|state| match state {
 				JobState::Request(basic_credentials) => {
-					//TODO: support progress
-					let result = fetch_all(
-						&self.repo,
-						&basic_credentials,
-						&None,
+					let (sender, receiver) = unbounded();
+
+					let operation = self.operation;
+
+					let result = thread::scope(|s| {
+						s.spawn(|| {
+							let mut throttle =
+								ProgressThrottle::default();
+							for notification in &receiver {
+								Self::update_progress(
+									&params,
+									notification.progress(),
+								);
+
+								if let Some(event) = notification
+									.progress_event()
+									.filter(|event| {
+										throttle.pass(
+											event,
+											Instant::now(),
+										)
+									}) {
+									Self::send_event(
+										&params, operation, event,
+									);
+								}
+							}
+						});
+
+						fetch_remotes(
+							&self.repo,
+							None,
+							basic_credentials,
+							Some(sender),
+						)
+						.map(|_| ())
+					});
+
+					Self::send_event(
+						&params,
+						operation,
+						ProgressEvent::from_result(&result),
 					);
 
 					JobState::Response(result)
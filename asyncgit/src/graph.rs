@@ -0,0 +1,240 @@
+//! lanes of the commit graph drawn next to the log
+//!
+//! rows are computed one commit at a time (newest first, in the order
+//! of the log), so the graph can be extended as more of the log is
+//! walked.
+
+use crate::sync::CommitId;
+
+/// default limit of lanes tracked at the same time
+pub const MAX_LANES: usize = 32;
+
+/// line segment of a single graph row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphEdge {
+	/// lane passes this row without touching its commit
+	Continue(usize),
+	/// lane from above ends in this row's commit
+	/// (the commit is the parent of a branch that forked off here)
+	Fork(usize),
+	/// line from this row's commit to a parent tracked in this lane
+	/// (the commit is a merge)
+	Merge(usize),
+}
+
+/// graph of a single commit of the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+	/// lane of the commit
+	pub lane: usize,
+	/// `Continue` and `Fork` edges ordered by lane, followed by `Merge`
+	/// edges in parent order
+	pub edges: Vec<GraphEdge>,
+}
+
+/// incremental lane assignment, see `next_row`
+#[derive(Debug, Clone)]
+pub struct CommitGraph {
+	/// commit each lane is waiting for
+	lanes: Vec<Option<CommitId>>,
+	max_lanes: usize,
+}
+
+impl Default for CommitGraph {
+	fn default() -> Self {
+		Self::new(MAX_LANES)
+	}
+}
+
+impl CommitGraph {
+	/// at most `max_lanes` (at least one) are tracked, parents not
+	/// fitting in start a new lane once they show up in the log
+	pub fn new(max_lanes: usize) -> Self {
+		Self {
+			lanes: Vec::new(),
+			max_lanes: max_lanes.max(1),
+		}
+	}
+
+	/// row of the next commit of the log
+	pub fn next_row(
+		&mut self,
+		id: CommitId,
+		parents: &[CommitId],
+	) -> GraphRow {
+		let lane = self
+			.lanes
+			.iter()
+			.position(|waiting| *waiting == Some(id))
+			.unwrap_or_else(|| self.free_lane_or_evict());
+
+		let mut edges = Vec::new();
+		for (idx, waiting) in self.lanes.iter_mut().enumerate() {
+			if idx == lane {
+				continue;
+			}
+
+			match waiting {
+				Some(waiting_id) if *waiting_id == id => {
+					edges.push(GraphEdge::Fork(idx));
+					*waiting = None;
+				}
+				Some(_) => edges.push(GraphEdge::Continue(idx)),
+				None => (),
+			}
+		}
+
+		self.lanes[lane] = parents.first().copied();
+
+		for parent in parents.iter().skip(1) {
+			let parent_lane = self
+				.lanes
+				.iter()
+				.position(|waiting| *waiting == Some(*parent))
+				.or_else(|| {
+					let free = self.free_lane()?;
+					self.lanes[free] = Some(*parent);
+					Some(free)
+				});
+
+			if let Some(parent_lane) = parent_lane {
+				edges.push(GraphEdge::Merge(parent_lane));
+			}
+		}
+
+		while self.lanes.last().is_some_and(Option::is_none) {
+			self.lanes.pop();
+		}
+
+		GraphRow { lane, edges }
+	}
+
+	/// rows of the next `commits` (with their parents) of the log
+	pub fn extend(
+		&mut self,
+		commits: &[(CommitId, Vec<CommitId>)],
+	) -> Vec<GraphRow> {
+		commits
+			.iter()
+			.map(|(id, parents)| self.next_row(*id, parents))
+			.collect()
+	}
+
+	/// number of lanes currently tracked
+	pub fn lanes(&self) -> usize {
+		self.lanes.len()
+	}
+
+	fn free_lane(&mut self) -> Option<usize> {
+		if let Some(idx) = self.lanes.iter().position(Option::is_none)
+		{
+			return Some(idx);
+		}
+
+		(self.lanes.len() < self.max_lanes).then(|| {
+			self.lanes.push(None);
+			self.lanes.len() - 1
+		})
+	}
+
+	/// drops the last lane if all are in use
+	fn free_lane_or_evict(&mut self) -> usize {
+		self.free_lane().unwrap_or_else(|| {
+			let last = self.lanes.len() - 1;
+			self.lanes[last] = None;
+			last
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{CommitGraph, GraphEdge, GraphRow};
+	use crate::sync::CommitId;
+	use git2::Oid;
+
+	fn id(n: u8) -> CommitId {
+		CommitId::new(Oid::from_bytes(&[n; 20]).unwrap())
+	}
+
+	fn row(lane: usize, edges: &[GraphEdge]) -> GraphRow {
+		GraphRow {
+			lane,
+			edges: edges.to_vec(),
+		}
+	}
+
+	/// ```text
+	/// u2        unmerged branch
+	/// | m3      merge of feature into main
+	/// | |\
+	/// | | f2
+	/// | m |     m2
+	/// u | |     u1
+	/// | | f1
+	/// m1        root
+	/// ```
+	fn log() -> Vec<(CommitId, Vec<CommitId>)> {
+		let (m1, m2, m3) = (id(1), id(2), id(3));
+		let (f1, f2) = (id(11), id(12));
+		let (u1, u2) = (id(21), id(22));
+
+		vec![
+			(u2, vec![u1]),
+			(m3, vec![m2, f2]),
+			(f2, vec![f1]),
+			(m2, vec![m1]),
+			(u1, vec![m1]),
+			(f1, vec![m1]),
+			(m1, vec![]),
+		]
+	}
+
+	#[test]
+	fn test_lanes() {
+		use GraphEdge::{Continue, Fork, Merge};
+
+		let mut graph = CommitGraph::default();
+		let rows = graph.extend(&log());
+
+		assert_eq!(
+			rows,
+			vec![
+				row(0, &[]),
+				row(1, &[Continue(0), Merge(2)]),
+				row(2, &[Continue(0), Continue(1)]),
+				row(1, &[Continue(0), Continue(2)]),
+				row(0, &[Continue(1), Continue(2)]),
+				row(2, &[Continue(0), Continue(1)]),
+				row(0, &[Fork(1), Fork(2)]),
+			]
+		);
+		assert_eq!(graph.lanes(), 0);
+	}
+
+	#[test]
+	fn test_incremental() {
+		let log = log();
+
+		let mut graph = CommitGraph::default();
+		let mut rows = graph.extend(&log[..3]);
+		rows.extend(graph.extend(&log[3..]));
+
+		assert_eq!(rows, CommitGraph::default().extend(&log));
+	}
+
+	#[test]
+	fn test_max_lanes() {
+		let mut graph = CommitGraph::new(2);
+
+		for row in graph.extend(&log()) {
+			assert!(row.lane < 2);
+			assert!(row.edges.iter().all(|edge| match edge {
+				GraphEdge::Continue(lane)
+				| GraphEdge::Fork(lane)
+				| GraphEdge::Merge(lane) => *lane < 2,
+			}));
+			assert!(graph.lanes() <= 2);
+		}
+	}
+}
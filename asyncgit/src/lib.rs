@@ -41,6 +41,7 @@ mod diff;
 mod error;
 mod fetch_job;
 mod filter_commits;
+pub mod graph;
 mod progress;
 mod pull;
 mod push;
@@ -65,11 +66,17 @@ pub use crate::{
 	pull::{AsyncPull, FetchRequest},
 	push::{AsyncPush, PushRequest},
 	push_tags::{AsyncPushTags, PushTagsRequest},
-	remote_progress::{RemoteProgress, RemoteProgressState},
+	remote_progress::{
+		OperationId, ProgressEvent, RemoteProgress,
+		RemoteProgressState,
+	},
 	revlog::{AsyncLog, FetchStatus},
 	status::{AsyncStatus, StatusParams},
 	sync::{
-		diff::{DiffLine, DiffLineType, FileDiff},
+		diff::{
+			DiffLine, DiffLineType, FileDiff, Hunk, HunkHeader,
+			HunkStage, LineEnding,
+		},
 		remotes::push::PushType,
 		status::{StatusItem, StatusItemType},
 	},
@@ -117,6 +124,8 @@ pub enum AsyncGitNotification {
 	TreeFiles,
 	///
 	CommitFilter,
+	/// progress of the remote operation with the given id
+	RemoteProgress(OperationId, ProgressEvent),
 }
 
 /// helper function to calculate the hash of an arbitrary type that implements the `Hash` trait
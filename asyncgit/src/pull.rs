@@ -1,5 +1,8 @@
 use crate::{
 	error::{Error, Result},
+	remote_progress::{
+		next_operation_id, OperationId, ProgressEvent,
+	},
 	sync::{
 		cred::BasicAuthCredential,
 		remotes::{fetch, push::ProgressNotification},
@@ -24,9 +27,10 @@ pub struct FetchRequest {
 	pub basic_credential: Option<BasicAuthCredential>,
 }
 
-//TODO: since this is empty we can go with a simple AtomicBool to mark that we are fetching or not
 #[derive(Default, Clone, Debug)]
-struct FetchState {}
+struct FetchState {
+	operation: OperationId,
+}
 
 ///
 pub struct AsyncPull {
@@ -64,6 +68,12 @@ impl AsyncPull {
 		Ok(res.clone())
 	}
 
+	/// id of the pending request, see `ProgressEvent`
+	pub fn operation(&self) -> Result<Option<OperationId>> {
+		let state = self.state.lock()?;
+		Ok(state.as_ref().map(|state| state.operation))
+	}
+
 	///
 	pub fn progress(&self) -> Result<Option<RemoteProgress>> {
 		let res = self.progress.lock()?;
@@ -78,7 +88,7 @@ impl AsyncPull {
 			return Ok(());
 		}
 
-		self.set_request(&params)?;
+		let operation = self.set_request(&params)?;
 		RemoteProgress::set_progress(&self.progress, None)?;
 
 		let arc_state = Arc::clone(&self.state);
@@ -95,6 +105,7 @@ impl AsyncPull {
 				sender.clone(),
 				receiver,
 				arc_progress,
+				Some(operation),
 			);
 
 			let res = fetch(
@@ -110,6 +121,13 @@ impl AsyncPull {
 
 			handle.join().expect("joining thread failed");
 
+			sender
+				.send(AsyncGitNotification::RemoteProgress(
+					operation,
+					ProgressEvent::from_result(&res),
+				))
+				.expect("AsyncNotification error");
+
 			Self::set_result(&arc_res, res).expect("result error");
 
 			Self::clear_request(&arc_state).expect("clear error");
@@ -122,16 +140,20 @@ impl AsyncPull {
 		Ok(())
 	}
 
-	fn set_request(&self, _params: &FetchRequest) -> Result<()> {
+	fn set_request(
+		&self,
+		_params: &FetchRequest,
+	) -> Result<OperationId> {
 		let mut state = self.state.lock()?;
 
 		if state.is_some() {
 			return Err(Error::Generic("pending request".into()));
 		}
 
-		*state = Some(FetchState {});
+		let operation = next_operation_id();
+		*state = Some(FetchState { operation });
 
-		Ok(())
+		Ok(operation)
 	}
 
 	fn clear_request(
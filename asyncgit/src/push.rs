@@ -1,5 +1,8 @@
 use crate::{
 	error::{Error, Result},
+	remote_progress::{
+		next_operation_id, OperationId, ProgressEvent,
+	},
 	sync::{
 		cred::BasicAuthCredential,
 		remotes::push::push_raw,
@@ -27,13 +30,17 @@ pub struct PushRequest {
 	pub force: bool,
 	///
 	pub delete: bool,
+	/// make `remote/branch` the upstream of a pushed branch that has
+	/// none yet
+	pub set_upstream: bool,
 	///
 	pub basic_credential: Option<BasicAuthCredential>,
 }
 
-//TODO: since this is empty we can go with a simple AtomicBool to mark that we are fetching or not
 #[derive(Default, Clone, Debug)]
-struct PushState {}
+struct PushState {
+	operation: OperationId,
+}
 
 ///
 pub struct AsyncPush {
@@ -71,6 +78,12 @@ impl AsyncPush {
 		Ok(res.clone())
 	}
 
+	/// id of the pending request, see `ProgressEvent`
+	pub fn operation(&self) -> Result<Option<OperationId>> {
+		let state = self.state.lock()?;
+		Ok(state.as_ref().map(|state| state.operation))
+	}
+
 	///
 	pub fn progress(&self) -> Result<Option<RemoteProgress>> {
 		let res = self.progress.lock()?;
@@ -85,7 +98,7 @@ impl AsyncPush {
 			return Ok(());
 		}
 
-		self.set_request(&params)?;
+		let operation = self.set_request(&params)?;
 		RemoteProgress::set_progress(&self.progress, None)?;
 
 		let arc_state = Arc::clone(&self.state);
@@ -102,6 +115,7 @@ impl AsyncPush {
 				sender.clone(),
 				receiver,
 				arc_progress,
+				Some(operation),
 			);
 
 			let res = push_raw(
@@ -111,6 +125,7 @@ impl AsyncPush {
 				params.push_type,
 				params.force,
 				params.delete,
+				params.set_upstream,
 				params.basic_credential.clone(),
 				Some(progress_sender.clone()),
 			);
@@ -121,6 +136,13 @@ impl AsyncPush {
 
 			handle.join().expect("joining thread failed");
 
+			sender
+				.send(AsyncGitNotification::RemoteProgress(
+					operation,
+					ProgressEvent::from_result(&res),
+				))
+				.expect("AsyncNotification error");
+
 			Self::set_result(&arc_res, res).expect("result error");
 
 			Self::clear_request(&arc_state).expect("clear error");
@@ -133,16 +155,20 @@ impl AsyncPush {
 		Ok(())
 	}
 
-	fn set_request(&self, _params: &PushRequest) -> Result<()> {
+	fn set_request(
+		&self,
+		_params: &PushRequest,
+	) -> Result<OperationId> {
 		let mut state = self.state.lock()?;
 
 		if state.is_some() {
 			return Err(Error::Generic("pending request".into()));
 		}
 
-		*state = Some(PushState {});
+		let operation = next_operation_id();
+		*state = Some(PushState { operation });
 
-		Ok(())
+		Ok(operation)
 	}
 
 	fn clear_request(
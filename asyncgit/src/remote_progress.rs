@@ -9,10 +9,129 @@ use crate::{
 use crossbeam_channel::{Receiver, Sender};
 use git2::PackBuilderStage;
 use std::{
-	sync::{Arc, Mutex},
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
 	thread::{self, JoinHandle},
+	time::{Duration, Instant},
 };
 
+/// max `ProgressEvent`s per second sent for a single operation
+pub const PROGRESS_EVENTS_PER_SEC: u32 = 30;
+
+/// id of a running remote operation, see `ProgressEvent`
+pub type OperationId = u64;
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// unique id for a new remote operation
+pub(crate) fn next_operation_id() -> OperationId {
+	NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// progress of a remote operation (fetch/push/pull), sent as
+/// `AsyncGitNotification::RemoteProgress` with the id of the operation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+	/// objects received while fetching
+	Objects {
+		///
+		received: usize,
+		///
+		total: usize,
+		/// bytes received so far
+		bytes: usize,
+	},
+	/// deltas resolved once all objects are received
+	Deltas {
+		///
+		resolved: usize,
+		///
+		total: usize,
+	},
+	/// objects written while pushing
+	PushWrites {
+		///
+		current: usize,
+		///
+		total: usize,
+		/// bytes written so far
+		bytes: usize,
+	},
+	/// the operation succeeded
+	Done,
+	/// the operation failed, the error is reported by the job
+	Failed,
+}
+
+impl ProgressEvent {
+	/// `Done` or `Failed` are the last event of an operation
+	pub const fn is_terminal(&self) -> bool {
+		matches!(self, Self::Done | Self::Failed)
+	}
+
+	/// terminal event of an operation ending with `res`
+	pub(crate) const fn from_result<T>(res: &Result<T>) -> Self {
+		if res.is_ok() {
+			Self::Done
+		} else {
+			Self::Failed
+		}
+	}
+}
+
+/// drops progress events coming in faster than the limit so a fast
+/// local fetch does not flood the notification channel, terminal
+/// events always pass
+#[derive(Debug, Clone)]
+pub(crate) struct ProgressThrottle {
+	interval: Duration,
+	last: Option<Instant>,
+}
+
+impl Default for ProgressThrottle {
+	fn default() -> Self {
+		Self::new(PROGRESS_EVENTS_PER_SEC)
+	}
+}
+
+impl ProgressThrottle {
+	///
+	pub fn new(per_sec: u32) -> Self {
+		Self {
+			interval: Duration::from_secs(1) / per_sec.max(1),
+			last: None,
+		}
+	}
+
+	/// whether `event` arriving at `now` should be sent
+	pub fn pass(
+		&mut self,
+		event: &ProgressEvent,
+		now: Instant,
+	) -> bool {
+		self.due(event.is_terminal(), now)
+	}
+
+	/// whether an update arriving at `now` should be sent, `terminal`
+	/// ones always are
+	pub fn due(&mut self, terminal: bool, now: Instant) -> bool {
+		if terminal {
+			return true;
+		}
+
+		if self.last.is_some_and(|last| {
+			now.saturating_duration_since(last) < self.interval
+		}) {
+			return false;
+		}
+
+		self.last = Some(now);
+		true
+	}
+}
+
 /// used for push/pull
 #[derive(Clone, Debug)]
 pub enum RemoteProgressState {
@@ -66,7 +185,10 @@ impl RemoteProgress {
 		Ok(())
 	}
 
-	/// spawn thread to listen to progress notifications coming in from blocking remote git method (fetch/push)
+	/// spawn thread to listen to progress notifications coming in from blocking remote git method (fetch/push).
+	/// `notification_type` is sent throttled (the latest progress is
+	/// always stored), with an `operation` id its `ProgressEvent`s are
+	/// sent along with it
 	pub(crate) fn spawn_receiver_thread<
 		T: 'static + AsyncProgress,
 	>(
@@ -74,7 +196,9 @@ impl RemoteProgress {
 		sender: Sender<AsyncGitNotification>,
 		receiver: Receiver<T>,
 		progress: Arc<Mutex<Option<T>>>,
+		operation: Option<OperationId>,
 	) -> JoinHandle<()> {
+		let mut throttle = ProgressThrottle::default();
 		thread::spawn(move || loop {
 			let incoming = receiver.recv();
 			match incoming {
@@ -84,9 +208,25 @@ impl RemoteProgress {
 						Some(update.clone()),
 					)
 					.expect("set progress failed");
-					sender
-						.send(notification_type)
-						.expect("Notification error");
+
+					if throttle.due(update.is_done(), Instant::now())
+					{
+						sender
+							.send(notification_type)
+							.expect("Notification error");
+
+						if let (Some(operation), Some(event)) =
+							(operation, update.progress_event())
+						{
+							sender
+								.send(
+									AsyncGitNotification::RemoteProgress(
+										operation, event,
+									),
+								)
+								.expect("Notification error");
+						}
+					}
 
 					thread::yield_now();
 
@@ -147,3 +287,89 @@ impl From<ProgressNotification> for RemoteProgress {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn transfer(
+		objects: usize,
+		indexed_deltas: usize,
+		total_deltas: usize,
+	) -> ProgressNotification {
+		ProgressNotification::Transfer {
+			objects,
+			total_objects: 10,
+			received_bytes: objects * 100,
+			indexed_deltas,
+			total_deltas,
+		}
+	}
+
+	#[test]
+	fn test_progress_events() {
+		assert_eq!(
+			transfer(3, 0, 4).progress_event(),
+			Some(ProgressEvent::Objects {
+				received: 3,
+				total: 10,
+				bytes: 300
+			})
+		);
+		assert_eq!(
+			transfer(10, 1, 4).progress_event(),
+			Some(ProgressEvent::Deltas {
+				resolved: 1,
+				total: 4
+			})
+		);
+		assert_eq!(
+			ProgressNotification::PushTransfer {
+				current: 1,
+				total: 2,
+				bytes: 3
+			}
+			.progress_event(),
+			Some(ProgressEvent::PushWrites {
+				current: 1,
+				total: 2,
+				bytes: 3
+			})
+		);
+		assert_eq!(ProgressNotification::Done.progress_event(), None);
+
+		assert_eq!(
+			ProgressEvent::from_result(&Ok(())),
+			ProgressEvent::Done
+		);
+		assert_eq!(
+			ProgressEvent::from_result::<()>(&Err(
+				crate::Error::NoHead
+			)),
+			ProgressEvent::Failed
+		);
+	}
+
+	#[test]
+	fn test_throttle() {
+		let mut throttle = ProgressThrottle::new(10);
+		let start = Instant::now();
+		let event = transfer(1, 0, 0).progress_event().unwrap();
+		let at = |millis| start + Duration::from_millis(millis);
+
+		assert!(throttle.pass(&event, at(0)));
+		assert!(!throttle.pass(&event, at(50)));
+		assert!(!throttle.pass(&event, at(99)));
+		assert!(throttle.pass(&event, at(100)));
+		assert!(!throttle.pass(&event, at(150)));
+
+		// terminal events are never dropped
+		assert!(throttle.pass(&ProgressEvent::Done, at(151)));
+		assert!(throttle.pass(&ProgressEvent::Failed, at(152)));
+
+		let passed = (0..1000)
+			.filter(|millis| throttle.pass(&event, at(1000 + millis)))
+			.count();
+		assert_eq!(passed, 10);
+	}
+}
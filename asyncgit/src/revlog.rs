@@ -10,7 +10,7 @@ use crossbeam_channel::Sender;
 use scopetime::scope_time;
 use std::{
 	sync::{
-		atomic::{AtomicBool, Ordering},
+		atomic::{AtomicBool, AtomicUsize, Ordering},
 		Arc, Mutex,
 	},
 	thread,
@@ -42,11 +42,16 @@ pub struct AsyncLog {
 	sender: Sender<AsyncGitNotification>,
 	pending: Arc<AtomicBool>,
 	background: Arc<AtomicBool>,
+	generation: Arc<AtomicUsize>,
 	filter: Option<SharedCommitFilterFn>,
 	partial_extract: AtomicBool,
 	repo: RepoPath,
 }
 
+/// shared counter of the latest walk and the walk it is compared to,
+/// a walk is cancelled once they differ
+type Generation<'a> = (&'a Arc<AtomicUsize>, usize);
+
 static LIMIT_COUNT: usize = 3000;
 static SLEEP_FOREGROUND: Duration = Duration::from_millis(2);
 static SLEEP_BACKGROUND: Duration = Duration::from_millis(1000);
@@ -68,6 +73,7 @@ impl AsyncLog {
 			sender: sender.clone(),
 			pending: Arc::new(AtomicBool::new(false)),
 			background: Arc::new(AtomicBool::new(false)),
+			generation: Arc::new(AtomicUsize::new(0)),
 			filter,
 			partial_extract: AtomicBool::new(false),
 		}
@@ -130,6 +136,19 @@ impl AsyncLog {
 		self.background.store(true, Ordering::Relaxed);
 	}
 
+	/// stops a running walk (e.g. when its tab is left), the next
+	/// `fetch` starts a new one
+	pub fn cancel(&self) -> Result<()> {
+		let mut current = self.current.lock()?;
+		self.generation.fetch_add(1, Ordering::Relaxed);
+		self.pending.store(false, Ordering::Relaxed);
+		current.commits.clear();
+		drop(current);
+
+		*self.current_head.lock()? = None;
+		Ok(())
+	}
+
 	///
 	fn current_head(&self) -> Result<Option<CommitId>> {
 		Ok(*self.current_head.lock()?)
@@ -145,19 +164,25 @@ impl AsyncLog {
 		Ok(false)
 	}
 
-	///
+	/// starts a new walk if HEAD changed, a walk of an outdated HEAD
+	/// that is still running gets cancelled
 	pub fn fetch(&self) -> Result<FetchStatus> {
 		self.background.store(false, Ordering::Relaxed);
 
-		if self.is_pending() {
-			return Ok(FetchStatus::Pending);
-		}
-
 		if !self.head_changed()? {
-			return Ok(FetchStatus::NoChange);
+			return Ok(if self.is_pending() {
+				FetchStatus::Pending
+			} else {
+				FetchStatus::NoChange
+			});
 		}
 
+		let mut current = self.current.lock()?;
 		self.pending.store(true, Ordering::Relaxed);
+		let generation =
+			self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+		current.commits.clear();
+		drop(current);
 
 		self.clear()?;
 
@@ -165,6 +190,7 @@ impl AsyncLog {
 		let sender = self.sender.clone();
 		let arc_pending = Arc::clone(&self.pending);
 		let arc_background = Arc::clone(&self.background);
+		let arc_generation = Arc::clone(&self.generation);
 		let filter = self.filter.clone();
 		let repo_path = self.repo.clone();
 
@@ -180,12 +206,18 @@ impl AsyncLog {
 				&repo_path,
 				&arc_current,
 				&arc_background,
+				(&arc_generation, generation),
 				&sender,
 				filter,
 			)
 			.expect("failed to fetch");
 
+			let current = arc_current.lock().expect("lock poisoned");
+			if Self::is_cancelled((&arc_generation, generation)) {
+				return;
+			}
 			arc_pending.store(false, Ordering::Relaxed);
+			drop(current);
 
 			Self::notify(&sender);
 		});
@@ -197,6 +229,7 @@ impl AsyncLog {
 		repo_path: &RepoPath,
 		arc_current: &Arc<Mutex<AsyncLogResult>>,
 		arc_background: &Arc<AtomicBool>,
+		generation: Generation<'_>,
 		sender: &Sender<AsyncGitNotification>,
 		filter: Option<SharedCommitFilterFn>,
 	) -> Result<()> {
@@ -206,6 +239,7 @@ impl AsyncLog {
 					repo_path,
 					arc_current,
 					arc_background,
+					generation,
 					sender,
 				)
 			},
@@ -214,6 +248,7 @@ impl AsyncLog {
 					repo_path,
 					arc_current,
 					arc_background,
+					generation,
 					sender,
 					filter,
 				)
@@ -225,6 +260,7 @@ impl AsyncLog {
 		repo_path: &RepoPath,
 		arc_current: &Arc<Mutex<AsyncLogResult>>,
 		arc_background: &Arc<AtomicBool>,
+		generation: Generation<'_>,
 		sender: &Sender<AsyncGitNotification>,
 		filter: SharedCommitFilterFn,
 	) -> Result<()> {
@@ -242,6 +278,9 @@ impl AsyncLog {
 			let read = walker.read(&mut entries)?;
 
 			let mut current = arc_current.lock()?;
+			if Self::is_cancelled(generation) {
+				return Ok(());
+			}
 			current.commits.extend(entries.iter());
 			current.duration = start_time.elapsed();
 
@@ -269,6 +308,7 @@ impl AsyncLog {
 		repo_path: &RepoPath,
 		arc_current: &Arc<Mutex<AsyncLogResult>>,
 		arc_background: &Arc<AtomicBool>,
+		generation: Generation<'_>,
 		sender: &Sender<AsyncGitNotification>,
 	) -> Result<()> {
 		let start_time = Instant::now();
@@ -287,6 +327,9 @@ impl AsyncLog {
 			let read = walker.read(&mut entries)?;
 
 			let mut current = arc_current.lock()?;
+			if Self::is_cancelled(generation) {
+				return Ok(());
+			}
 			current.commits.extend(entries.iter());
 			current.duration = start_time.elapsed();
 
@@ -311,12 +354,17 @@ impl AsyncLog {
 	}
 
 	fn clear(&self) -> Result<()> {
-		self.current.lock()?.commits.clear();
 		*self.current_head.lock()? = None;
 		self.partial_extract.store(false, Ordering::Relaxed);
 		Ok(())
 	}
 
+	fn is_cancelled(
+		(arc_generation, generation): Generation<'_>,
+	) -> bool {
+		arc_generation.load(Ordering::Relaxed) != generation
+	}
+
 	fn notify(sender: &Sender<AsyncGitNotification>) {
 		sender
 			.send(AsyncGitNotification::Log)
@@ -326,20 +374,151 @@ impl AsyncLog {
 
 #[cfg(test)]
 mod tests {
-	use std::sync::atomic::AtomicBool;
+	use std::sync::atomic::{AtomicBool, AtomicUsize};
 	use std::sync::{Arc, Mutex};
-	use std::time::Duration;
+	use std::time::{Duration, Instant};
+	use std::{fs::File, io::Write, path::Path, thread};
 
 	use crossbeam_channel::unbounded;
 	use serial_test::serial;
 	use tempfile::TempDir;
 
-	use crate::sync::tests::{debug_cmd_print, repo_init};
-	use crate::sync::RepoPath;
-	use crate::AsyncLog;
+	use crate::sync::tests::{
+		debug_cmd_print, repo_init, repo_init_empty,
+	};
+	use crate::sync::{commit, stage_add_file, CommitId, RepoPath};
+	use crate::{AsyncGitNotification, AsyncLog, FetchStatus};
 
 	use super::AsyncLogResult;
 
+	fn commit_n(repo_path: &RepoPath, n: usize) -> Vec<CommitId> {
+		let root = repo_path.gitpath();
+		let file_path = Path::new("foo");
+
+		(0..n)
+			.map(|i| {
+				File::create(root.join(file_path))
+					.unwrap()
+					.write_all(format!("{i}").as_bytes())
+					.unwrap();
+				stage_add_file(repo_path, file_path).unwrap();
+				commit(repo_path, &format!("commit{i}")).unwrap()
+			})
+			.collect()
+	}
+
+	fn wait_for(log: &AsyncLog) {
+		let start = Instant::now();
+		while log.is_pending() {
+			assert!(start.elapsed() < Duration::from_secs(10));
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	#[test]
+	#[serial]
+	fn test_fetch_newest_first() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		let mut ids = commit_n(&repo_path, 20);
+		ids.reverse();
+
+		let (tx_git, rx_git) = unbounded();
+		let log = AsyncLog::new(repo_path, &tx_git, None);
+
+		assert_eq!(log.fetch().unwrap(), FetchStatus::Started);
+		wait_for(&log);
+
+		assert_eq!(log.count().unwrap(), 20);
+		assert_eq!(log.get_items().unwrap(), ids);
+		assert_eq!(log.get_slice(18, 5).unwrap(), ids[18..]);
+		assert!(rx_git
+			.try_iter()
+			.any(|n| n == AsyncGitNotification::Log));
+
+		assert_eq!(log.fetch().unwrap(), FetchStatus::NoChange);
+	}
+
+	#[test]
+	#[serial]
+	fn test_fetch_batches() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		commit_n(&repo_path, 20);
+
+		let (tx_git, rx_git) = unbounded();
+		let arc_current = Arc::new(Mutex::new(AsyncLogResult {
+			commits: Vec::new(),
+			duration: Duration::default(),
+		}));
+
+		AsyncLog::fetch_helper_without_filter(
+			&repo_path,
+			&arc_current,
+			&Arc::new(AtomicBool::new(false)),
+			(&Arc::new(AtomicUsize::new(0)), 0),
+			&tx_git,
+		)
+		.unwrap();
+
+		// one notification per non empty batch
+		assert_eq!(rx_git.try_iter().count(), 1);
+		assert_eq!(arc_current.lock().unwrap().commits.len(), 20);
+
+		// cancelled walks add nothing
+		let arc_current = Arc::new(Mutex::new(AsyncLogResult {
+			commits: Vec::new(),
+			duration: Duration::default(),
+		}));
+
+		AsyncLog::fetch_helper_without_filter(
+			&repo_path,
+			&arc_current,
+			&Arc::new(AtomicBool::new(false)),
+			(&Arc::new(AtomicUsize::new(1)), 0),
+			&tx_git,
+		)
+		.unwrap();
+
+		assert_eq!(rx_git.try_iter().count(), 0);
+		assert!(arc_current.lock().unwrap().commits.is_empty());
+	}
+
+	#[test]
+	#[serial]
+	fn test_cancel_and_restart() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		commit_n(&repo_path, 20);
+
+		let (tx_git, _rx_git) = unbounded();
+		let log = AsyncLog::new(repo_path.clone(), &tx_git, None);
+
+		assert_eq!(log.fetch().unwrap(), FetchStatus::Started);
+		log.cancel().unwrap();
+		assert!(!log.is_pending());
+
+		assert_eq!(log.fetch().unwrap(), FetchStatus::Started);
+		wait_for(&log);
+		assert_eq!(log.count().unwrap(), 20);
+
+		// new HEAD restarts the walk
+		let new_head = commit_n(&repo_path, 1)[0];
+		assert_eq!(log.fetch().unwrap(), FetchStatus::Started);
+		wait_for(&log);
+		assert_eq!(log.count().unwrap(), 21);
+		assert_eq!(log.get_slice(0, 1).unwrap(), vec![new_head]);
+	}
+
 	#[test]
 	#[serial]
 	fn test_smoke_in_subdir() {
@@ -366,6 +545,7 @@ mod tests {
 			&subdir_path,
 			&arc_current,
 			&arc_background,
+			(&Arc::new(AtomicUsize::new(0)), 0),
 			&tx_git,
 		);
 
@@ -397,6 +577,7 @@ mod tests {
 			&empty_path,
 			&arc_current,
 			&arc_background,
+			(&Arc::new(AtomicUsize::new(0)), 0),
 			&tx_git,
 		);
 
@@ -2,9 +2,11 @@ use crate::{
 	error::Result,
 	hash,
 	sync::{
-		self, status::StatusType, RepoPath, ShowUntrackedFilesConfig,
+		self,
+		status::{StatusOptions, StatusPass, StatusType},
+		RepoPath, ShowUntrackedFilesConfig,
 	},
-	AsyncGitNotification, StatusItem,
+	AsyncGitNotification, StatusItem, StatusItemType,
 };
 use crossbeam_channel::Sender;
 use std::{
@@ -25,7 +27,10 @@ fn current_tick() -> u128 {
 
 #[derive(Default, Hash, Clone)]
 pub struct Status {
+	/// the workdir side for a `StatusParams::split` fetch
 	pub items: Vec<StatusItem>,
+	/// only filled by a `StatusParams::split` fetch
+	pub stage: Vec<StatusItem>,
 }
 
 ///
@@ -34,6 +39,8 @@ pub struct StatusParams {
 	tick: u128,
 	status_type: StatusType,
 	config: Option<ShowUntrackedFilesConfig>,
+	pass: StatusPass,
+	split: bool,
 }
 
 impl StatusParams {
@@ -46,8 +53,26 @@ impl StatusParams {
 			tick: current_tick(),
 			status_type,
 			config,
+			pass: StatusPass::Full,
+			split: false,
 		}
 	}
+
+	/// stage and workdir status from a single walk of the worktree,
+	/// see `sync::status::get_status_both`
+	pub fn split(config: Option<ShowUntrackedFilesConfig>) -> Self {
+		Self {
+			split: true,
+			..Self::new(StatusType::Both, config)
+		}
+	}
+
+	/// a `StatusPass::Quick` fetch keeps the untracked files
+	/// of the previous result
+	#[must_use]
+	pub const fn pass(self, pass: StatusPass) -> Self {
+		Self { pass, ..self }
+	}
 }
 
 struct Request<R, A>(R, Option<A>);
@@ -120,8 +145,7 @@ impl AsyncStatus {
 		let arc_last = Arc::clone(&self.last);
 		let sender = self.sender.clone();
 		let arc_pending = Arc::clone(&self.pending);
-		let status_type = params.status_type;
-		let config = params.config;
+		let params = *params;
 		let repo = self.repo.clone();
 
 		self.pending.fetch_add(1, Ordering::Relaxed);
@@ -129,8 +153,7 @@ impl AsyncStatus {
 		rayon_core::spawn(move || {
 			if let Err(e) = Self::fetch_helper(
 				&repo,
-				status_type,
-				config,
+				params,
 				hash_request,
 				&arc_current,
 				&arc_last,
@@ -150,13 +173,42 @@ impl AsyncStatus {
 
 	fn fetch_helper(
 		repo: &RepoPath,
-		status_type: StatusType,
-		config: Option<ShowUntrackedFilesConfig>,
+		params: StatusParams,
 		hash_request: u64,
 		arc_current: &Arc<Mutex<Request<u64, Status>>>,
 		arc_last: &Arc<Mutex<Status>>,
 	) -> Result<()> {
-		let res = Self::get_status(repo, status_type, config)?;
+		let StatusParams {
+			status_type,
+			config,
+			pass,
+			split,
+			..
+		} = params;
+
+		let options = StatusOptions {
+			status_type,
+			show_untracked: config,
+			pass,
+			..StatusOptions::default()
+		};
+
+		let mut res = if split {
+			let (stage, items) =
+				sync::status::get_status_both(repo, &options)?;
+			Status { items, stage }
+		} else {
+			Status {
+				items: sync::status::get_status(repo, &options)?,
+				stage: Vec::new(),
+			}
+		};
+
+		if pass == StatusPass::Quick
+			&& (split || status_type == StatusType::WorkingDir)
+		{
+			Self::keep_untracked(&mut res, &arc_last.lock()?);
+		}
 		log::trace!(
 			"status fetched: {} (type: {:?})",
 			hash_request,
@@ -178,17 +230,22 @@ impl AsyncStatus {
 		Ok(())
 	}
 
-	fn get_status(
-		repo: &RepoPath,
-		status_type: StatusType,
-		config: Option<ShowUntrackedFilesConfig>,
-	) -> Result<Status> {
-		Ok(Status {
-			items: sync::status::get_status(
-				repo,
-				status_type,
-				config,
-			)?,
-		})
+	/// a quick pass does not see untracked files, carry them over
+	/// from `last` until the next full pass
+	fn keep_untracked(res: &mut Status, last: &Status) {
+		let untracked = last
+			.items
+			.iter()
+			.filter(|item| {
+				item.status == StatusItemType::New
+					&& !res.items.iter().any(|i| i.path == item.path)
+			})
+			.cloned()
+			.collect::<Vec<_>>();
+
+		if !untracked.is_empty() {
+			res.items.extend(untracked);
+			sync::status::sort_status_items(&mut res.items, false);
+		}
 	}
 }
@@ -5,7 +5,7 @@ use crate::{
 	error::{Error, Result},
 	sync::{get_commits_info, repository::repo},
 };
-use git2::BlameOptions;
+use git2::{BlameOptions, ErrorCode};
 use scopetime::scope_time;
 use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader};
@@ -53,7 +53,8 @@ fn fixup_windows_path(path: &str) -> String {
 	}
 }
 
-///
+/// blame of `file_path` as of `commit_id` (HEAD if `None`), pass the
+/// parent of a line's commit to look further back in its history
 pub fn blame_file(
 	repo_path: &RepoPath,
 	file_path: &str,
@@ -72,7 +73,16 @@ pub fn blame_file(
 	let spec =
 		format!("{}:{}", commit_id, fixup_windows_path(file_path));
 
-	let object = repo.revparse_single(&spec)?;
+	let object = repo.revparse_single(&spec).map_err(|e| {
+		if e.code() == ErrorCode::NotFound {
+			Error::Generic(format!(
+				"`{file_path}` does not exist in {}",
+				commit_id.get_short_string()
+			))
+		} else {
+			e.into()
+		}
+	})?;
 	let blob = repo.find_blob(object.id())?;
 
 	if blob.is_binary() {
@@ -151,7 +161,10 @@ mod tests {
 	use super::*;
 	use crate::{
 		error::Result,
-		sync::{commit, stage_add_file, tests::repo_init_empty},
+		sync::{
+			commit, stage_add_file,
+			tests::{repo_init_empty, write_commit_file},
+		},
 	};
 	use std::{
 		fs::{File, OpenOptions},
@@ -239,6 +252,69 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_blame_at_commit() -> Result<()> {
+		let (_td, repo) = repo_init_empty()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "foo", "a\nb\n", "c1");
+		let c2 =
+			write_commit_file(&repo, "foo", "a\nx\nb\ny\n", "c2");
+
+		let blame = blame_file(repo_path, "foo", None)?;
+		let lines = blame
+			.lines
+			.iter()
+			.map(|(hunk, line)| {
+				(hunk.as_ref().unwrap().commit_id, line.as_str())
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			lines,
+			vec![(c1, "a"), (c2, "x"), (c1, "b"), (c2, "y")]
+		);
+
+		// blame parent
+		let blame = blame_file(repo_path, "foo", Some(c1))?;
+		assert_eq!(blame.commit_id, c1);
+		let lines = blame
+			.lines
+			.iter()
+			.map(|(hunk, line)| {
+				(hunk.as_ref().unwrap().commit_id, line.as_str())
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(lines, vec![(c1, "a"), (c1, "b")]);
+
+		let c3 = write_commit_file(&repo, "bar", "bar", "c3");
+		assert!(matches!(
+			blame_file(repo_path, "bar", Some(c2)),
+			Err(Error::Generic(_))
+		));
+		assert!(blame_file(repo_path, "bar", Some(c3)).is_ok());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_blame_binary() -> Result<()> {
+		let (_td, repo) = repo_init_empty()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo", "a\0b", "c1");
+
+		assert!(matches!(
+			blame_file(repo_path, "foo", None),
+			Err(Error::NoBlameOnBinaryFile)
+		));
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_blame_windows_path_dividers() {
 		let file_path = Path::new("bar\\foo");
@@ -0,0 +1,157 @@
+//! preview of merging a branch into HEAD
+
+use crate::{
+	error::{Error, Result},
+	sync::{
+		commit_files::{get_compare_commits_diff, OldNew},
+		diff::{raw_diff_to_file_diffs, DiffOptions, FileDiff},
+		repository::repo,
+		utils::get_head_repo,
+		CommitId, RepoPath,
+	},
+};
+use git2::{ErrorCode, Sort};
+use scopetime::scope_time;
+
+/// what merging `branch_name` (local or remote branch) into HEAD would
+/// bring in: the commits only on the branch (newest first) and the
+/// diff of every file changed since the merge base, one `FileDiff` per
+/// file. both are empty if the branch is already merged
+pub fn diff_branch(
+	repo_path: &RepoPath,
+	branch_name: &str,
+	options: Option<DiffOptions>,
+) -> Result<(Vec<CommitId>, Vec<FileDiff>)> {
+	scope_time!("diff_branch");
+
+	let repo = repo(repo_path)?;
+
+	let head = get_head_repo(&repo)?;
+	let tip = CommitId::new(
+		repo.resolve_reference_from_short_name(branch_name)?
+			.peel_to_commit()?
+			.id(),
+	);
+
+	let base = match repo.merge_base(head.into(), tip.into()) {
+		Ok(base) => CommitId::new(base),
+		Err(e) if e.code() == ErrorCode::NotFound => {
+			return Err(Error::NoMergeBase(branch_name.to_string()));
+		}
+		Err(e) => return Err(e.into()),
+	};
+
+	let mut walk = repo.revwalk()?;
+	walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+	walk.push(tip.into())?;
+	walk.hide(head.into())?;
+	let commits = walk
+		.map(|id| id.map(CommitId::new))
+		.collect::<std::result::Result<Vec<_>, _>>()?;
+
+	let ids = OldNew {
+		old: base,
+		new: tip,
+	};
+	let diff = get_compare_commits_diff(&repo, ids, None, options)?;
+	let files = raw_diff_to_file_diffs(&repo, &diff, options)?;
+
+	Ok((commits, files))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::diff_branch;
+	use crate::{
+		error::Error,
+		sync::{
+			checkout_branch, create_branch, merge_commit,
+			tests::{repo_init, write_commit_file},
+			RepoPath,
+		},
+	};
+	use git2::Signature;
+
+	#[test]
+	fn test_diverged() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "a.txt", "base", "c1");
+		create_branch(repo_path, "feature").unwrap();
+		let f1 = write_commit_file(&repo, "a.txt", "feature", "f1");
+		let f2 = write_commit_file(&repo, "b.txt", "new", "f2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "c.txt", "master", "m1");
+
+		let (commits, files) =
+			diff_branch(repo_path, "feature", None).unwrap();
+
+		assert_eq!(commits, vec![f2, f1]);
+		// changes of master since the merge base are not part of it
+		assert_eq!(
+			files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+			vec!["a.txt", "b.txt"]
+		);
+		assert!(files.iter().all(|f| f.hunks.len() == 1));
+	}
+
+	#[test]
+	fn test_already_merged() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "old").unwrap();
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "a.txt", "a", "c1");
+
+		let (commits, files) =
+			diff_branch(repo_path, "old", None).unwrap();
+
+		assert!(commits.is_empty());
+		assert!(files.is_empty());
+
+		// merged by a merge commit
+		create_branch(repo_path, "feature").unwrap();
+		let f1 = write_commit_file(&repo, "b.txt", "b", "f1");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "c.txt", "c", "m1");
+		merge_commit(repo_path, "merge", &[f1]).unwrap();
+
+		let (commits, files) =
+			diff_branch(repo_path, "feature", None).unwrap();
+
+		assert!(commits.is_empty());
+		assert!(files.is_empty());
+	}
+
+	#[test]
+	fn test_unrelated() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let sig = Signature::now("name", "email").unwrap();
+		let tree_id = repo.index().unwrap().write_tree().unwrap();
+		let tree = repo.find_tree(tree_id).unwrap();
+		repo.commit(
+			Some("refs/heads/orphan"),
+			&sig,
+			&sig,
+			"orphan",
+			&tree,
+			&[],
+		)
+		.unwrap();
+
+		assert!(matches!(
+			diff_branch(repo_path, "orphan", None),
+			Err(Error::NoMergeBase(name)) if name == "orphan"
+		));
+	}
+}
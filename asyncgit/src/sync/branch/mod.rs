@@ -1,17 +1,16 @@
 //! branch functions
 
+pub mod compare;
 pub mod merge_commit;
 pub mod merge_ff;
 pub mod merge_rebase;
+pub mod pull;
 pub mod rename;
 
 use super::{utils::bytes2string, RepoPath};
 use crate::{
 	error::{Error, Result},
-	sync::{
-		remotes::get_default_remote_for_push_in_repo,
-		repository::repo, utils::get_head_repo, CommitId,
-	},
+	sync::{repository::repo, utils::get_head_repo, CommitId},
 };
 use git2::{Branch, BranchType, Repository};
 use scopetime::scope_time;
@@ -53,6 +52,11 @@ pub struct LocalBranch {
 	pub has_upstream: bool,
 	///
 	pub upstream: Option<UpstreamBranch>,
+	/// commits ahead/behind `upstream`
+	pub upstream_compare: Option<BranchCompare>,
+	/// an upstream is configured but its remote tracking branch does
+	/// not exist (anymore), e.g. after it was pruned
+	pub upstream_gone: bool,
 	///
 	pub remote: Option<String>,
 }
@@ -116,7 +120,8 @@ pub fn validate_branch_name(name: &str) -> Result<bool> {
 }
 
 /// returns a list of `BranchInfo` with a simple summary on each branch
-/// `local` filters for local branches otherwise remote branches will be returned
+/// `local` filters for local branches otherwise remote branches will be returned.
+/// the checked out branch comes first, the others are sorted by name
 pub fn get_branches_info(
 	repo_path: &RepoPath,
 	local: bool,
@@ -168,10 +173,32 @@ pub fn get_branches_info(
 				});
 
 			let details = if local {
+				let upstream_compare = upstream_branch
+					.as_ref()
+					.and_then(|upstream| {
+						repo.refname_to_id(&upstream.reference).ok()
+					})
+					.and_then(|upstream| {
+						repo.graph_ahead_behind(
+							top_commit.id(),
+							upstream,
+						)
+						.ok()
+					})
+					.map(|(ahead, behind)| BranchCompare {
+						ahead,
+						behind,
+					});
+
+				let upstream_gone = upstream_branch.is_none()
+					&& repo.branch_upstream_name(&reference).is_ok();
+
 				BranchDetails::Local(LocalBranch {
 					is_head: branch.is_head(),
 					has_upstream: upstream_branch.is_some(),
+					upstream_gone,
 					upstream: upstream_branch,
+					upstream_compare,
 					remote,
 				})
 			} else {
@@ -194,13 +221,22 @@ pub fn get_branches_info(
 		.filter_map(Result::ok)
 		.collect();
 
-	branches_for_display.sort_by(|a, b| a.name.cmp(&b.name));
+	let is_head = |branch: &BranchInfo| {
+		branch
+			.local_details()
+			.is_some_and(|details| details.is_head)
+	};
+	branches_for_display.sort_by(|a, b| {
+		is_head(b)
+			.cmp(&is_head(a))
+			.then_with(|| a.name.cmp(&b.name))
+	});
 
 	Ok(branches_for_display)
 }
 
 ///
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct BranchCompare {
 	///
 	pub ahead: usize,
@@ -208,9 +244,10 @@ pub struct BranchCompare {
 	pub behind: usize,
 }
 
-///
+/// sets `remote/branch_name` as upstream unless there already is one
 pub(crate) fn branch_set_upstream_after_push(
 	repo: &Repository,
+	remote: &str,
 	branch_name: &str,
 ) -> Result<()> {
 	scope_time!("branch_set_upstream");
@@ -219,7 +256,6 @@ pub(crate) fn branch_set_upstream_after_push(
 		repo.find_branch(branch_name, BranchType::Local)?;
 
 	if branch.upstream().is_err() {
-		let remote = get_default_remote_for_push_in_repo(repo)?;
 		let upstream_name = format!("{remote}/{branch_name}");
 		branch.set_upstream(Some(upstream_name.as_str()))?;
 	}
@@ -282,11 +318,49 @@ pub fn branch_compare_upstream(
 	Ok(BranchCompare { ahead, behind })
 }
 
+/// commits HEAD's branch is ahead and behind of its upstream,
+/// `None` if HEAD is detached, unborn or the branch has no upstream
+pub fn ahead_behind(
+	repo_path: &RepoPath,
+) -> Result<Option<(usize, usize)>> {
+	scope_time!("ahead_behind");
+
+	let repo = repo(repo_path)?;
+
+	let head = match repo.head() {
+		Ok(head) if head.is_branch() => head,
+		Ok(_) => return Ok(None),
+		Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+			return Ok(None)
+		}
+		Err(e) => return Err(e.into()),
+	};
+
+	let local = head.target();
+
+	let upstream = match Branch::wrap(head).upstream() {
+		Ok(upstream) => upstream,
+		Err(e) if e.code() == git2::ErrorCode::NotFound => {
+			return Ok(None)
+		}
+		Err(e) => return Err(e.into()),
+	};
+
+	let (Some(local), Some(upstream)) =
+		(local, upstream.into_reference().target())
+	else {
+		return Ok(None);
+	};
+
+	Ok(Some(repo.graph_ahead_behind(local, upstream)?))
+}
+
 /// Switch branch to given `branch_name`.
 ///
 /// Method will fail if there are conflicting changes between current and target branch. However,
 /// if files are not conflicting, they will remain in tree (e.g. tracked new file is not
 /// conflicting and therefore is kept in tree even after checkout).
+/// Conflicting changes fail with `Error::UncommittedChanges`.
 pub fn checkout_branch(
 	repo_path: &RepoPath,
 	branch_name: &str,
@@ -302,11 +376,19 @@ pub fn checkout_branch(
 	let target_treeish = branch_ref.peel_to_tree()?;
 	let target_treeish_object = target_treeish.as_object();
 
-	// modify state to match branch's state
+	// modify state to match branch's state, a safe checkout keeps local
+	// changes unless they touch files differing between the branches
 	repo.checkout_tree(
 		target_treeish_object,
 		Some(&mut git2::build::CheckoutBuilder::new()),
-	)?;
+	)
+	.map_err(|e| {
+		if e.code() == git2::ErrorCode::Conflict {
+			Error::UncommittedChanges
+		} else {
+			e.into()
+		}
+	})?;
 
 	let branch_ref = branch_ref.name().ok_or_else(|| {
 		Error::Generic(String::from("branch ref not found"))
@@ -348,7 +430,11 @@ pub fn checkout_commit(
 	}
 }
 
-///
+/// creates a local branch named like the remote `branch` without the
+/// remote prefix (`origin/feature` becomes `feature`), sets the remote
+/// branch as its upstream and checks it out (see `checkout_branch`).
+/// an existing local branch of that name is reused if it points to the
+/// same commit, otherwise `Error::BranchAlreadyExists` is returned
 pub fn checkout_remote_branch(
 	repo_path: &RepoPath,
 	branch: &BranchInfo,
@@ -356,59 +442,89 @@ pub fn checkout_remote_branch(
 	scope_time!("checkout_remote_branch");
 
 	let repo = repo(repo_path)?;
-	let cur_ref = repo.head()?;
 
-	if !repo
-		.statuses(Some(
-			git2::StatusOptions::new().include_ignored(false),
-		))?
-		.is_empty()
+	let remote =
+		bytes2string(&repo.branch_remote_name(&branch.reference)?)?;
+	let local_name = branch
+		.name
+		.strip_prefix(&format!("{remote}/"))
+		.ok_or_else(|| {
+			Error::Generic(format!(
+				"`{}` is not a branch of remote `{remote}`",
+				branch.name
+			))
+		})?;
+
+	let commit =
+		repo.find_reference(&branch.reference)?.peel_to_commit()?;
+
+	let (mut local_branch, created) = match repo
+		.find_branch(local_name, BranchType::Local)
 	{
-		return Err(Error::UncommittedChanges);
-	}
+		Ok(existing) => {
+			if existing.get().peel_to_commit()?.id() != commit.id() {
+				return Err(Error::BranchAlreadyExists(
+					local_name.to_string(),
+				));
+			}
+			(existing, false)
+		}
+		Err(_) => (repo.branch(local_name, &commit, false)?, true),
+	};
+
+	local_branch.set_upstream(Some(&branch.name))?;
 
-	let name = branch.name.find('/').map_or_else(
-		|| branch.name.clone(),
-		|pos| branch.name[pos..].to_string(),
-	);
-
-	let commit = repo.find_commit(branch.top_commit.into())?;
-	let mut new_branch = repo.branch(&name, &commit, false)?;
-	new_branch.set_upstream(Some(&branch.name))?;
-
-	repo.set_head(
-		bytes2string(new_branch.into_reference().name_bytes())?
-			.as_str(),
-	)?;
-
-	if let Err(e) = repo.checkout_head(Some(
-		git2::build::CheckoutBuilder::new().force(),
-	)) {
-		// This is safe because cur_ref was just found
-		repo.set_head(bytes2string(cur_ref.name_bytes())?.as_str())?;
-		return Err(Error::Git(e));
+	if let Err(e) = checkout_branch(repo_path, local_name) {
+		if created {
+			local_branch.delete()?;
+		}
+		return Err(e);
 	}
+
 	Ok(())
 }
 
-/// The user must not be on the branch for the branch to be deleted
+/// whether the commit `branch` points to is reachable from HEAD
+fn is_merged_into_head(
+	repo: &Repository,
+	branch: &Branch<'_>,
+) -> Result<bool> {
+	let target = branch.get().peel_to_commit()?.id();
+	let head = get_head_repo(repo)?.into();
+
+	Ok(head == target || repo.graph_descendant_of(head, target)?)
+}
+
+/// The user must not be on the branch for the branch to be deleted.
+/// Unless `force` is set (like `git branch -D`) the branch also has to
+/// be fully merged into HEAD, otherwise `Error::BranchNotMerged` is
+/// returned
 pub fn delete_branch(
 	repo_path: &RepoPath,
 	branch_ref: &str,
+	force: bool,
 ) -> Result<()> {
 	scope_time!("delete_branch");
 
 	let repo = repo(repo_path)?;
 	let branch_as_ref = repo.find_reference(branch_ref)?;
 	let mut branch = git2::Branch::wrap(branch_as_ref);
+	let name = bytes2string(branch.name_bytes()?)?;
+
 	if branch.is_head() {
-		return Err(Error::Generic("You cannot be on the branch you want to delete, switch branch, then delete this branch".to_string()));
+		return Err(Error::DeleteCheckedOutBranch(name));
 	}
+	if !force && !is_merged_into_head(&repo, &branch)? {
+		return Err(Error::BranchNotMerged(name));
+	}
+
 	branch.delete()?;
 	Ok(())
 }
 
 /// creates a new branch pointing to current HEAD commit and updating HEAD to new branch
+/// (also works on a detached HEAD, on an unborn HEAD only HEAD is
+/// pointed to the new branch). returns the full ref name
 pub fn create_branch(
 	repo_path: &RepoPath,
 	name: &str,
@@ -417,6 +533,22 @@ pub fn create_branch(
 
 	let repo = repo(repo_path)?;
 
+	if !Branch::name_is_valid(name)? {
+		return Err(Error::InvalidBranchName(name.to_string()));
+	}
+	if repo.find_branch(name, BranchType::Local).is_ok() {
+		return Err(Error::BranchAlreadyExists(name.to_string()));
+	}
+
+	// on an unborn branch there is nothing to point to yet,
+	// the branch gets created by the first commit
+	if matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch)
+	{
+		let branch_ref_name = format!("refs/heads/{name}");
+		repo.set_head(&branch_ref_name)?;
+		return Ok(branch_ref_name);
+	}
+
 	let head_id = get_head_repo(&repo)?;
 	let head_commit = repo.find_commit(head_id.into())?;
 
@@ -463,7 +595,9 @@ mod tests_branch_name {
 #[cfg(test)]
 mod tests_create_branch {
 	use super::*;
-	use crate::sync::tests::repo_init;
+	use crate::sync::tests::{
+		repo_init, repo_init_empty, write_commit_file,
+	};
 
 	#[test]
 	fn test_smoke() {
@@ -479,12 +613,107 @@ mod tests_create_branch {
 			"branch1"
 		);
 	}
+
+	#[test]
+	fn test_invalid_and_duplicate() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert!(matches!(
+			create_branch(repo_path, "a..b"),
+			Err(Error::InvalidBranchName(_))
+		));
+		assert!(matches!(
+			create_branch(repo_path, "master"),
+			Err(Error::BranchAlreadyExists(name)) if name == "master"
+		));
+	}
+
+	#[test]
+	fn test_unborn_and_detached() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "main").unwrap();
+		assert_eq!(
+			repo.find_reference("HEAD")
+				.unwrap()
+				.symbolic_target()
+				.unwrap(),
+			"refs/heads/main"
+		);
+
+		let c1 = write_commit_file(&repo, "a.txt", "a", "c1");
+		write_commit_file(&repo, "a.txt", "b", "c2");
+		checkout_commit(repo_path, c1).unwrap();
+
+		create_branch(repo_path, "from-detached").unwrap();
+		assert_eq!(
+			get_branch_name(repo_path).unwrap().as_str(),
+			"from-detached"
+		);
+		assert_eq!(get_head_repo(&repo).unwrap(), c1);
+	}
 }
 
 #[cfg(test)]
 mod tests_branch_compare {
 	use super::*;
-	use crate::sync::tests::repo_init;
+	use crate::sync::{
+		remotes::{fetch, push::push_branch},
+		tests::{
+			repo_clone, repo_init, repo_init_bare, write_commit_file,
+		},
+	};
+
+	#[test]
+	fn test_ahead_behind() {
+		let (r1_dir, _repo) = repo_init_bare().unwrap();
+
+		let (clone1_dir, clone1) =
+			repo_clone(r1_dir.path().to_str().unwrap()).unwrap();
+		let clone1_dir: &RepoPath =
+			&clone1_dir.path().to_str().unwrap().into();
+
+		assert_eq!(ahead_behind(clone1_dir).unwrap(), None);
+
+		let c1 = write_commit_file(&clone1, "test.txt", "1", "c1");
+		push_branch(
+			clone1_dir, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+
+		assert_eq!(ahead_behind(clone1_dir).unwrap(), Some((0, 0)));
+
+		let (clone2_dir, clone2) =
+			repo_clone(r1_dir.path().to_str().unwrap()).unwrap();
+		let clone2_dir: &RepoPath =
+			&clone2_dir.path().to_str().unwrap().into();
+
+		write_commit_file(&clone2, "test2.txt", "2", "c2");
+		push_branch(
+			clone2_dir, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+
+		write_commit_file(&clone1, "test.txt", "3", "c3");
+		write_commit_file(&clone1, "test.txt", "4", "c4");
+		fetch(clone1_dir, "master", None, None).unwrap();
+
+		assert_eq!(ahead_behind(clone1_dir).unwrap(), Some((2, 1)));
+
+		// no upstream
+		create_branch(clone1_dir, "foo").unwrap();
+		assert_eq!(ahead_behind(clone1_dir).unwrap(), None);
+
+		// detached
+		checkout_commit(clone1_dir, c1).unwrap();
+		assert_eq!(ahead_behind(clone1_dir).unwrap(), None);
+	}
 
 	#[test]
 	fn test_smoke() {
@@ -545,10 +774,80 @@ mod tests_branches {
 				.iter()
 				.map(|b| b.name.clone())
 				.collect::<Vec<_>>(),
-			vec!["master", "test"]
+			vec!["test", "master"]
 		);
 	}
 
+	#[test]
+	fn test_local_branch_metadata() {
+		let (r1_dir, _repo) = repo_init_bare().unwrap();
+		let (clone_dir, clone) =
+			repo_clone(r1_dir.path().to_str().unwrap()).unwrap();
+		let repo_path: &RepoPath =
+			&clone_dir.path().to_str().unwrap().into();
+
+		write_commit_file(&clone, "a.txt", "a", "c1");
+		push_branch(
+			repo_path, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+
+		// upstream gets deleted
+		create_branch(repo_path, "gone").unwrap();
+		push_branch(
+			repo_path, "origin", "gone", false, false, None, None,
+		)
+		.unwrap();
+		clone
+			.find_reference("refs/remotes/origin/gone")
+			.unwrap()
+			.delete()
+			.unwrap();
+
+		checkout_branch(repo_path, "master").unwrap();
+		create_branch(repo_path, "feature").unwrap();
+		let c2 = write_commit_file(&clone, "b.txt", "b", "c2");
+
+		checkout_branch(repo_path, "master").unwrap();
+		let c3 = write_commit_file(&clone, "c.txt", "c", "c3");
+
+		let branches = get_branches_info(repo_path, true).unwrap();
+
+		assert_eq!(
+			branches
+				.iter()
+				.map(|b| b.name.as_str())
+				.collect::<Vec<_>>(),
+			vec!["master", "feature", "gone"]
+		);
+
+		let master = branches[0].local_details().unwrap();
+		assert!(master.is_head);
+		assert_eq!(branches[0].top_commit, c3);
+		assert_eq!(branches[0].top_commit_message, "c3");
+		assert_eq!(
+			master.upstream.as_ref().unwrap().reference,
+			"refs/remotes/origin/master"
+		);
+		assert_eq!(
+			master.upstream_compare,
+			Some(BranchCompare {
+				ahead: 1,
+				behind: 0
+			})
+		);
+
+		let feature = branches[1].local_details().unwrap();
+		assert!(!feature.is_head);
+		assert_eq!(branches[1].top_commit, c2);
+		assert!(!feature.has_upstream);
+		assert_eq!(feature.upstream_compare, None);
+
+		let gone = branches[2].local_details().unwrap();
+		assert!(!gone.has_upstream);
+		assert_eq!(gone.upstream_compare, None);
+	}
+
 	fn clone_branch_commit_push(target: &str, branch_name: &str) {
 		let (dir, repo) = repo_clone(target).unwrap();
 		let dir = dir.path().to_str().unwrap();
@@ -615,16 +914,17 @@ mod tests_branches {
 			"git checkout --track r2/r2branch",
 		);
 
+		// checked out r2branch comes first
 		let branches = get_branches_info(repo_path, true).unwrap();
 		assert_eq!(branches.len(), 3);
 		assert_eq!(
-			branches[1]
+			branches[0]
 				.local_details()
 				.unwrap()
 				.remote
 				.as_ref()
 				.unwrap(),
-			"r1"
+			"r2"
 		);
 		assert_eq!(
 			branches[2]
@@ -633,7 +933,7 @@ mod tests_branches {
 				.remote
 				.as_ref()
 				.unwrap(),
-			"r2"
+			"r1"
 		);
 
 		assert_eq!(
@@ -678,9 +978,48 @@ mod tests_branches {
 #[cfg(test)]
 mod tests_checkout {
 	use super::*;
-	use crate::sync::{stage_add_file, tests::repo_init};
+	use crate::sync::{
+		reset_workdir_file, stage_add_file,
+		tests::{repo_init, write_commit_file},
+	};
 	use std::{fs::File, path::Path};
 
+	#[test]
+	fn test_conflicting_changes() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "a.txt", "a", "c1");
+		write_commit_file(&repo, "b.txt", "b", "c2");
+		create_branch(repo_path, "test").unwrap();
+		let c3 = write_commit_file(&repo, "a.txt", "test", "c3");
+		checkout_branch(repo_path, "master").unwrap();
+
+		// changes not touched by the checkout are kept
+		std::fs::write(root.join("b.txt"), "local").unwrap();
+		checkout_branch(repo_path, "test").unwrap();
+		assert_eq!(get_head_repo(&repo).unwrap(), c3);
+		assert_eq!(
+			std::fs::read_to_string(root.join("b.txt")).unwrap(),
+			"local"
+		);
+		checkout_branch(repo_path, "master").unwrap();
+
+		std::fs::write(root.join("a.txt"), "local").unwrap();
+		assert!(matches!(
+			checkout_branch(repo_path, "test"),
+			Err(Error::UncommittedChanges)
+		));
+		assert_eq!(get_branch_name(repo_path).unwrap(), "master");
+
+		reset_workdir_file(repo_path, "a.txt").unwrap();
+		checkout_branch(repo_path, "test").unwrap();
+		assert_eq!(get_branch_name(repo_path).unwrap(), "test");
+		assert_eq!(get_head_repo(&repo).unwrap(), c3);
+	}
+
 	#[test]
 	fn test_smoke() {
 		let (_td, repo) = repo_init().unwrap();
@@ -766,7 +1105,7 @@ mod tests_checkout_commit {
 #[cfg(test)]
 mod test_delete_branch {
 	use super::*;
-	use crate::sync::tests::repo_init;
+	use crate::sync::tests::{repo_init, write_commit_file};
 
 	#[test]
 	fn test_delete_branch() {
@@ -793,7 +1132,8 @@ mod test_delete_branch {
 			"branch2"
 		);
 
-		delete_branch(repo_path, "refs/heads/branch2").unwrap();
+		delete_branch(repo_path, "refs/heads/branch2", false)
+			.unwrap();
 
 		assert_eq!(
 			repo.branches(None)
@@ -808,6 +1148,49 @@ mod test_delete_branch {
 			"master"
 		);
 	}
+
+	#[test]
+	fn test_delete_unmerged() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "feature").unwrap();
+		write_commit_file(&repo, "a.txt", "a", "c1");
+		checkout_branch(repo_path, "master").unwrap();
+
+		assert!(matches!(
+			delete_branch(repo_path, "refs/heads/master", true),
+			Err(Error::DeleteCheckedOutBranch(name)) if name == "master"
+		));
+		assert!(matches!(
+			delete_branch(repo_path, "refs/heads/feature", false),
+			Err(Error::BranchNotMerged(name)) if name == "feature"
+		));
+		assert!(repo
+			.find_branch("feature", BranchType::Local)
+			.is_ok());
+
+		delete_branch(repo_path, "refs/heads/feature", true).unwrap();
+		assert!(repo
+			.find_branch("feature", BranchType::Local)
+			.is_err());
+	}
+
+	#[test]
+	fn test_delete_merged_behind_head() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "old").unwrap();
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "a.txt", "a", "c1");
+
+		delete_branch(repo_path, "refs/heads/old", false).unwrap();
+	}
 }
 
 #[cfg(test)]
@@ -1008,6 +1391,80 @@ mod test_remote_branches {
 		);
 	}
 
+	#[test]
+	fn test_checkout_remote_branch_tracking() {
+		let (r1_dir, _repo) = repo_init_bare().unwrap();
+
+		let (clone1_dir, clone1) =
+			repo_clone(r1_dir.path().to_str().unwrap()).unwrap();
+		let clone1_dir: &RepoPath =
+			&clone1_dir.path().to_str().unwrap().into();
+
+		write_commit_file(&clone1, "test.txt", "test", "commit1");
+		push_branch(
+			clone1_dir, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+		create_branch(clone1_dir, "feature-x").unwrap();
+		let tip = write_commit_file(
+			&clone1, "test.txt", "test2", "commit2",
+		);
+		push_branch(
+			clone1_dir,
+			"origin",
+			"feature-x",
+			false,
+			false,
+			None,
+			None,
+		)
+		.unwrap();
+
+		let (clone2_dir, clone2) =
+			repo_clone(r1_dir.path().to_str().unwrap()).unwrap();
+		let clone2_dir: &RepoPath =
+			&clone2_dir.path().to_str().unwrap().into();
+
+		let remote = get_branches_info(clone2_dir, false)
+			.unwrap()
+			.into_iter()
+			.find(|b| b.name == "origin/feature-x")
+			.unwrap();
+
+		checkout_remote_branch(clone2_dir, &remote).unwrap();
+
+		assert_eq!(get_branch_name(clone2_dir).unwrap(), "feature-x");
+		assert_eq!(get_head_repo(&clone2).unwrap(), tip);
+
+		let config = clone2.config().unwrap().snapshot().unwrap();
+		assert_eq!(
+			config.get_str("branch.feature-x.remote").unwrap(),
+			"origin"
+		);
+		assert_eq!(
+			config.get_str("branch.feature-x.merge").unwrap(),
+			"refs/heads/feature-x"
+		);
+
+		// same commit: the local branch is reused
+		checkout_branch(clone2_dir, "master").unwrap();
+		checkout_remote_branch(clone2_dir, &remote).unwrap();
+		assert_eq!(get_branch_name(clone2_dir).unwrap(), "feature-x");
+
+		// differently pointing local branch
+		checkout_branch(clone2_dir, "master").unwrap();
+		delete_branch(clone2_dir, "refs/heads/feature-x", true)
+			.unwrap();
+		create_branch(clone2_dir, "feature-x").unwrap();
+		checkout_branch(clone2_dir, "master").unwrap();
+
+		assert!(matches!(
+			checkout_remote_branch(clone2_dir, &remote),
+			Err(Error::BranchAlreadyExists(name)) if name == "feature-x"
+		));
+		assert_eq!(get_branch_name(clone2_dir).unwrap(), "master");
+	}
+
 	#[test]
 	fn test_has_tracking() {
 		let (r1_dir, _repo) = repo_init_bare().unwrap();
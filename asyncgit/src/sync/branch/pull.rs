@@ -0,0 +1,304 @@
+//! fetching and integrating the upstream of the current branch
+
+use super::{
+	config_is_pull_rebase, get_branch_name,
+	merge_commit::commit_merge_with_head,
+	merge_rebase::merge_upstream_rebase,
+};
+use crate::{
+	error::{Error, Result},
+	sync::{
+		cred::BasicAuthCredential,
+		merge::{merge_branch_repo, MergeOutcome},
+		merge_msg,
+		remotes::fetch,
+		repository::repo,
+		CommitId, RepoPath,
+	},
+};
+use git2::BranchType;
+use scopetime::scope_time;
+
+/// how `pull` integrated the upstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullOutcome {
+	/// nothing new upstream
+	UpToDate,
+	/// HEAD moved forward to the upstream commit
+	FastForward(CommitId),
+	/// histories diverged, a merge commit was created
+	Merged(CommitId),
+	/// histories diverged and `pull.rebase` is set, the local commits
+	/// were replayed onto the upstream
+	Rebased(CommitId),
+	/// merge stopped with this many conflicted files left in the index
+	/// (see `RepoState::Merge`)
+	Conflicts(usize),
+}
+
+/// fetches the upstream of the current branch and fast-forwards to it
+/// or (unless `ff_only`) rebases onto it if `pull.rebase` is set or
+/// merges it, honoring `merge.ff`. local changes the incoming changes
+/// would overwrite fail with `Error::UncommittedChanges` before
+/// anything is changed
+pub fn pull(
+	repo_path: &RepoPath,
+	ff_only: bool,
+	basic_credential: Option<BasicAuthCredential>,
+) -> Result<PullOutcome> {
+	scope_time!("pull");
+
+	let branch_name = get_branch_name(repo_path)?;
+	fetch(repo_path, &branch_name, basic_credential, None)?;
+
+	let repo = repo(repo_path)?;
+	let upstream = repo
+		.find_branch(&branch_name, BranchType::Local)?
+		.upstream()?;
+	let upstream_name = upstream
+		.name()?
+		.ok_or_else(|| {
+			Error::Generic(String::from("upstream name not utf8"))
+		})?
+		.to_string();
+	let upstream_commit = upstream.get().peel_to_commit()?;
+
+	let annotated =
+		repo.reference_to_annotated_commit(upstream.get())?;
+	let (analysis, _) = repo.merge_analysis(&[&annotated])?;
+	if !analysis.is_up_to_date() && !analysis.is_fast_forward() {
+		if ff_only {
+			return Err(Error::NotFastForward);
+		}
+
+		if config_is_pull_rebase(repo_path)? {
+			return merge_upstream_rebase(repo_path, &branch_name)
+				.map(PullOutcome::Rebased);
+		}
+	}
+
+	Ok(
+		match merge_branch_repo(
+			&repo,
+			&upstream_name,
+			BranchType::Remote,
+			false,
+		)? {
+			MergeOutcome::UpToDate => PullOutcome::UpToDate,
+			MergeOutcome::FastForward(id) => {
+				PullOutcome::FastForward(id)
+			}
+			MergeOutcome::Merged => {
+				let msg = merge_msg(repo_path)?;
+				PullOutcome::Merged(commit_merge_with_head(
+					&repo,
+					&[upstream_commit],
+					&msg,
+				)?)
+			}
+			MergeOutcome::Conflicts(paths) => {
+				PullOutcome::Conflicts(paths.len())
+			}
+		},
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{pull, PullOutcome};
+	use crate::{
+		error::Error,
+		sync::{
+			remotes::push::push_branch,
+			repo_state,
+			tests::{repo_clone, repo_init_bare, write_commit_file},
+			utils::get_head_repo,
+			RepoPath, RepoState,
+		},
+	};
+	use git2::Repository;
+	use tempfile::TempDir;
+
+	/// upstream with a first commit and two clones of it
+	fn setup(
+	) -> (TempDir, (TempDir, Repository), (TempDir, Repository)) {
+		let (upstream_dir, _) = repo_init_bare().unwrap();
+		let upstream = upstream_dir.path().to_str().unwrap();
+
+		let (dir1, repo1) = repo_clone(upstream).unwrap();
+		write_commit_file(&repo1, "a.txt", "a\n", "c1");
+		push(&dir1, false);
+
+		let clone2 = repo_clone(upstream).unwrap();
+
+		(upstream_dir, (dir1, repo1), clone2)
+	}
+
+	fn path(dir: &TempDir) -> RepoPath {
+		dir.path().to_str().unwrap().into()
+	}
+
+	fn push(dir: &TempDir, force: bool) {
+		push_branch(
+			&path(dir),
+			"origin",
+			"master",
+			force,
+			false,
+			None,
+			None,
+		)
+		.unwrap();
+	}
+
+	#[test]
+	fn test_fast_forward() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		assert_eq!(
+			pull(&path(&dir2), false, None).unwrap(),
+			PullOutcome::UpToDate
+		);
+
+		let c2 = write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+
+		// local changes to other files are kept
+		std::fs::write(dir2.path().join("other.txt"), "x").unwrap();
+
+		assert_eq!(
+			pull(&path(&dir2), true, None).unwrap(),
+			PullOutcome::FastForward(c2)
+		);
+		assert_eq!(get_head_repo(&repo2).unwrap(), c2);
+		assert_eq!(
+			std::fs::read_to_string(dir2.path().join("a.txt"))
+				.unwrap(),
+			"b\n"
+		);
+	}
+
+	#[test]
+	fn test_local_changes_abort() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+
+		let head = get_head_repo(&repo2).unwrap();
+		std::fs::write(dir2.path().join("a.txt"), "local\n").unwrap();
+
+		assert!(matches!(
+			pull(&path(&dir2), false, None),
+			Err(Error::UncommittedChanges)
+		));
+		assert_eq!(get_head_repo(&repo2).unwrap(), head);
+		assert_eq!(
+			std::fs::read_to_string(dir2.path().join("a.txt"))
+				.unwrap(),
+			"local\n"
+		);
+	}
+
+	#[test]
+	fn test_merge() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		let c2 = write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+		let local = write_commit_file(&repo2, "b.txt", "b", "local");
+
+		assert!(matches!(
+			pull(&path(&dir2), true, None),
+			Err(Error::NotFastForward)
+		));
+		assert_eq!(get_head_repo(&repo2).unwrap(), local);
+
+		let PullOutcome::Merged(id) =
+			pull(&path(&dir2), false, None).unwrap()
+		else {
+			panic!("expected a merge commit");
+		};
+
+		let merge = repo2.find_commit(id.into()).unwrap();
+		assert_eq!(
+			merge.parent_ids().collect::<Vec<_>>(),
+			vec![local.get_oid(), c2.get_oid()]
+		);
+		assert_eq!(
+			repo_state(&path(&dir2)).unwrap(),
+			RepoState::Clean
+		);
+	}
+
+	#[test]
+	fn test_conflicts() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+		write_commit_file(&repo2, "a.txt", "c\n", "local");
+
+		assert_eq!(
+			pull(&path(&dir2), false, None).unwrap(),
+			PullOutcome::Conflicts(1)
+		);
+		assert_eq!(
+			repo_state(&path(&dir2)).unwrap(),
+			RepoState::Merge
+		);
+		assert!(repo2.index().unwrap().has_conflicts());
+	}
+
+	#[test]
+	fn test_rebase_config() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		let c2 = write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+		write_commit_file(&repo2, "b.txt", "b", "local");
+		repo2
+			.config()
+			.unwrap()
+			.set_str("pull.rebase", "true")
+			.unwrap();
+
+		let PullOutcome::Rebased(id) =
+			pull(&path(&dir2), false, None).unwrap()
+		else {
+			panic!("expected a rebase");
+		};
+
+		let head = repo2.find_commit(id.into()).unwrap();
+		assert_eq!(
+			head.parent_ids().collect::<Vec<_>>(),
+			vec![c2.get_oid()]
+		);
+		assert_eq!(head.message(), Some("local"));
+	}
+
+	#[test]
+	fn test_merge_ff_config() {
+		let (_upstream, (dir1, repo1), (dir2, repo2)) = setup();
+
+		let c2 = write_commit_file(&repo1, "a.txt", "b\n", "c2");
+		push(&dir1, false);
+		let head = get_head_repo(&repo2).unwrap();
+		repo2.config().unwrap().set_bool("merge.ff", false).unwrap();
+
+		let PullOutcome::Merged(id) =
+			pull(&path(&dir2), false, None).unwrap()
+		else {
+			panic!("expected a merge commit");
+		};
+
+		assert_eq!(
+			repo2
+				.find_commit(id.into())
+				.unwrap()
+				.parent_ids()
+				.collect::<Vec<_>>(),
+			vec![head.get_oid(), c2.get_oid()]
+		);
+	}
+}
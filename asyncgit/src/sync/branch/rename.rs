@@ -1,12 +1,15 @@
 //! renaming of branches
 
 use crate::{
-	error::Result,
+	error::{Error, Result},
 	sync::{repository::repo, RepoPath},
 };
+use git2::{Branch, ErrorCode};
 use scopetime::scope_time;
 
-/// Rename the branch reference
+/// Rename the branch reference, its `branch.<name>.*` config (like
+/// the upstream it tracks) is moved along.
+/// Refuses to overwrite an existing branch (`Error::BranchAlreadyExists`)
 pub fn rename_branch(
 	repo_path: &RepoPath,
 	branch_ref: &str,
@@ -14,10 +17,20 @@ pub fn rename_branch(
 ) -> Result<()> {
 	scope_time!("rename_branch");
 
+	if !Branch::name_is_valid(new_name)? {
+		return Err(Error::InvalidBranchName(new_name.to_string()));
+	}
+
 	let repo = repo(repo_path)?;
 	let branch_as_ref = repo.find_reference(branch_ref)?;
-	let mut branch = git2::Branch::wrap(branch_as_ref);
-	branch.rename(new_name, true)?;
+	let mut branch = Branch::wrap(branch_as_ref);
+	branch.rename(new_name, false).map_err(|e| {
+		if e.code() == ErrorCode::Exists {
+			Error::BranchAlreadyExists(new_name.to_string())
+		} else {
+			e.into()
+		}
+	})?;
 
 	Ok(())
 }
@@ -26,7 +39,7 @@ pub fn rename_branch(
 mod test {
 	use super::super::{checkout_branch, create_branch, RepoPath};
 	use super::rename_branch;
-	use crate::sync::tests::repo_init;
+	use crate::{error::Error, sync::tests::repo_init};
 
 	#[test]
 	fn test_rename_branch() {
@@ -68,4 +81,55 @@ mod test {
 			"AnotherName"
 		);
 	}
+
+	#[test]
+	fn test_rename_keeps_upstream() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "branch1").unwrap();
+		let mut config = repo.config().unwrap();
+		config.set_str("branch.branch1.remote", "origin").unwrap();
+		config
+			.set_str("branch.branch1.merge", "refs/heads/branch1")
+			.unwrap();
+
+		rename_branch(repo_path, "refs/heads/branch1", "renamed")
+			.unwrap();
+
+		let config = repo.config().unwrap().snapshot().unwrap();
+		assert_eq!(
+			config.get_str("branch.renamed.remote").unwrap(),
+			"origin"
+		);
+		assert_eq!(
+			config.get_str("branch.renamed.merge").unwrap(),
+			"refs/heads/branch1"
+		);
+		assert!(config.get_str("branch.branch1.remote").is_err());
+	}
+
+	#[test]
+	fn test_rename_refuses_existing() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "branch1").unwrap();
+
+		assert!(matches!(
+			rename_branch(repo_path, "refs/heads/branch1", "master"),
+			Err(Error::BranchAlreadyExists(name)) if name == "master"
+		));
+		assert!(matches!(
+			rename_branch(repo_path, "refs/heads/branch1", "a..b"),
+			Err(Error::InvalidBranchName(_))
+		));
+		assert!(repo
+			.find_branch("branch1", git2::BranchType::Local)
+			.is_ok());
+	}
 }
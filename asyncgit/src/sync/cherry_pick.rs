@@ -0,0 +1,285 @@
+//! cherry-picking a commit onto HEAD
+
+use super::{CommitId, RepoPath};
+use crate::{
+	error::{Error, Result},
+	sync::{
+		commit::signature_allow_undefined_name,
+		commit_msg::parse_trailers, conflict::conflicting_paths,
+		repository::repo, utils::get_head_repo,
+	},
+};
+use git2::{CherrypickOptions, ErrorCode};
+use scopetime::scope_time;
+
+/// git config flag (bool) to append `(cherry picked from commit …)`
+/// to the message of picked commits, like `git cherry-pick -x`
+pub const CHERRY_PICK_RECORD_ORIGIN: &str =
+	"gitui.cherryPickRecordOrigin";
+
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CherryPickOutcome {
+	/// the pick applied cleanly and was committed
+	Committed(CommitId),
+	/// the repo is left in the `CherryPick` state with these paths
+	/// conflicting in the index
+	Conflicts(Vec<String>),
+}
+
+fn record_origin(msg: &str, id: CommitId) -> String {
+	let msg = msg.trim_end();
+	let separator = if parse_trailers(msg).is_empty() {
+		"\n\n"
+	} else {
+		"\n"
+	};
+
+	format!("{msg}{separator}(cherry picked from commit {id})")
+}
+
+/// applies the changes of commit `id` on top of HEAD (against its
+/// first parent if it is a merge). a clean result is committed right
+/// away keeping the original author, otherwise the conflicts are left
+/// in the index to be resolved (or `abort_pending_state`).
+/// fails with `Error::UncommittedChanges` if anything is staged
+pub fn cherry_pick(
+	repo_path: &RepoPath,
+	id: CommitId,
+) -> Result<CherryPickOutcome> {
+	scope_time!("cherry_pick");
+
+	let repo = repo(repo_path)?;
+	let commit = repo.find_commit(id.into())?;
+
+	ensure_index_matches_head(&repo)?;
+
+	let mut options = CherrypickOptions::new();
+	if commit.parent_count() > 1 {
+		options.mainline(1);
+	}
+
+	repo.cherrypick(&commit, Some(&mut options)).map_err(|e| {
+		if e.code() == ErrorCode::Conflict {
+			Error::UncommittedChanges
+		} else {
+			Error::Git(e)
+		}
+	})?;
+
+	let mut index = repo.index()?;
+	if index.has_conflicts() {
+		return Ok(CherryPickOutcome::Conflicts(conflicting_paths(
+			&index,
+		)?));
+	}
+
+	let head = repo.find_commit(get_head_repo(&repo)?.into())?;
+	let tree_id = index.write_tree()?;
+	if tree_id == head.tree_id() {
+		repo.cleanup_state()?;
+		return Err(Error::NothingToCommit);
+	}
+	let tree = repo.find_tree(tree_id)?;
+
+	let mut msg =
+		String::from_utf8_lossy(commit.message_bytes()).to_string();
+	if repo
+		.config()?
+		.get_bool(CHERRY_PICK_RECORD_ORIGIN)
+		.unwrap_or(false)
+	{
+		msg = record_origin(&msg, id);
+	}
+
+	let committer =
+		signature_allow_undefined_name(&repo).map_err(|e| {
+			if e.code() == ErrorCode::NotFound {
+				Error::NoSignature
+			} else {
+				Error::Git(e)
+			}
+		})?;
+
+	let new_id = repo.commit(
+		Some("HEAD"),
+		&commit.author(),
+		&committer,
+		&msg,
+		&tree,
+		&[&head],
+	)?;
+
+	repo.cleanup_state()?;
+
+	Ok(CherryPickOutcome::Committed(new_id.into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		cherry_pick, CherryPickOutcome, CHERRY_PICK_RECORD_ORIGIN,
+	};
+	use crate::{
+		error::Error,
+		sync::{
+			abort_pending_state, repo_state,
+			tests::{repo_init, write_commit_file},
+			utils::repo_read_file,
+			RepoPath, RepoState,
+		},
+	};
+	use git2::ResetType;
+	use std::path::Path;
+
+	#[test]
+	fn test_cherry_pick_clean() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let base = write_commit_file(&repo, "file", "base", "c1");
+		let picked = write_commit_file(&repo, "other", "a", "picked");
+
+		repo.reset(
+			&repo.find_object(base.into(), None).unwrap(),
+			ResetType::Hard,
+			None,
+		)
+		.unwrap();
+		repo.config()
+			.unwrap()
+			.set_str("user.name", "picker")
+			.unwrap();
+		write_commit_file(&repo, "file", "changed", "c2");
+
+		let CherryPickOutcome::Committed(id) =
+			cherry_pick(repo_path, picked).unwrap()
+		else {
+			panic!("expected a commit");
+		};
+
+		let commit = repo.find_commit(id.into()).unwrap();
+		assert_eq!(commit.author().name(), Some("name"));
+		assert_eq!(commit.committer().name(), Some("picker"));
+		assert_eq!(commit.message(), Some("picked"));
+		assert_eq!(repo_read_file(&repo, "other").unwrap(), "a");
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+
+		// already applied
+		assert!(matches!(
+			cherry_pick(repo_path, picked),
+			Err(Error::NothingToCommit)
+		));
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+	}
+
+	#[test]
+	fn test_cherry_pick_record_origin() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let base = write_commit_file(&repo, "file", "base", "c1");
+		let picked = write_commit_file(&repo, "other", "a", "picked");
+
+		repo.reset(
+			&repo.find_object(base.into(), None).unwrap(),
+			ResetType::Hard,
+			None,
+		)
+		.unwrap();
+		repo.config()
+			.unwrap()
+			.set_bool(CHERRY_PICK_RECORD_ORIGIN, true)
+			.unwrap();
+
+		let CherryPickOutcome::Committed(id) =
+			cherry_pick(repo_path, picked).unwrap()
+		else {
+			panic!("expected a commit");
+		};
+
+		assert_eq!(
+			repo.find_commit(id.into()).unwrap().message(),
+			Some(
+				format!(
+					"picked\n\n(cherry picked from commit {picked})"
+				)
+				.as_str()
+			)
+		);
+	}
+
+	#[test]
+	fn test_cherry_pick_conflict() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let base = write_commit_file(&repo, "file", "base", "c1");
+		let picked = write_commit_file(&repo, "file", "theirs", "c2");
+
+		repo.reset(
+			&repo.find_object(base.into(), None).unwrap(),
+			ResetType::Hard,
+			None,
+		)
+		.unwrap();
+		let head = write_commit_file(&repo, "file", "ours", "c3");
+
+		assert_eq!(
+			cherry_pick(repo_path, picked).unwrap(),
+			CherryPickOutcome::Conflicts(vec![String::from("file")])
+		);
+		assert_eq!(
+			repo_state(repo_path).unwrap(),
+			RepoState::CherryPick
+		);
+		assert!(repo.path().join("CHERRY_PICK_HEAD").exists());
+
+		abort_pending_state(repo_path).unwrap();
+
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+		assert!(!repo.path().join("CHERRY_PICK_HEAD").exists());
+		assert_eq!(repo_read_file(&repo, "file").unwrap(), "ours");
+		assert_eq!(
+			repo.head().unwrap().target().unwrap(),
+			head.into()
+		);
+	}
+
+	#[test]
+	fn test_cherry_pick_staged_changes() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let base = write_commit_file(&repo, "file", "base", "c1");
+		let picked = write_commit_file(&repo, "other", "a", "picked");
+
+		repo.reset(
+			&repo.find_object(base.into(), None).unwrap(),
+			ResetType::Hard,
+			None,
+		)
+		.unwrap();
+		repo_write_file(&repo, "unrelated", "staged").unwrap();
+		stage_add_file(repo_path, Path::new("unrelated")).unwrap();
+
+		assert!(matches!(
+			cherry_pick(repo_path, picked),
+			Err(Error::UncommittedChanges)
+		));
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+		assert_eq!(
+			repo.head().unwrap().target().unwrap(),
+			base.into()
+		);
+		assert!(!root.join("other").exists());
+	}
+}
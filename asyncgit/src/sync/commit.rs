@@ -1,14 +1,25 @@
 //! Git Api for Commits
-use super::{CommitId, RepoPath};
+use super::{
+	commit_msg::{
+		append_trailers, cleanup_commit_msg, CommitTrailers,
+	},
+	config::get_config_string_repo,
+	hooks::{
+		hooks_prepare_commit_msg, HookResult, PrepareCommitMsgSource,
+	},
+	CommitId, RepoPath,
+};
 use crate::sync::sign::{SignBuilder, SignError};
 use crate::{
 	error::{Error, Result},
 	sync::{repository::repo, utils::get_head_repo},
 };
 use git2::{
-	message_prettify, ErrorCode, ObjectType, Repository, Signature,
+	message_prettify, Config, ErrorCode, ObjectType, Repository,
+	RepositoryState, Signature,
 };
 use scopetime::scope_time;
+use std::{fs, path::PathBuf};
 
 ///
 pub fn amend(
@@ -34,7 +45,14 @@ pub fn amend(
 		let head = get_head_repo(&repo)?;
 		if head == commit.id().into() {
 			undo_last_commit(repo_path)?;
-			return self::commit(repo_path, msg);
+			return commit_with_options(
+				repo_path,
+				msg,
+				&CommitOptions {
+					allow_empty: true,
+					..CommitOptions::default()
+				},
+			);
 		}
 
 		return Err(Error::SignAmendNonLastCommit);
@@ -52,6 +70,57 @@ pub fn amend(
 	Ok(CommitId::new(new_id))
 }
 
+/// amends HEAD with the current index, keeping its message unless
+/// `new_msg` is given. `trailers` are added to the trailers already
+/// in the message. all parents of a merge commit are kept.
+/// diffs cached by `AsyncDiff` depend on HEAD and are not reused
+/// afterwards
+pub fn commit_amend(
+	repo_path: &RepoPath,
+	new_msg: Option<&str>,
+	trailers: &CommitTrailers,
+) -> Result<CommitId> {
+	scope_time!("commit_amend");
+
+	let repo = repo(repo_path)?;
+
+	let head = get_head_repo(&repo)?;
+	let commit = repo.find_commit(head.into())?;
+
+	let msg = match new_msg {
+		Some(msg) if msg.trim().is_empty() => {
+			return Err(Error::EmptyCommitMessage);
+		}
+		Some(msg) => msg.to_string(),
+		None => String::from_utf8_lossy(commit.message_bytes())
+			.to_string(),
+	};
+
+	// signed amends recommit on top of the first parent only
+	if commit.parent_count() > 1
+		&& repo.config()?.get_bool("commit.gpgsign").unwrap_or(false)
+	{
+		return Err(Error::Generic(String::from(
+			"cannot amend a merge commit with commit.gpgsign=true, it would drop parents",
+		)));
+	}
+
+	amend(repo_path, head, &append_trailers(&msg, trailers))
+}
+
+/// `user.name <user.email>` for a `Signed-off-by` trailer,
+/// `None` unless both are configured
+pub fn get_signoff(repo_path: &RepoPath) -> Result<Option<String>> {
+	let repo = repo(repo_path)?;
+
+	let user = get_config_string_repo(&repo, "user.name")?;
+	let mail = get_config_string_repo(&repo, "user.email")?;
+
+	Ok(user
+		.zip(mail)
+		.map(|(user, mail)| format!("{user} <{mail}>")))
+}
+
 /// Wrap `Repository::signature` to allow unknown user.name.
 ///
 /// See <https://github.com/extrawurst/gitui/issues/79>.
@@ -79,13 +148,50 @@ pub(crate) fn signature_allow_undefined_name(
 	signature
 }
 
-/// this does not run any git hooks, git-hooks have to be executed manually, checkout `hooks_commit_msg` for example
+/// this does not run any git hooks, git-hooks have to be executed manually, checkout `hooks_commit_msg` for example.
+/// commits the index on top of HEAD (no parent on an unborn branch),
+/// the message is cleaned up (see `cleanup_commit_msg`) and must
+/// not end up empty
 pub fn commit(repo_path: &RepoPath, msg: &str) -> Result<CommitId> {
-	scope_time!("commit");
+	commit_with_options(repo_path, msg, &CommitOptions::default())
+}
+
+/// extras for `commit_with_options`
+#[derive(Debug, Default, Clone)]
+pub struct CommitOptions {
+	/// appended to the message (see `append_trailers`)
+	pub trailers: CommitTrailers,
+	/// commit even if nothing changed compared to HEAD
+	/// (always allowed to conclude a merge)
+	pub allow_empty: bool,
+}
+
+/// `commit` with trailers, fails with `Error::NothingToCommit` if the
+/// index equals HEAD unless `allow_empty` is set
+pub fn commit_with_options(
+	repo_path: &RepoPath,
+	msg: &str,
+	options: &CommitOptions,
+) -> Result<CommitId> {
+	scope_time!("commit_with_options");
 
 	let repo = repo(repo_path)?;
 	let config = repo.config()?;
-	let signature = signature_allow_undefined_name(&repo)?;
+
+	let msg = cleanup_commit_msg(msg, Some(comment_char(&config)));
+	if msg.is_empty() {
+		return Err(Error::EmptyCommitMessage);
+	}
+	let msg = append_trailers(&msg, &options.trailers);
+	let msg = msg.as_str();
+	let signature =
+		signature_allow_undefined_name(&repo).map_err(|e| {
+			if e.code() == ErrorCode::NotFound {
+				Error::NoSignature
+			} else {
+				Error::Git(e)
+			}
+		})?;
 	let mut index = repo.index()?;
 	let tree_id = index.write_tree()?;
 	let tree = repo.find_tree(tree_id)?;
@@ -96,6 +202,17 @@ pub fn commit(repo_path: &RepoPath, msg: &str) -> Result<CommitId> {
 		Vec::new()
 	};
 
+	let unchanged = parents.first().map_or_else(
+		|| index.is_empty(),
+		|parent| parent.tree_id() == tree_id,
+	);
+	if unchanged
+		&& !options.allow_empty
+		&& repo.state() != RepositoryState::Merge
+	{
+		return Err(Error::NothingToCommit);
+	}
+
 	let parents = parents.iter().collect::<Vec<_>>();
 
 	let commit_id = if config
@@ -154,32 +271,47 @@ pub fn commit(repo_path: &RepoPath, msg: &str) -> Result<CommitId> {
 	Ok(commit_id.into())
 }
 
-/// Tag a commit.
+/// Tag a commit, annotated (tagger from config) if `message` is set,
+/// lightweight otherwise.
 ///
 /// This function will return an `Err(…)` variant if the tag’s name is refused
-/// by git or if the tag already exists.
+/// by git (`Error::InvalidTagName`) or if the tag already exists
+/// (`Error::TagAlreadyExists`) unless `force` is set to move it.
 pub fn tag_commit(
 	repo_path: &RepoPath,
 	commit_id: &CommitId,
 	tag: &str,
 	message: Option<&str>,
+	force: bool,
 ) -> Result<CommitId> {
 	scope_time!("tag_commit");
 
+	if !git2::Tag::is_valid_name(tag) {
+		return Err(Error::InvalidTagName(tag.to_string()));
+	}
+
 	let repo = repo(repo_path)?;
 
 	let object_id = commit_id.get_oid();
 	let target =
 		repo.find_object(object_id, Some(ObjectType::Commit))?;
 
-	let c = if let Some(message) = message {
+	let res = if let Some(message) = message {
 		let signature = signature_allow_undefined_name(&repo)?;
-		repo.tag(tag, &target, &signature, message, false)?.into()
+		repo.tag(tag, &target, &signature, message, force)
 	} else {
-		repo.tag_lightweight(tag, &target, false)?.into()
+		repo.tag_lightweight(tag, &target, force)
 	};
 
-	Ok(c)
+	let c = res.map_err(|e| {
+		if e.code() == ErrorCode::Exists {
+			Error::TagAlreadyExists(tag.to_string())
+		} else {
+			e.into()
+		}
+	})?;
+
+	Ok(c.into())
 }
 
 /// Loads the comment prefix from config & uses it to prettify commit messages
@@ -187,30 +319,101 @@ pub fn commit_message_prettify(
 	repo_path: &RepoPath,
 	message: String,
 ) -> Result<String> {
-	let comment_char = repo(repo_path)?
-		.config()?
+	let comment_char =
+		comment_char(&repo(repo_path)?.config()?) as u8;
+
+	Ok(message_prettify(message, Some(comment_char))?)
+}
+
+/// the message to pre-fill the commit editor with: `MERGE_MSG` while
+/// merging, otherwise the contents of the `commit.template` file.
+/// the `prepare-commit-msg` hook gets to change it before it is returned
+pub fn get_commit_template(
+	repo_path: &RepoPath,
+) -> Result<Option<String>> {
+	scope_time!("get_commit_template");
+
+	let repo = repo(repo_path)?;
+
+	let (mut msg, source) = if repo.state() == RepositoryState::Merge
+	{
+		(repo.message()?, PrepareCommitMsgSource::Merge)
+	} else if let Some(template) = read_commit_template(&repo)? {
+		(template, PrepareCommitMsgSource::Template)
+	} else {
+		return Ok(None);
+	};
+
+	if let HookResult::NotOk(e) =
+		hooks_prepare_commit_msg(repo_path, source, &mut msg)?
+	{
+		log::error!("prepare-commit-msg hook rejection: {e}");
+	}
+
+	Ok(Some(msg))
+}
+
+/// contents of the `commit.template` file, its path shell expanded
+/// (`~`, environment variables). a file that cannot be read is logged
+/// and skipped
+fn read_commit_template(repo: &Repository) -> Result<Option<String>> {
+	let Some(path) = get_config_string_repo(repo, "commit.template")?
+	else {
+		return Ok(None);
+	};
+
+	let path = match shellexpand::full(&path) {
+		Ok(path) => PathBuf::from(path.as_ref()),
+		Err(e) => {
+			log::error!("expand commit.template failed: {e}");
+			return Ok(None);
+		}
+	};
+
+	Ok(fs::read_to_string(&path)
+		.map_err(|e| {
+			log::error!(
+				"read commit.template failed: {e} (path: '{:?}')",
+				path
+			);
+		})
+		.ok())
+}
+
+/// `core.commentChar`, defaults to `#`
+fn comment_char(config: &Config) -> char {
+	config
 		.get_string("core.commentChar")
 		.ok()
 		.and_then(|char_string| char_string.chars().next())
-		.unwrap_or('#') as u8;
-
-	Ok(message_prettify(message, Some(comment_char))?)
+		.unwrap_or('#')
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::error::Result;
+	use crate::error::{Error, Result};
 	use crate::sync::tags::Tag;
-	use crate::sync::RepoPath;
 	use crate::sync::{
-		commit, get_commit_details, get_commit_files, stage_add_file,
+		checkout_branch, commit, create_branch, get_commit_details,
+		get_commit_files, merge_branch, stage_add_file,
+		status::{get_status, StatusType},
 		tags::get_tags,
-		tests::{get_statuses, repo_init, repo_init_empty},
+		tests::{
+			get_statuses, repo_init, repo_init_empty,
+			write_commit_file,
+		},
 		utils::get_head,
 		LogWalker,
 	};
-	use commit::{amend, commit_message_prettify, tag_commit};
-	use git2::Repository;
+	use crate::sync::{
+		commit_msg::CommitTrailers, CommitId, RepoPath,
+	};
+	use commit::{
+		amend, commit_amend, commit_message_prettify,
+		commit_with_options, get_commit_template, get_signoff,
+		tag_commit, CommitOptions,
+	};
+	use git2::{BranchType, ObjectType, Repository};
 	use std::{fs::File, io::Write, path::Path};
 
 	fn count_commits(repo: &Repository, max: usize) -> usize {
@@ -270,6 +473,206 @@ mod tests {
 		assert_eq!(get_statuses(repo_path), (0, 0));
 	}
 
+	#[test]
+	fn test_commit_head() {
+		let file_path = Path::new("foo");
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let parent = get_head(repo_path).unwrap();
+
+		File::create(root.join(file_path))
+			.unwrap()
+			.write_all(b"1\n2\n3\n4\n")
+			.unwrap();
+		stage_add_file(repo_path, file_path).unwrap();
+
+		assert!(matches!(
+			commit(repo_path, " \n"),
+			Err(Error::EmptyCommitMessage)
+		));
+
+		let id = commit(repo_path, "add foo").unwrap();
+
+		assert!(get_status(repo_path, &StatusType::Stage.into())
+			.unwrap()
+			.is_empty());
+
+		let head = repo.head().unwrap().peel_to_commit().unwrap();
+		assert_eq!(CommitId::new(head.id()), id);
+		assert_eq!(head.message(), Some("add foo"));
+		assert_eq!(
+			head.parent_ids().map(CommitId::new).collect::<Vec<_>>(),
+			vec![parent]
+		);
+	}
+
+	#[test]
+	fn test_commit_cleanup() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("foo"))
+			.unwrap()
+			.write_all(b"a\n")
+			.unwrap();
+		stage_add_file(repo_path, Path::new("foo")).unwrap();
+
+		assert!(matches!(
+			commit(repo_path, "# only a comment\n"),
+			Err(Error::EmptyCommitMessage)
+		));
+
+		let id = commit(
+			repo_path,
+			"# template\r\nsubject  \r\n\r\n\r\nbody\r\n# comment\r\n",
+		)
+		.unwrap();
+
+		assert_eq!(
+			repo.find_commit(id.into()).unwrap().message(),
+			Some("subject\n\nbody")
+		);
+	}
+
+	#[test]
+	fn test_commit_template() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert_eq!(get_commit_template(repo_path).unwrap(), None);
+
+		let template = tempfile::NamedTempFile::new().unwrap();
+		std::fs::write(template.path(), "subject\n\n# why?\n")
+			.unwrap();
+		repo.config()
+			.unwrap()
+			.set_str(
+				"commit.template",
+				template.path().to_str().unwrap(),
+			)
+			.unwrap();
+
+		assert_eq!(
+			get_commit_template(repo_path).unwrap().as_deref(),
+			Some("subject\n\n# why?\n")
+		);
+
+		// a template that cannot be read is skipped
+		repo.config()
+			.unwrap()
+			.set_str(
+				"commit.template",
+				root.join("missing.txt").to_str().unwrap(),
+			)
+			.unwrap();
+		assert_eq!(get_commit_template(repo_path).unwrap(), None);
+	}
+
+	#[test]
+	fn test_commit_template_merge() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		std::fs::write(root.join("template.txt"), "template")
+			.unwrap();
+		repo.config()
+			.unwrap()
+			.set_str(
+				"commit.template",
+				root.join("template.txt").to_str().unwrap(),
+			)
+			.unwrap();
+
+		write_commit_file(&repo, "test.txt", "test", "commit1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "foo.txt", "foo", "commit2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "test2", "commit3");
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		assert!(get_commit_template(repo_path)
+			.unwrap()
+			.unwrap()
+			.starts_with("Merge branch"));
+	}
+
+	#[test]
+	fn test_nothing_to_commit() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let head = get_head(repo_path).unwrap();
+
+		assert!(matches!(
+			commit(repo_path, "empty"),
+			Err(Error::NothingToCommit)
+		));
+		assert_eq!(get_head(repo_path).unwrap(), head);
+
+		let id = commit_with_options(
+			repo_path,
+			"empty",
+			&CommitOptions {
+				allow_empty: true,
+				..CommitOptions::default()
+			},
+		)
+		.unwrap();
+
+		let commit = repo.find_commit(id.into()).unwrap();
+		assert_eq!(CommitId::new(commit.parent_id(0).unwrap()), head);
+		assert_eq!(
+			commit.tree_id(),
+			repo.find_commit(head.into()).unwrap().tree_id()
+		);
+	}
+
+	#[test]
+	fn test_nothing_to_commit_unborn() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert!(matches!(
+			commit(repo_path, "empty"),
+			Err(Error::NothingToCommit)
+		));
+	}
+
+	#[test]
+	fn test_conclude_merge_unchanged() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "test", "commit1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "test.txt", "test2", "commit2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "test2", "commit3");
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		// the merge does not change anything
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		commit(repo_path, "merge").unwrap();
+	}
+
 	#[test]
 	fn test_amend() -> Result<()> {
 		let file_path1 = Path::new("foo");
@@ -308,6 +711,100 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_commit_amend() -> Result<()> {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert!(matches!(
+			commit_amend(repo_path, None, &CommitTrailers::default()),
+			Err(Error::NoHead)
+		));
+
+		File::create(root.join("foo"))?.write_all(b"a\n")?;
+		stage_add_file(repo_path, Path::new("foo"))?;
+		commit(repo_path, "commit msg")?;
+
+		File::create(root.join("bar"))?.write_all(b"b\n")?;
+		stage_add_file(repo_path, Path::new("bar"))?;
+
+		let id = commit_amend(
+			repo_path,
+			None,
+			&CommitTrailers::default(),
+		)?;
+
+		assert_eq!(count_commits(&repo, 10), 1);
+		assert_eq!(get_head(repo_path)?, id);
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		let head = repo.find_commit(id.into())?;
+		assert_eq!(head.message(), Some("commit msg"));
+		assert!(head.tree()?.get_name("foo").is_some());
+		assert!(head.tree()?.get_name("bar").is_some());
+
+		let id = commit_amend(
+			repo_path,
+			Some("new msg"),
+			&CommitTrailers::default(),
+		)?;
+
+		assert_eq!(count_commits(&repo, 10), 1);
+		assert_eq!(
+			repo.find_commit(id.into())?.message(),
+			Some("new msg")
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_commit_trailers() -> Result<()> {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let signoff = get_signoff(repo_path)?.unwrap();
+		assert_eq!(signoff, "name <email>");
+
+		File::create(root.join("foo"))?.write_all(b"a\n")?;
+		stage_add_file(repo_path, Path::new("foo"))?;
+
+		let id = commit_with_options(
+			repo_path,
+			"subject\n\nbody",
+			&CommitOptions {
+				trailers: CommitTrailers::default()
+					.signed_off_by(&signoff),
+				..CommitOptions::default()
+			},
+		)?;
+		assert_eq!(
+			repo.find_commit(id.into())?.message(),
+			Some("subject\n\nbody\n\nSigned-off-by: name <email>")
+		);
+
+		let id = commit_amend(
+			repo_path,
+			None,
+			&CommitTrailers::default()
+				.signed_off_by(&signoff)
+				.co_authored_by("other <other@mail>"),
+		)?;
+		assert_eq!(
+			repo.find_commit(id.into())?.message(),
+			Some("subject\n\nbody\n\nSigned-off-by: name <email>\nCo-authored-by: other <other@mail>")
+		);
+
+		repo.config()?.remove("user.email")?;
+		assert_eq!(get_signoff(repo_path)?, None);
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_tag() -> Result<()> {
 		let file_path = Path::new("foo");
@@ -323,21 +820,24 @@ mod tests {
 
 		let new_id = commit(repo_path, "commit msg")?;
 
-		tag_commit(repo_path, &new_id, "tag", None)?;
+		tag_commit(repo_path, &new_id, "tag", None, false)?;
 
 		assert_eq!(
 			get_tags(repo_path).unwrap()[&new_id],
 			vec![Tag::new("tag")]
 		);
 
-		assert!(tag_commit(repo_path, &new_id, "tag", None).is_err());
+		assert!(matches!(
+			tag_commit(repo_path, &new_id, "tag", None, false),
+			Err(Error::TagAlreadyExists(name)) if name == "tag"
+		));
 
 		assert_eq!(
 			get_tags(repo_path).unwrap()[&new_id],
 			vec![Tag::new("tag")]
 		);
 
-		tag_commit(repo_path, &new_id, "second-tag", None)?;
+		tag_commit(repo_path, &new_id, "second-tag", None, false)?;
 
 		assert_eq!(
 			get_tags(repo_path).unwrap()[&new_id],
@@ -362,7 +862,13 @@ mod tests {
 
 		let new_id = commit(repo_path, "commit msg")?;
 
-		tag_commit(repo_path, &new_id, "tag", Some("tag-message"))?;
+		tag_commit(
+			repo_path,
+			&new_id,
+			"tag",
+			Some("tag-message"),
+			false,
+		)?;
 
 		assert_eq!(
 			get_tags(repo_path).unwrap()[&new_id][0]
@@ -372,6 +878,66 @@ mod tests {
 			"tag-message"
 		);
 
+		let tag = repo
+			.find_reference("refs/tags/tag")?
+			.peel(ObjectType::Tag)?
+			.into_tag()
+			.unwrap();
+		assert_eq!(tag.message(), Some("tag-message"));
+		assert_eq!(tag.target_id(), new_id.get_oid());
+		assert_eq!(tag.tagger().unwrap().name(), Some("name"));
+		assert_eq!(tag.tagger().unwrap().email(), Some("email"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_tag_force_and_invalid() -> Result<()> {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "foo", "a", "c1");
+		let c2 = write_commit_file(&repo, "foo", "b", "c2");
+
+		tag_commit(repo_path, &c1, "light", None, false)?;
+		tag_commit(repo_path, &c1, "annotated", Some("msg"), false)?;
+
+		let target = |name: &str| {
+			repo.find_reference(&format!("refs/tags/{name}"))
+				.and_then(|r| r.peel_to_commit())
+				.map(|c| CommitId::new(c.id()))
+				.unwrap()
+		};
+		assert_eq!(target("light"), c1);
+		assert!(repo
+			.find_reference("refs/tags/light")?
+			.peel(ObjectType::Tag)
+			.is_err());
+
+		assert!(matches!(
+			tag_commit(
+				repo_path,
+				&c2,
+				"annotated",
+				Some("msg"),
+				false
+			),
+			Err(Error::TagAlreadyExists(_))
+		));
+		assert_eq!(target("annotated"), c1);
+
+		tag_commit(repo_path, &c2, "light", None, true)?;
+		tag_commit(repo_path, &c2, "annotated", Some("moved"), true)?;
+		assert_eq!(target("light"), c2);
+		assert_eq!(target("annotated"), c2);
+
+		assert!(matches!(
+			tag_commit(repo_path, &c2, "bad..name", None, false),
+			Err(Error::InvalidTagName(_))
+		));
+
 		Ok(())
 	}
 
@@ -398,7 +964,7 @@ mod tests {
 
 		let error = commit(repo_path, "commit msg");
 
-		assert!(error.is_err());
+		assert!(matches!(error, Err(Error::NoSignature)));
 
 		repo.config()?.set_str("user.email", "email")?;
 
@@ -445,6 +1011,10 @@ mod tests {
 
 		repo.config()?.set_str("user.name", "name")?;
 
+		File::create(root.join(file_path))?
+			.write_all(b"test\nbar")?;
+		stage_add_file(repo_path, file_path)?;
+
 		success = commit(repo_path, "commit msg");
 
 		assert!(success.is_ok());
@@ -459,6 +1029,111 @@ mod tests {
 		Ok(())
 	}
 
+	/// writes an executable `gpg` stand-in that signs with a fixed
+	/// signature, or fails with `error` on stderr
+	#[cfg(unix)]
+	fn fake_gpg(dir: &Path, error: Option<&str>) -> String {
+		use std::os::unix::fs::PermissionsExt;
+
+		let script = error.map_or_else(
+			|| {
+				String::from(
+					"#!/bin/sh
+cat > /dev/null
+echo '[GNUPG:] BEGIN_SIGNING' >&2
+echo '[GNUPG:] SIG_CREATED D 1 8 00 0 0' >&2
+echo '-----BEGIN PGP SIGNATURE-----'
+echo 'fake'
+echo '-----END PGP SIGNATURE-----'
+",
+				)
+			},
+			|error| {
+				format!("#!/bin/sh\necho '{error}' >&2\nexit 2\n")
+			},
+		);
+
+		let path = dir.join("fake-gpg");
+		std::fs::write(&path, script).unwrap();
+		std::fs::set_permissions(
+			&path,
+			std::fs::Permissions::from_mode(0o755),
+		)
+		.unwrap();
+
+		path.to_str().unwrap().to_string()
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_commit_signed() -> Result<()> {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+		let bin = tempfile::TempDir::new()?;
+
+		{
+			let mut config = repo.config()?;
+			config.set_bool("commit.gpgsign", true)?;
+			config.set_str("user.signingKey", "KEY")?;
+			config.set_str(
+				"gpg.program",
+				&fake_gpg(bin.path(), None),
+			)?;
+		}
+
+		File::create(root.join("foo"))?.write_all(b"a\n")?;
+		stage_add_file(repo_path, Path::new("foo"))?;
+
+		let id = commit(repo_path, "signed")?;
+
+		assert_eq!(get_head(repo_path)?, id);
+
+		let (signature, _) =
+			repo.extract_signature(&id.into(), None)?;
+		let signature = signature.as_str().unwrap();
+		assert!(
+			signature.starts_with("-----BEGIN PGP SIGNATURE-----")
+		);
+		assert!(signature.contains("\nfake\n"));
+
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_commit_sign_failure() -> Result<()> {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+		let bin = tempfile::TempDir::new()?;
+
+		{
+			let mut config = repo.config()?;
+			config.set_bool("commit.gpgsign", true)?;
+			config.set_str("user.signingKey", "KEY")?;
+			config.set_str(
+				"gpg.program",
+				&fake_gpg(bin.path(), Some("key locked")),
+			)?;
+		}
+
+		let head = get_head(repo_path)?;
+
+		File::create(root.join("foo"))?.write_all(b"a\n")?;
+		stage_add_file(repo_path, Path::new("foo"))?;
+
+		let err = commit(repo_path, "signed").unwrap_err();
+
+		assert!(matches!(err, Error::Sign(_)));
+		assert!(err.to_string().contains("key locked"));
+		assert_eq!(get_head(repo_path)?, head);
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_empty_comment_char() -> Result<()> {
 		let (_td, repo) = repo_init_empty().unwrap();
@@ -1,4 +1,9 @@
-use super::{commits_info::get_message, CommitId, RepoPath};
+use super::{
+	commit_files::get_commit_parent_diff,
+	commits_info::get_message,
+	diff::{tree_diff_stats, FileDiffStat},
+	CommitId, RepoPath,
+};
 use crate::{error::Result, sync::repository::repo};
 use git2::Signature;
 use scopetime::scope_time;
@@ -78,6 +83,11 @@ pub struct CommitDetails {
 	pub message: Option<CommitMessage>,
 	///
 	pub hash: String,
+	/// empty for the root commit, more than one for merge commits
+	pub parents: Vec<CommitId>,
+	/// changed files compared to the first parent
+	/// (the empty tree for the root commit)
+	pub files: Vec<FileDiffStat>,
 }
 
 impl CommitDetails {
@@ -109,11 +119,18 @@ pub fn get_commit_details(
 	let msg =
 		CommitMessage::from(get_message(&commit, None).as_str());
 
+	let parents = commit.parent_ids().map(CommitId::new).collect();
+	let files = tree_diff_stats(&get_commit_parent_diff(
+		&repo, id, 0, None, None,
+	)?)?;
+
 	let details = CommitDetails {
 		author,
 		committer,
 		message: Some(msg),
 		hash: id.to_string(),
+		parents,
+		files,
 	};
 
 	Ok(details)
@@ -125,11 +142,79 @@ mod tests {
 	use crate::{
 		error::Result,
 		sync::{
-			commit, stage_add_file, tests::repo_init_empty, RepoPath,
+			checkout_branch, commit, create_branch, merge_branch,
+			merge_commit, stage_add_file,
+			tests::{repo_init_empty, write_commit_file},
+			RepoPath,
 		},
 	};
+	use git2::BranchType;
 	use std::{fs::File, io::Write, path::Path};
 
+	#[test]
+	fn test_details_root_commit() -> Result<()> {
+		let file_path = Path::new("foo");
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join(file_path))?.write_all(b"a\nb\n")?;
+		stage_add_file(repo_path, file_path).unwrap();
+		let id =
+			commit(repo_path, "subject\n\nbody line\nsecond line")
+				.unwrap();
+
+		let res = get_commit_details(repo_path, id).unwrap();
+
+		let msg = res.message.unwrap();
+		assert_eq!(msg.subject, "subject");
+		assert_eq!(
+			msg.body.unwrap().trim_start(),
+			"body line\nsecond line"
+		);
+
+		assert_eq!(res.author.name, "name");
+		assert_eq!(res.author.email, "email");
+		assert_eq!(res.committer, None);
+
+		assert!(res.parents.is_empty());
+		assert_eq!(res.files.len(), 1);
+		assert_eq!(res.files[0].path, "foo");
+		assert_eq!(res.files[0].insertions, 2);
+		assert_eq!(res.files[0].deletions, 0);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_details_merge_commit() -> Result<()> {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "a.txt", "a", "c1");
+		create_branch(repo_path, "foo").unwrap();
+		let c2 = write_commit_file(&repo, "b.txt", "b\n", "c2");
+
+		checkout_branch(repo_path, "master").unwrap();
+		let c3 = write_commit_file(&repo, "a.txt", "a2", "c3");
+
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+		let id = merge_commit(repo_path, "merge foo", &[c2]).unwrap();
+
+		let res = get_commit_details(repo_path, id).unwrap();
+
+		assert_eq!(res.parents, vec![c3, c2]);
+		assert_eq!(res.files.len(), 1);
+		assert_eq!(res.files[0].path, "b.txt");
+		assert_eq!(res.files[0].insertions, 1);
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_msg_invalid_utf8() -> Result<()> {
 		let file_path = Path::new("foo");
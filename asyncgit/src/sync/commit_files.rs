@@ -6,7 +6,7 @@ use crate::{
 	sync::{get_stashes, repository::repo},
 	StatusItem, StatusItemType,
 };
-use git2::{Diff, Repository};
+use git2::{Diff, DiffFindOptions, Repository};
 use scopetime::scope_time;
 use std::collections::HashSet;
 
@@ -40,7 +40,8 @@ pub fn sort_commits(
 	}
 }
 
-/// get all files that are part of a commit
+/// get all files that are part of a commit (compared to its first
+/// parent or `other`), renames are detected
 pub fn get_commit_files(
 	repo_path: &RepoPath,
 	id: CommitId,
@@ -50,7 +51,7 @@ pub fn get_commit_files(
 
 	let repo = repo(repo_path)?;
 
-	let diff = if let Some(other) = other {
+	let mut diff = if let Some(other) = other {
 		get_compare_commits_diff(
 			&repo,
 			sort_commits(&repo, (id, other))?,
@@ -67,6 +68,8 @@ pub fn get_commit_files(
 		)?
 	};
 
+	diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
 	let res = diff
 		.deltas()
 		.map(|delta| {
@@ -78,7 +81,15 @@ pub fn get_commit_files(
 					.path()
 					.map(|p| p.to_str().unwrap_or("").to_string())
 					.unwrap_or_default(),
+				old_path: (status == StatusItemType::Renamed)
+					.then(|| delta.old_file().path())
+					.flatten()
+					.and_then(|p| p.to_str())
+					.map(String::from),
 				status,
+				ignored: false,
+				mtime: None,
+				size: None,
 			}
 		})
 		.collect::<Vec<_>>();
@@ -106,13 +117,10 @@ pub fn get_compare_commits_diff(
 	};
 
 	let mut opts = git2::DiffOptions::new();
-	if let Some(options) = options {
-		opts.context_lines(options.context);
-		opts.ignore_whitespace(options.ignore_whitespace);
-		opts.interhunk_lines(options.interhunk_lines);
-	}
-	if let Some(p) = &pathspec {
+	options.unwrap_or_default().apply(&mut opts)?;
+	if let Some(p) = pathspec.as_ref().filter(|p| !p.is_empty()) {
 		opts.pathspec(p.clone());
+		opts.disable_pathspec_match(true);
 	}
 
 	let diff: Diff<'_> = repo.diff_tree_to_tree(
@@ -135,31 +143,13 @@ pub(crate) fn get_commit_diff<'a>(
 	// scope_time!("get_commit_diff");
 
 	let commit = repo.find_commit(id.into())?;
-	let commit_tree = commit.tree()?;
-
-	let parent = if commit.parent_count() > 0 {
-		repo.find_commit(commit.parent_id(0)?)
-			.ok()
-			.and_then(|c| c.tree().ok())
-	} else {
-		None
-	};
-
-	let mut opts = git2::DiffOptions::new();
-	if let Some(options) = options {
-		opts.context_lines(options.context);
-		opts.ignore_whitespace(options.ignore_whitespace);
-		opts.interhunk_lines(options.interhunk_lines);
-	}
-	if let Some(p) = &pathspec {
-		opts.pathspec(p.clone());
-	}
-	opts.show_binary(true);
 
-	let mut diff = repo.diff_tree_to_tree(
-		parent.as_ref(),
-		Some(&commit_tree),
-		Some(&mut opts),
+	let mut diff = get_commit_parent_diff(
+		repo,
+		id,
+		0,
+		pathspec.clone(),
+		options,
 	)?;
 
 	if stashes.is_some_and(|stashes| stashes.contains(&id)) {
@@ -179,6 +169,44 @@ pub(crate) fn get_commit_diff<'a>(
 	Ok(diff)
 }
 
+/// get diff of a commit to its parent at `parent_index`
+/// (the empty tree for root commits)
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn get_commit_parent_diff(
+	repo: &Repository,
+	id: CommitId,
+	parent_index: usize,
+	pathspec: Option<String>,
+	options: Option<DiffOptions>,
+) -> Result<Diff<'_>> {
+	let commit = repo.find_commit(id.into())?;
+	let commit_tree = commit.tree()?;
+
+	let parent = if parent_index == 0 && commit.parent_count() == 0 {
+		None
+	} else {
+		repo.find_commit(commit.parent_id(parent_index)?)
+			.ok()
+			.and_then(|c| c.tree().ok())
+	};
+
+	let mut opts = git2::DiffOptions::new();
+	options.unwrap_or_default().apply(&mut opts)?;
+	if let Some(p) = pathspec.as_ref().filter(|p| !p.is_empty()) {
+		opts.pathspec(p.clone());
+		opts.disable_pathspec_match(true);
+	}
+	opts.show_binary(true);
+
+	let diff = repo.diff_tree_to_tree(
+		parent.as_ref(),
+		Some(&commit_tree),
+		Some(&mut opts),
+	)?;
+
+	Ok(diff)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::get_commit_files;
@@ -216,6 +244,35 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_renamed() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("foo"))?
+			.write_all(b"line 1\nline 2\nline 3\n")?;
+		stage_add_file(repo_path, Path::new("foo"))?;
+		commit(repo_path, "c1")?;
+
+		std::fs::rename(root.join("foo"), root.join("bar"))?;
+		let mut index = repo.index()?;
+		index.remove_path(Path::new("foo"))?;
+		index.add_path(Path::new("bar"))?;
+		index.write()?;
+		let id = commit(repo_path, "rename")?;
+
+		let diff = get_commit_files(repo_path, id, None)?;
+
+		assert_eq!(diff.len(), 1);
+		assert_eq!(diff[0].status, StatusItemType::Renamed);
+		assert_eq!(diff[0].path, "bar");
+		assert_eq!(diff[0].old_path.as_deref(), Some("foo"));
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_stashed_untracked() -> Result<()> {
 		let file_path = Path::new("file1.txt");
@@ -1,32 +1,42 @@
-use super::{commit_files::get_commit_diff, CommitId};
-use crate::error::Result;
+use super::{commit_files::get_commit_diff, CommitId, RepoPath};
+use crate::{error::Result, sync::repository::repo};
 use bitflags::bitflags;
 use fuzzy_matcher::FuzzyMatcher;
-use git2::{Diff, Repository};
-use std::sync::Arc;
+use git2::{Commit, Diff, Oid, Repository, Sort, Tree};
+use scopetime::scope_time;
+use std::{path::Path, sync::Arc};
 
 ///
 pub type SharedCommitFilterFn = Arc<
 	Box<dyn Fn(&Repository, &CommitId) -> Result<bool> + Send + Sync>,
 >;
 
-///
+/// id and mode of `path` in `tree`, `None` if it does not exist
+fn path_entry(tree: &Tree<'_>, path: &Path) -> Option<(Oid, i32)> {
+	tree.get_path(path)
+		.ok()
+		.map(|entry| (entry.id(), entry.filemode()))
+}
+
+/// whether `file_path` differs from the first parent
+/// (or exists in a root commit)
 pub fn diff_contains_file(file_path: String) -> SharedCommitFilterFn {
 	Arc::new(Box::new(
 		move |repo: &Repository,
 		      commit_id: &CommitId|
 		      -> Result<bool> {
-			let diff = get_commit_diff(
-				repo,
-				*commit_id,
-				Some(file_path.clone()),
-				None,
-				None,
-			)?;
+			let path = Path::new(&file_path);
+			let commit = repo.find_commit((*commit_id).into())?;
+			let entry = path_entry(&commit.tree()?, path);
 
-			let contains_file = diff.deltas().len() > 0;
+			let parent_entry = commit
+				.parents()
+				.next()
+				.map(|parent| parent.tree())
+				.transpose()?
+				.and_then(|tree| path_entry(&tree, path));
 
-			Ok(contains_file)
+			Ok(entry != parent_entry)
 		},
 	))
 }
@@ -84,6 +94,11 @@ pub struct LogFilterSearchOptions {
 	pub fields: SearchFields,
 	///
 	pub options: SearchOptions,
+	/// the author name or email has to match this as well
+	pub author: Option<String>,
+	/// the commit has to change files matching this pathspec
+	/// (like `src/*.rs`) as well
+	pub pathspec: Option<String>,
 }
 
 ///
@@ -102,6 +117,8 @@ impl LogFilterSearch {
 		if !options.options.contains(SearchOptions::CASE_SENSITIVE) {
 			options.search_pattern =
 				options.search_pattern.to_lowercase();
+			options.author =
+				options.author.map(|author| author.to_lowercase());
 		}
 		Self {
 			matcher: fuzzy_matcher::skim::SkimMatcherV2::default(),
@@ -130,24 +147,55 @@ impl LogFilterSearch {
 
 	///
 	pub fn match_text(&self, text: &str) -> bool {
+		self.match_pattern(text, &self.options.search_pattern)
+	}
+
+	fn match_pattern(&self, text: &str, pattern: &str) -> bool {
 		if self.options.options.contains(SearchOptions::FUZZY_SEARCH)
 		{
-			self.matcher
-				.fuzzy_match(
-					text,
-					self.options.search_pattern.as_str(),
-				)
-				.is_some()
+			self.matcher.fuzzy_match(text, pattern).is_some()
 		} else if self
 			.options
 			.options
 			.contains(SearchOptions::CASE_SENSITIVE)
 		{
-			text.contains(self.options.search_pattern.as_str())
+			text.contains(pattern)
 		} else {
-			text.to_lowercase()
-				.contains(self.options.search_pattern.as_str())
+			text.to_lowercase().contains(pattern)
+		}
+	}
+
+	/// `LogFilterSearchOptions::author` and `pathspec`
+	fn match_conditions(
+		&self,
+		repo: &Repository,
+		commit: &Commit<'_>,
+	) -> Result<bool> {
+		if let Some(pattern) = &self.options.author {
+			let author = commit.author();
+			let found = [author.name(), author.email()]
+				.into_iter()
+				.flatten()
+				.any(|text| self.match_pattern(text, pattern));
+			if !found {
+				return Ok(false);
+			}
 		}
+
+		if let Some(pathspec) = &self.options.pathspec {
+			let diff = get_commit_diff(
+				repo,
+				commit.id().into(),
+				Some(pathspec.clone()),
+				None,
+				None,
+			)?;
+			if diff.deltas().len() == 0 {
+				return Ok(false);
+			}
+		}
+
+		Ok(true)
 	}
 }
 
@@ -212,10 +260,141 @@ pub fn filter_commit_by_search(
 				})
 				.unwrap_or_default();
 
-			Ok(msg_summary_match
+			Ok((msg_summary_match
 				|| msg_body_match
 				|| file_match
 				|| authors_match)
+				&& filter.match_conditions(repo, &commit)?)
 		},
 	))
 }
+
+/// up to `limit` commits reachable from HEAD (newest first) matching
+/// `options`, the walk stops as soon as `limit` is reached
+pub fn search_commits(
+	repo_path: &RepoPath,
+	options: LogFilterSearchOptions,
+	limit: usize,
+) -> Result<Vec<CommitId>> {
+	scope_time!("search_commits");
+
+	let repo = repo(repo_path)?;
+	let filter =
+		filter_commit_by_search(LogFilterSearch::new(options));
+
+	let mut walk = repo.revwalk()?;
+	walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+	walk.push_head()?;
+
+	let mut res = Vec::new();
+	for id in walk {
+		if res.len() == limit {
+			break;
+		}
+
+		let id = CommitId::new(id?);
+		if filter(&repo, &id)? {
+			res.push(id);
+		}
+	}
+
+	Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		filter_commit_by_search, search_commits, LogFilterSearch,
+		LogFilterSearchOptions, SearchFields,
+	};
+	use crate::sync::{
+		tests::{repo_init_empty, write_commit_file},
+		CommitId, RepoPath,
+	};
+	use git2::Repository;
+
+	fn set_author(repo: &Repository, name: &str, email: &str) {
+		let mut config = repo.config().unwrap();
+		config.set_str("user.name", name).unwrap();
+		config.set_str("user.email", email).unwrap();
+	}
+
+	fn search(
+		repo_path: &RepoPath,
+		options: &LogFilterSearchOptions,
+	) -> Vec<CommitId> {
+		search_commits(repo_path, options.clone(), 100).unwrap()
+	}
+
+	#[test]
+	fn test_search_commits() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		set_author(&repo, "Alice", "alice@example.com");
+		let c1 = write_commit_file(
+			&repo,
+			"src/lib.rs",
+			"a",
+			"Add lib\n\nFixes the Parser",
+		);
+		let c2 = write_commit_file(&repo, "README.md", "a", "docs");
+
+		set_author(&repo, "Bob", "bob@example.com");
+		let c3 =
+			write_commit_file(&repo, "src/main.rs", "a", "add main");
+		let c4 =
+			write_commit_file(&repo, "README.md", "b", "parser docs");
+
+		assert_eq!(
+			search(repo_path, &LogFilterSearchOptions::default())
+				.len(),
+			4
+		);
+
+		let message = LogFilterSearchOptions {
+			search_pattern: String::from("PARSER"),
+			fields: SearchFields::MESSAGE_SUMMARY
+				| SearchFields::MESSAGE_BODY,
+			..LogFilterSearchOptions::default()
+		};
+		assert_eq!(search(repo_path, &message), vec![c4, c1]);
+
+		let author = LogFilterSearchOptions {
+			author: Some(String::from("ALICE@")),
+			..LogFilterSearchOptions::default()
+		};
+		assert_eq!(search(repo_path, &author), vec![c2, c1]);
+
+		let path = LogFilterSearchOptions {
+			pathspec: Some(String::from("src/*.rs")),
+			..LogFilterSearchOptions::default()
+		};
+		assert_eq!(search(repo_path, &path), vec![c3, c1]);
+
+		let all = LogFilterSearchOptions {
+			search_pattern: String::from("add"),
+			author: Some(String::from("bob")),
+			pathspec: Some(String::from("src")),
+			..message.clone()
+		};
+		assert_eq!(search(repo_path, &all), vec![c3]);
+
+		assert_eq!(
+			search_commits(
+				repo_path,
+				LogFilterSearchOptions::default(),
+				2
+			)
+			.unwrap(),
+			vec![c4, c3]
+		);
+
+		let filter =
+			filter_commit_by_search(LogFilterSearch::new(message));
+		assert!(filter(&repo, &c1).unwrap());
+		assert!(!filter(&repo, &c2).unwrap());
+	}
+}
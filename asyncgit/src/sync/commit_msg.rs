@@ -0,0 +1,411 @@
+//! pure helpers to clean up, split and check commit messages
+
+use super::CommitMessage;
+
+/// default subject length limit (see `CommitMsgLimits`)
+pub const SUBJECT_LIMIT: usize = 50;
+/// default body line length limit (see `CommitMsgLimits`)
+pub const BODY_LIMIT: usize = 72;
+
+/// line lengths `validate_commit_msg` warns about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitMsgLimits {
+	/// max chars of the first line
+	pub subject: usize,
+	/// max chars of any other line
+	pub body: usize,
+}
+
+impl Default for CommitMsgLimits {
+	fn default() -> Self {
+		Self {
+			subject: SUBJECT_LIMIT,
+			body: BODY_LIMIT,
+		}
+	}
+}
+
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitMsgWarning {
+	/// subject has more chars than the limit
+	SubjectTooLong(usize),
+	/// subject is not followed by a blank line
+	NoBlankLine,
+	/// line (0 based) of the body has more chars than the limit
+	BodyLineTooLong {
+		///
+		line: usize,
+		///
+		len: usize,
+	},
+}
+
+/// like `git commit --cleanup=strip` (without the final newline):
+/// drops lines starting with `comment_char`, trailing whitespace and
+/// leading, trailing or repeated blank lines. CRLF becomes LF
+pub fn cleanup_commit_msg(
+	msg: &str,
+	comment_char: Option<char>,
+) -> String {
+	let mut lines: Vec<&str> = Vec::new();
+
+	for line in msg.lines() {
+		if comment_char.is_some_and(|c| line.starts_with(c)) {
+			continue;
+		}
+
+		let line = line.trim_end();
+		if line.is_empty()
+			&& lines.last().map_or(true, |last| last.is_empty())
+		{
+			continue;
+		}
+
+		lines.push(line);
+	}
+
+	if lines.last().is_some_and(|last| last.is_empty()) {
+		lines.pop();
+	}
+
+	lines.join("\n")
+}
+
+/// splits `msg` on the first blank line, the lines of the first
+/// paragraph are joined into the subject (like git's `%s`)
+pub fn parse_commit_msg(msg: &str) -> CommitMessage {
+	let mut lines = msg.lines().skip_while(|l| l.trim().is_empty());
+
+	let subject = lines
+		.by_ref()
+		.take_while(|l| !l.trim().is_empty())
+		.map(str::trim)
+		.collect::<Vec<_>>()
+		.join(" ");
+
+	let body = lines
+		.skip_while(|l| l.trim().is_empty())
+		.collect::<Vec<_>>()
+		.join("\n");
+	let body = body.trim_end();
+
+	CommitMessage {
+		subject,
+		body: if body.is_empty() {
+			None
+		} else {
+			Some(body.to_string())
+		},
+	}
+}
+
+/// checks the (cleaned up) message against `limits` and for a
+/// blank line after the subject
+pub fn validate_commit_msg(
+	msg: &str,
+	limits: CommitMsgLimits,
+) -> Vec<CommitMsgWarning> {
+	let mut warnings = Vec::new();
+
+	for (line, text) in msg.lines().enumerate() {
+		let len = text.chars().count();
+
+		match line {
+			0 if len > limits.subject => {
+				warnings.push(CommitMsgWarning::SubjectTooLong(len));
+			}
+			1 if !text.trim().is_empty() => {
+				warnings.push(CommitMsgWarning::NoBlankLine);
+			}
+			0 => (),
+			_ if len > limits.body => {
+				warnings.push(CommitMsgWarning::BodyLineTooLong {
+					line,
+					len,
+				});
+			}
+			_ => (),
+		}
+	}
+
+	warnings
+}
+
+/// `Key: value` lines appended to a commit message
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommitTrailers {
+	entries: Vec<(String, String)>,
+}
+
+impl CommitTrailers {
+	///
+	#[must_use]
+	pub fn add(mut self, key: &str, value: &str) -> Self {
+		self.entries.push((key.to_string(), value.to_string()));
+		self
+	}
+
+	/// `who` formatted like `Name <email>`
+	#[must_use]
+	pub fn co_authored_by(self, who: &str) -> Self {
+		self.add("Co-authored-by", who)
+	}
+
+	/// `who` formatted like `Name <email>` (see `get_signoff`)
+	#[must_use]
+	pub fn signed_off_by(self, who: &str) -> Self {
+		self.add("Signed-off-by", who)
+	}
+
+	///
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+/// `Key: value` with a key of alphanumerics and dashes
+fn parse_trailer(line: &str) -> Option<(&str, &str)> {
+	let (key, value) = line.split_once(':')?;
+	let value = value.trim();
+
+	let valid_key = !key.is_empty()
+		&& key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+	(valid_key && !value.is_empty()).then_some((key, value))
+}
+
+/// the trailers of `msg`: its last paragraph if that is not the
+/// subject and consists of trailer lines only
+pub fn parse_trailers(msg: &str) -> Vec<(String, String)> {
+	trailer_block(msg).map_or_else(Vec::new, |block| {
+		block
+			.lines()
+			.filter_map(parse_trailer)
+			.map(|(key, value)| (key.to_string(), value.to_string()))
+			.collect()
+	})
+}
+
+fn trailer_block(msg: &str) -> Option<&str> {
+	let msg = msg.trim_end();
+	let start = msg.rfind("\n\n")?;
+
+	let block = msg[start..].trim_start();
+	if msg[..start].trim().is_empty() {
+		return None;
+	}
+
+	block
+		.lines()
+		.all(|line| parse_trailer(line).is_some())
+		.then_some(block)
+}
+
+/// appends `trailers` to `msg`: joins an existing trailer block or
+/// starts one after a blank line. trailers already present (same key,
+/// ignoring case, and value) are skipped
+pub fn append_trailers(
+	msg: &str,
+	trailers: &CommitTrailers,
+) -> String {
+	let mut existing = parse_trailers(msg);
+	let mut msg = msg.trim_end().to_string();
+
+	for (key, value) in &trailers.entries {
+		if existing
+			.iter()
+			.any(|(k, v)| k.eq_ignore_ascii_case(key) && v == value)
+		{
+			continue;
+		}
+
+		msg.push_str(if existing.is_empty() { "\n\n" } else { "\n" });
+		msg.push_str(&format!("{key}: {value}"));
+
+		existing.push((key.clone(), value.clone()));
+	}
+
+	msg
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cleanup() {
+		assert_eq!(cleanup_commit_msg("", Some('#')), "");
+		assert_eq!(
+			cleanup_commit_msg("subject", Some('#')),
+			"subject"
+		);
+		assert_eq!(
+			cleanup_commit_msg(
+				"subject  \r\n\r\nbody\r\n",
+				Some('#')
+			),
+			"subject\n\nbody"
+		);
+		assert_eq!(
+			cleanup_commit_msg(
+				"# template\n\n\nsubject\n\n\n\nbody\n# comment\n\n",
+				Some('#')
+			),
+			"subject\n\nbody"
+		);
+		assert_eq!(
+			cleanup_commit_msg("#1 fixed\n; comment", Some(';')),
+			"#1 fixed"
+		);
+		assert_eq!(
+			cleanup_commit_msg("#1 fixed\n\n", None),
+			"#1 fixed"
+		);
+		assert_eq!(
+			cleanup_commit_msg("# only\n#comments", Some('#')),
+			""
+		);
+	}
+
+	#[test]
+	fn test_parse() {
+		let msg = parse_commit_msg("subject");
+		assert_eq!(msg.subject, "subject");
+		assert_eq!(msg.body, None);
+
+		let msg =
+			parse_commit_msg("subject\r\n\r\nbody\r\n\r\nmore\r\n");
+		assert_eq!(msg.subject, "subject");
+		assert_eq!(msg.body.as_deref(), Some("body\n\nmore"));
+
+		let msg = parse_commit_msg("\nfirst\nsecond\n\n\nbody");
+		assert_eq!(msg.subject, "first second");
+		assert_eq!(msg.body.as_deref(), Some("body"));
+
+		let msg = parse_commit_msg("");
+		assert_eq!(msg.subject, "");
+		assert_eq!(msg.body, None);
+	}
+
+	#[test]
+	fn test_validate() {
+		let limits = CommitMsgLimits::default();
+
+		assert!(
+			validate_commit_msg("subject\n\nbody", limits).is_empty()
+		);
+		assert!(validate_commit_msg("subject", limits).is_empty());
+
+		assert_eq!(
+			validate_commit_msg(&"a".repeat(51), limits),
+			vec![CommitMsgWarning::SubjectTooLong(51)]
+		);
+		assert!(
+			validate_commit_msg(&"ä".repeat(50), limits).is_empty()
+		);
+
+		assert_eq!(
+			validate_commit_msg("subject\nbody", limits),
+			vec![CommitMsgWarning::NoBlankLine]
+		);
+
+		assert_eq!(
+			validate_commit_msg(
+				&format!("subject\n\nbody\n{}", "b".repeat(73)),
+				limits
+			),
+			vec![CommitMsgWarning::BodyLineTooLong {
+				line: 3,
+				len: 73
+			}]
+		);
+
+		assert_eq!(
+			validate_commit_msg(
+				&"a".repeat(72),
+				CommitMsgLimits {
+					subject: 72,
+					body: 72
+				}
+			),
+			vec![]
+		);
+	}
+
+	#[test]
+	fn test_parse_trailers() {
+		assert!(parse_trailers("subject").is_empty());
+		assert!(parse_trailers("Fixes: #1").is_empty());
+		assert!(parse_trailers("subject\n\nbody").is_empty());
+		assert!(parse_trailers(
+			"subject\n\nFixes: #1\nin the middle\n\nbody"
+		)
+		.is_empty());
+		assert!(parse_trailers(
+			"subject\n\nbody\n\nSigned-off-by: a <a@b>\nnot a trailer"
+		)
+		.is_empty());
+		assert!(parse_trailers("subject\n\nsee http://foo: bar")
+			.is_empty());
+
+		assert_eq!(
+			parse_trailers(
+				"subject\n\nFixes: #1\n\nbody\n\nSigned-off-by: a <a@b>\nCo-authored-by: b <b@c>\n"
+			),
+			vec![
+				(
+					String::from("Signed-off-by"),
+					String::from("a <a@b>")
+				),
+				(
+					String::from("Co-authored-by"),
+					String::from("b <b@c>")
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn test_append_trailers() {
+		let trailers = CommitTrailers::default()
+			.co_authored_by("b <b@c>")
+			.signed_off_by("a <a@b>");
+
+		assert_eq!(
+			append_trailers("subject\n", &trailers),
+			"subject\n\nCo-authored-by: b <b@c>\nSigned-off-by: a <a@b>"
+		);
+
+		assert_eq!(
+			append_trailers(
+				"subject\n\nbody\n\nsigned-off-by: a <a@b>",
+				&trailers
+			),
+			"subject\n\nbody\n\nsigned-off-by: a <a@b>\nCo-authored-by: b <b@c>"
+		);
+
+		assert_eq!(
+			append_trailers(
+				"subject\n\nFixes: #1\n\nbody",
+				&CommitTrailers::default().signed_off_by("a <a@b>")
+			),
+			"subject\n\nFixes: #1\n\nbody\n\nSigned-off-by: a <a@b>"
+		);
+
+		assert_eq!(
+			append_trailers(
+				"subject",
+				&CommitTrailers::default()
+					.signed_off_by("a <a@b>")
+					.signed_off_by("a <a@b>")
+			),
+			"subject\n\nSigned-off-by: a <a@b>"
+		);
+
+		assert_eq!(
+			append_trailers("subject", &CommitTrailers::default()),
+			"subject"
+		);
+	}
+}
@@ -1,26 +1,80 @@
 use super::{CommitId, RepoPath};
 use crate::{
-	error::Result,
-	sync::{repository::repo, utils::read_file},
+	error::{Error, Result},
+	sync::{
+		conflict::conflicting_paths,
+		repository::repo,
+		utils::{ensure_index_matches_head, read_file},
+	},
 };
+use git2::{Commit, ErrorCode};
 use scopetime::scope_time;
 
 const GIT_REVERT_HEAD_FILE: &str = "REVERT_HEAD";
 
 ///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevertOutcome {
+	/// the revert applied cleanly and was committed
+	Committed(CommitId),
+	/// the repo is left in the `Revert` state (`REVERT_HEAD`) with
+	/// these paths conflicting in the index
+	Conflicts(Vec<String>),
+}
+
+/// `Revert "<subject>"` message like the one of `git revert`
+fn revert_msg(commit: &Commit<'_>) -> String {
+	format!(
+		"Revert \"{}\"\n\nThis reverts commit {}.",
+		String::from_utf8_lossy(
+			commit.summary_bytes().unwrap_or_default()
+		),
+		commit.id()
+	)
+}
+
+/// reverts the changes of commit `id` in HEAD. a clean result is
+/// committed right away, otherwise the conflicts are left in the index
+/// to be resolved (see `commit_revert`) or aborted.
+/// fails with `Error::UncommittedChanges` if anything is staged,
+/// merge commits are not supported
 pub fn revert_commit(
 	repo_path: &RepoPath,
-	commit: CommitId,
-) -> Result<()> {
+	id: CommitId,
+) -> Result<RevertOutcome> {
 	scope_time!("revert");
 
 	let repo = repo(repo_path)?;
 
-	let commit = repo.find_commit(commit.into())?;
+	let commit = repo.find_commit(id.into())?;
+	if commit.parent_count() > 1 {
+		return Err(Error::RevertMergeCommit(id));
+	}
 
-	repo.revert(&commit, None)?;
+	ensure_index_matches_head(&repo)?;
 
-	Ok(())
+	repo.revert(&commit, None).map_err(|e| {
+		if e.code() == ErrorCode::Conflict {
+			Error::UncommittedChanges
+		} else {
+			Error::Git(e)
+		}
+	})?;
+
+	let index = repo.index()?;
+	if index.has_conflicts() {
+		return Ok(RevertOutcome::Conflicts(conflicting_paths(
+			&index,
+		)?));
+	}
+
+	match commit_revert(repo_path, &revert_msg(&commit)) {
+		Err(Error::NothingToCommit) => {
+			repo.cleanup_state()?;
+			Err(Error::NothingToCommit)
+		}
+		res => res.map(RevertOutcome::Committed),
+	}
 }
 
 ///
@@ -49,3 +103,142 @@ pub fn commit_revert(
 
 	Ok(id)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{revert_commit, RevertOutcome};
+	use crate::{
+		error::Error,
+		sync::{
+			abort_pending_state, repo_state, stage_add_file,
+			tests::{repo_init, write_commit_file},
+			utils::{repo_read_file, repo_write_file},
+			CommitId, RepoPath, RepoState,
+		},
+	};
+	use std::path::Path;
+
+	static HUNK_A: &str = "1   start\n2\n3\n4\n5   end\n";
+	static HUNK_B: &str = "1   start\n2   newa\n3\n4\n5   end\n";
+
+	#[test]
+	fn test_revert() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		let change =
+			write_commit_file(&repo, "bar.txt", HUNK_B, "c2");
+
+		let RevertOutcome::Committed(id) =
+			revert_commit(repo_path, change).unwrap()
+		else {
+			panic!("expected a commit");
+		};
+
+		let commit = repo.find_commit(id.into()).unwrap();
+		assert_eq!(
+			commit.message(),
+			Some(
+				format!(
+					"Revert \"c2\"\n\nThis reverts commit {change}."
+				)
+				.as_str()
+			)
+		);
+		assert_eq!(commit.parent_id(0).unwrap(), change.into());
+		assert_eq!(repo_read_file(&repo, "bar.txt").unwrap(), HUNK_A);
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+	}
+
+	#[test]
+	fn test_revert_staged_changes() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		let change =
+			write_commit_file(&repo, "bar.txt", HUNK_B, "c2");
+
+		repo_write_file(&repo, "unrelated", "staged").unwrap();
+		stage_add_file(repo_path, Path::new("unrelated")).unwrap();
+
+		assert!(matches!(
+			revert_commit(repo_path, change),
+			Err(Error::UncommittedChanges)
+		));
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+		assert_eq!(
+			repo.head().unwrap().target().unwrap(),
+			change.into()
+		);
+		assert_eq!(repo_read_file(&repo, "bar.txt").unwrap(), HUNK_B);
+	}
+
+	#[test]
+	fn test_revert_conflict() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		let change =
+			write_commit_file(&repo, "bar.txt", HUNK_B, "c2");
+		write_commit_file(
+			&repo,
+			"bar.txt",
+			&HUNK_B.replace("newa", "newer"),
+			"c3",
+		);
+
+		assert_eq!(
+			revert_commit(repo_path, change).unwrap(),
+			RevertOutcome::Conflicts(vec![String::from("bar.txt")])
+		);
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Revert);
+		assert!(repo.path().join("REVERT_HEAD").exists());
+
+		abort_pending_state(repo_path).unwrap();
+
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+		assert!(!repo.path().join("REVERT_HEAD").exists());
+	}
+
+	#[test]
+	fn test_revert_merge_commit() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		let c2 = write_commit_file(&repo, "bar.txt", HUNK_B, "c2");
+
+		let sig = repo.signature().unwrap();
+		let parents = [
+			repo.find_commit(c2.into()).unwrap(),
+			repo.find_commit(c1.into()).unwrap(),
+		];
+		let merge = repo
+			.commit(
+				Some("HEAD"),
+				&sig,
+				&sig,
+				"merge",
+				&parents[0].tree().unwrap(),
+				&[&parents[0], &parents[1]],
+			)
+			.unwrap();
+
+		assert!(matches!(
+			revert_commit(repo_path, merge.into()),
+			Err(Error::RevertMergeCommit(id)) if id == CommitId::from(merge)
+		));
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+	}
+}
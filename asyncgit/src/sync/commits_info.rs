@@ -178,7 +178,7 @@ mod tests {
 		File::create(root.join(file_path))?.write_all(b"a")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		let c1 = commit(repo_path, "commit1").unwrap();
-		File::create(root.join(file_path))?.write_all(b"a")?;
+		File::create(root.join(file_path))?.write_all(b"b")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		let c2 = commit(repo_path, "commit2").unwrap();
 
@@ -212,6 +212,30 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_log_truncate() -> Result<()> {
+		let file_path = Path::new("foo");
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join(file_path))?.write_all(b"a")?;
+		stage_add_file(repo_path, file_path).unwrap();
+		let c1 = commit(repo_path, "äöü日本語\nbody").unwrap();
+
+		let res = get_commits_info(repo_path, &[c1], 4).unwrap();
+		assert_eq!(res[0].message.as_str(), "äöü");
+
+		let res = get_commits_info(repo_path, &[c1], 6).unwrap();
+		assert_eq!(res[0].message.as_str(), "äöü日");
+
+		let res = get_commits_info(repo_path, &[c1], 7).unwrap();
+		assert_eq!(res[0].message.as_str(), "äöü日本");
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_invalid_utf8() -> Result<()> {
 		let file_path = Path::new("foo");
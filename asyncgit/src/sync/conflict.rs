@@ -0,0 +1,501 @@
+//! sync git api for inspecting and resolving merge conflicts
+
+use super::{
+	diff::{patches_to_file_diff, DiffOptions, FileDiff},
+	repository::repo,
+	utils::work_dir,
+	RepoPath,
+};
+use crate::error::{Error, Result};
+use git2::{
+	build::CheckoutBuilder, Blob, Index, IndexConflict, IndexEntry,
+	Patch,
+};
+use scopetime::scope_time;
+use std::{fs, path::Path};
+
+/// stage bits of `IndexEntry::flags` (`GIT_INDEX_ENTRY_STAGEMASK`)
+const INDEX_ENTRY_STAGE_MASK: u16 = 0x3000;
+
+/// version taken by `resolve_conflict`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+	/// stage 2, the version of HEAD
+	Ours,
+	/// stage 3, the version being merged in
+	Theirs,
+}
+
+/// one version of a conflicted file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictEntry {
+	/// path of this version (differs between versions on renames)
+	pub path: String,
+	///
+	pub content: Vec<u8>,
+	///
+	pub binary: bool,
+}
+
+/// all versions of a conflicted file
+#[derive(Debug, Clone, Default)]
+pub struct ConflictVersions {
+	/// common ancestor, missing on add/add conflicts
+	pub ancestor: Option<ConflictEntry>,
+	/// our version, missing if we deleted the file
+	pub ours: Option<ConflictEntry>,
+	/// their version, missing if they deleted the file
+	pub theirs: Option<ConflictEntry>,
+	/// diff of `ancestor` to `ours`
+	pub ours_diff: FileDiff,
+	/// diff of `ancestor` to `theirs`
+	pub theirs_diff: FileDiff,
+}
+
+/// paths of all conflicts in `index` (our path on renames)
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) fn conflicting_paths(
+	index: &Index,
+) -> Result<Vec<String>> {
+	let mut paths = Vec::new();
+
+	for conflict in index.conflicts()? {
+		let conflict = conflict?;
+		if let Some(entry) =
+			conflict.our.or(conflict.their).or(conflict.ancestor)
+		{
+			paths.push(
+				String::from_utf8_lossy(&entry.path).to_string(),
+			);
+		}
+	}
+
+	Ok(paths)
+}
+
+/// conflict with any version at `path`
+fn find_conflict(index: &Index, path: &str) -> Result<IndexConflict> {
+	let matches = |entry: &Option<IndexEntry>| {
+		entry.as_ref().is_some_and(|e| e.path == path.as_bytes())
+	};
+
+	index
+		.conflicts()?
+		.filter_map(std::result::Result::ok)
+		.find(|c| {
+			matches(&c.ancestor)
+				|| matches(&c.our)
+				|| matches(&c.their)
+		})
+		.ok_or_else(|| {
+			Error::Generic(format!("no conflict found: {path}"))
+		})
+}
+
+/// resolves the conflicted file `path` by staging the `side` entry in
+/// place of the conflict and checking it out (keeping its mode and
+/// applying filters). if that side deleted the file it is removed
+/// from worktree and index
+pub fn resolve_conflict(
+	repo_path: &RepoPath,
+	path: &str,
+	side: ConflictSide,
+) -> Result<()> {
+	scope_time!("resolve_conflict");
+
+	let repo = repo(repo_path)?;
+	let mut index = repo.index()?;
+
+	let conflict = find_conflict(&index, path)?;
+
+	// versions may differ in path on renames
+	let mut paths: Vec<String> =
+		[&conflict.ancestor, &conflict.our, &conflict.their]
+			.into_iter()
+			.flatten()
+			.map(|entry| {
+				String::from_utf8_lossy(&entry.path).to_string()
+			})
+			.collect();
+	paths.sort();
+	paths.dedup();
+
+	for conflict_path in &paths {
+		index.conflict_remove(Path::new(conflict_path))?;
+	}
+
+	let taken = match side {
+		ConflictSide::Ours => conflict.our,
+		ConflictSide::Theirs => conflict.their,
+	};
+
+	if let Some(mut entry) = taken {
+		entry.flags &= !INDEX_ENTRY_STAGE_MASK;
+		index.add(&entry)?;
+		index.write()?;
+
+		// checkout applies the mode and filters (like crlf) of the entry
+		let entry_path = String::from_utf8_lossy(&entry.path);
+		let mut checkout = CheckoutBuilder::new();
+		checkout
+			.force()
+			.disable_pathspec_match(true)
+			.path(entry_path.as_ref());
+		repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
+	} else {
+		let file = work_dir(&repo)?.join(path);
+		if file.symlink_metadata().is_ok() {
+			fs::remove_file(file)?;
+		}
+		index.write()?;
+	}
+
+	Ok(())
+}
+
+/// returns ancestor, ours and theirs version of the conflicted file `path`
+pub fn get_conflict(
+	repo_path: &RepoPath,
+	path: &str,
+) -> Result<ConflictVersions> {
+	scope_time!("get_conflict");
+
+	let repo = repo(repo_path)?;
+	let conflict = find_conflict(&repo.index()?, path)?;
+
+	let blob = |entry: Option<IndexEntry>| {
+		entry
+			.map(|entry| -> Result<(String, Blob)> {
+				Ok((
+					String::from_utf8_lossy(&entry.path).to_string(),
+					repo.find_blob(entry.id)?,
+				))
+			})
+			.transpose()
+	};
+
+	let ancestor = blob(conflict.ancestor)?;
+	let ours = blob(conflict.our)?;
+	let theirs = blob(conflict.their)?;
+
+	let diff = |new: &Option<(String, Blob)>| -> Result<FileDiff> {
+		let options = DiffOptions::default();
+		let mut opt = git2::DiffOptions::new();
+		options.apply(&mut opt)?;
+
+		// a missing version diffs like an empty file
+		let patch = Patch::from_buffers(
+			ancestor.as_ref().map_or(&[], |(_, blob)| blob.content()),
+			ancestor.as_ref().map(|(path, _)| Path::new(path)),
+			new.as_ref().map_or(&[], |(_, blob)| blob.content()),
+			new.as_ref().map(|(path, _)| Path::new(path)),
+			Some(&mut opt),
+		)?;
+
+		patches_to_file_diff(
+			&[patch.delta()],
+			&mut [patch],
+			options.max_size,
+		)
+	};
+
+	let entry = |version: &Option<(String, Blob)>| {
+		version.as_ref().map(|(path, blob)| ConflictEntry {
+			path: path.clone(),
+			content: blob.content().to_vec(),
+			binary: blob.is_binary(),
+		})
+	};
+
+	Ok(ConflictVersions {
+		ours_diff: diff(&ours)?,
+		theirs_diff: diff(&theirs)?,
+		ancestor: entry(&ancestor),
+		ours: entry(&ours),
+		theirs: entry(&theirs),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sync::{
+		checkout_branch, commit, create_branch,
+		diff::DiffLineType,
+		merge_branch, stage_add_file, stage_addremoved,
+		status::{get_status, StatusItemType, StatusType},
+		tests::{get_statuses, repo_init, write_commit_file},
+		utils::repo_read_file,
+	};
+	use git2::{BranchType, Repository};
+
+	/// commits `base`, then `theirs` on branch `foo` and `ours` on master
+	/// and merges `foo`. `None` deletes the file.
+	fn conflict_repo(
+		base: Option<&str>,
+		ours: Option<&str>,
+		theirs: Option<&str>,
+	) -> (tempfile::TempDir, Repository, RepoPath) {
+		let (td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		if let Some(base) = base {
+			write_commit_file(&repo, "test.txt", base, "base");
+		}
+
+		let apply = |content: Option<&str>, msg| {
+			if let Some(content) = content {
+				write_commit_file(&repo, "test.txt", content, msg);
+			} else {
+				fs::remove_file(root.join("test.txt")).unwrap();
+				stage_addremoved(&repo_path, Path::new("test.txt"))
+					.unwrap();
+				commit(&repo_path, msg).unwrap();
+			}
+		};
+
+		create_branch(&repo_path, "foo").unwrap();
+		apply(theirs, "theirs");
+		checkout_branch(&repo_path, "master").unwrap();
+		apply(ours, "ours");
+
+		merge_branch(&repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		(td, repo, repo_path)
+	}
+
+	fn changes(diff: &FileDiff) -> Vec<(DiffLineType, String)> {
+		diff.hunks
+			.iter()
+			.flat_map(|hunk| hunk.lines.iter())
+			.filter(|line| {
+				matches!(
+					line.line_type,
+					DiffLineType::Add | DiffLineType::Delete
+				)
+			})
+			.map(|line| (line.line_type, line.content.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn test_modify_modify() {
+		let (_td, _repo, repo_path) = conflict_repo(
+			Some("a\nb\n"),
+			Some("a\nours\n"),
+			Some("a\ntheirs\n"),
+		);
+
+		let res = get_conflict(&repo_path, "test.txt").unwrap();
+
+		assert_eq!(res.ancestor.unwrap().content, b"a\nb\n".to_vec());
+		assert_eq!(res.ours.unwrap().content, b"a\nours\n".to_vec());
+		assert_eq!(
+			res.theirs.unwrap().content,
+			b"a\ntheirs\n".to_vec()
+		);
+		assert_eq!(
+			changes(&res.ours_diff),
+			vec![
+				(DiffLineType::Delete, String::from("b")),
+				(DiffLineType::Add, String::from("ours")),
+			]
+		);
+		assert_eq!(
+			changes(&res.theirs_diff),
+			vec![
+				(DiffLineType::Delete, String::from("b")),
+				(DiffLineType::Add, String::from("theirs")),
+			]
+		);
+
+		assert!(get_conflict(&repo_path, "other.txt").is_err());
+	}
+
+	#[test]
+	fn test_add_add() {
+		let (_td, _repo, repo_path) =
+			conflict_repo(None, Some("ours\n"), Some("theirs\n"));
+
+		let res = get_conflict(&repo_path, "test.txt").unwrap();
+
+		assert!(res.ancestor.is_none());
+		assert_eq!(
+			changes(&res.ours_diff),
+			vec![(DiffLineType::Add, String::from("ours"))]
+		);
+		assert_eq!(
+			changes(&res.theirs_diff),
+			vec![(DiffLineType::Add, String::from("theirs"))]
+		);
+	}
+
+	#[test]
+	fn test_delete_modify() {
+		let (_td, _repo, repo_path) =
+			conflict_repo(Some("a\n"), None, Some("b\n"));
+
+		let res = get_conflict(&repo_path, "test.txt").unwrap();
+
+		assert!(res.ancestor.is_some());
+		assert!(res.ours.is_none());
+		assert_eq!(
+			changes(&res.ours_diff),
+			vec![(DiffLineType::Delete, String::from("a"))]
+		);
+		assert_eq!(res.theirs.unwrap().content, b"b\n".to_vec());
+	}
+
+	#[test]
+	fn test_binary() {
+		let (_td, _repo, repo_path) = conflict_repo(
+			Some("a\n"),
+			Some("\0ours"),
+			Some("\0theirs"),
+		);
+
+		let res = get_conflict(&repo_path, "test.txt").unwrap();
+
+		assert!(!res.ancestor.unwrap().binary);
+		assert!(res.ours.unwrap().binary);
+		assert!(res.theirs.unwrap().binary);
+		assert!(res.ours_diff.binary);
+		assert!(res.ours_diff.hunks.is_empty());
+	}
+
+	/// (workdir, stage) changes, asserting no conflict is left
+	fn resolved_statuses(repo_path: &RepoPath) -> (usize, usize) {
+		assert!(get_status(
+			repo_path,
+			&StatusType::WorkingDir.into()
+		)
+		.unwrap()
+		.iter()
+		.all(|item| item.status != StatusItemType::Conflicted));
+
+		get_statuses(repo_path)
+	}
+
+	#[test]
+	fn test_resolve_modify_modify() {
+		let setup = || {
+			conflict_repo(
+				Some("a\nb\n"),
+				Some("a\nours\n"),
+				Some("a\ntheirs\n"),
+			)
+		};
+
+		let (_td, repo, repo_path) = setup();
+		resolve_conflict(&repo_path, "test.txt", ConflictSide::Ours)
+			.unwrap();
+
+		assert_eq!(
+			repo_read_file(&repo, "test.txt").unwrap(),
+			"a\nours\n"
+		);
+		assert_eq!(resolved_statuses(&repo_path), (0, 0));
+		assert!(get_conflict(&repo_path, "test.txt").is_err());
+
+		let (_td, repo, repo_path) = setup();
+		resolve_conflict(
+			&repo_path,
+			"test.txt",
+			ConflictSide::Theirs,
+		)
+		.unwrap();
+
+		assert_eq!(
+			repo_read_file(&repo, "test.txt").unwrap(),
+			"a\ntheirs\n"
+		);
+		assert_eq!(resolved_statuses(&repo_path), (0, 1));
+	}
+
+	#[test]
+	fn test_resolve_delete_modify() {
+		let setup = || conflict_repo(Some("a\n"), None, Some("b\n"));
+
+		let (_td, repo, repo_path) = setup();
+		resolve_conflict(&repo_path, "test.txt", ConflictSide::Ours)
+			.unwrap();
+
+		assert!(!repo.workdir().unwrap().join("test.txt").exists());
+		assert_eq!(resolved_statuses(&repo_path), (0, 0));
+
+		let (_td, repo, repo_path) = setup();
+		resolve_conflict(
+			&repo_path,
+			"test.txt",
+			ConflictSide::Theirs,
+		)
+		.unwrap();
+
+		assert_eq!(repo_read_file(&repo, "test.txt").unwrap(), "b\n");
+		assert_eq!(resolved_statuses(&repo_path), (0, 1));
+	}
+
+	#[test]
+	fn test_resolve_add_add() {
+		let (_td, repo, repo_path) =
+			conflict_repo(None, Some("ours\n"), Some("theirs\n"));
+
+		resolve_conflict(
+			&repo_path,
+			"test.txt",
+			ConflictSide::Theirs,
+		)
+		.unwrap();
+
+		assert_eq!(
+			repo_read_file(&repo, "test.txt").unwrap(),
+			"theirs\n"
+		);
+		assert_eq!(resolved_statuses(&repo_path), (0, 1));
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_resolve_keeps_mode() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "base\n", "base");
+		create_branch(repo_path, "foo").unwrap();
+		fs::write(root.join("test.txt"), "theirs\n").unwrap();
+		fs::set_permissions(
+			root.join("test.txt"),
+			fs::Permissions::from_mode(0o755),
+		)
+		.unwrap();
+		stage_add_file(repo_path, Path::new("test.txt")).unwrap();
+		commit(repo_path, "theirs").unwrap();
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "ours\n", "ours");
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		resolve_conflict(repo_path, "test.txt", ConflictSide::Theirs)
+			.unwrap();
+
+		let mode = fs::metadata(root.join("test.txt"))
+			.unwrap()
+			.permissions()
+			.mode();
+		assert_eq!(mode & 0o111, 0o111);
+		let index = repo.index().unwrap();
+		let entry = index.get_path(Path::new("test.txt"), 0).unwrap();
+		assert_eq!(entry.mode, 0o100_755);
+		assert_eq!(
+			repo_read_file(&repo, "test.txt").unwrap(),
+			"theirs\n"
+		);
+	}
+}
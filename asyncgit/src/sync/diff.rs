@@ -2,7 +2,8 @@
 
 use super::{
 	commit_files::{
-		get_commit_diff, get_compare_commits_diff, OldNew,
+		get_commit_diff, get_commit_parent_diff,
+		get_compare_commits_diff, OldNew,
 	},
 	utils::{get_head_repo, work_dir},
 	CommitId, RepoPath,
@@ -11,15 +12,27 @@ use crate::{
 	error::Error,
 	error::Result,
 	hash,
-	sync::{get_stashes, repository::repo},
+	sync::{get_stashes, repository::repo, status::StatusItemType},
 };
 use easy_cast::Conv;
 use git2::{
-	Delta, Diff, DiffDelta, DiffFormat, DiffHunk, Patch, Repository,
+	ApplyLocation, ApplyOptions, AttrCheckFlags, AttrValue, Delta,
+	Diff, DiffDelta, DiffFindOptions, DiffFormat, DiffHunk, FileMode,
+	Patch, Repository,
 };
 use scopetime::scope_time;
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, fs, path::Path, rc::Rc};
+use std::{
+	cell::RefCell,
+	cmp,
+	collections::HashSet,
+	fs,
+	hash::{Hash, Hasher},
+	mem,
+	ops::Range,
+	path::Path,
+	rc::Rc,
+};
 
 /// type of diff of a single line
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
@@ -32,6 +45,14 @@ pub enum DiffLineType {
 	Add,
 	/// line deleted
 	Delete,
+	/// conflict marker (`<<<<<<<`, `|||||||`, `=======`, `>>>>>>>`)
+	ConflictMarker,
+	/// our side of a conflict
+	ConflictOurs,
+	/// common ancestor of a conflict (diff3 style markers)
+	ConflictBase,
+	/// their side of a conflict
+	ConflictTheirs,
 }
 
 impl From<git2::DiffLineType> for DiffLineType {
@@ -53,15 +74,105 @@ impl Default for DiffLineType {
 	}
 }
 
+/// line ending of a single diff line
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub enum LineEnding {
+	/// `\n`
+	#[default]
+	Lf,
+	/// `\r\n`
+	CrLf,
+	/// no newline (end of file)
+	None,
+}
+
 ///
 #[derive(Default, Clone, Hash, Debug)]
 pub struct DiffLine {
-	///
+	/// line content for display without line ending
+	/// (invalid utf8 is replaced)
 	pub content: Box<str>,
+	/// line ending stripped from `content`
+	pub ending: LineEnding,
 	///
 	pub line_type: DiffLineType,
 	///
 	pub position: DiffLinePosition,
+	/// original bytes of the line if they differ from `content`
+	pub raw: Option<Box<[u8]>>,
+	/// line is not valid utf8, see `raw`
+	pub invalid_utf8: bool,
+	/// control characters in `content` are replaced by placeholders
+	/// (e.g. `␛` for escape), see `raw`
+	pub sanitized: bool,
+	/// byte ranges of `content` differing from the paired
+	/// deleted/added line (see `enrich_diff`)
+	pub changes: Option<Vec<Range<usize>>>,
+	/// added line ending in spaces or tabs, like `git diff --check`
+	/// (see `annotate_whitespace`)
+	pub has_trailing_whitespace: bool,
+	/// columns spanned by each tab of `content`
+	/// (see `annotate_whitespace`)
+	pub tab_widths: Vec<usize>,
+}
+
+impl DiffLine {
+	/// original bytes of the line (without line ending)
+	pub fn bytes(&self) -> &[u8] {
+		self.raw.as_deref().unwrap_or(self.content.as_bytes())
+	}
+
+	/// line of `bytes` including its line ending
+	fn from_bytes(
+		bytes: &[u8],
+		line_type: DiffLineType,
+		position: DiffLinePosition,
+	) -> Self {
+		let ending = if bytes.ends_with(b"\r\n") {
+			LineEnding::CrLf
+		} else if bytes.ends_with(b"\n") {
+			LineEnding::Lf
+		} else {
+			LineEnding::None
+		};
+		let end = bytes
+			.iter()
+			.rposition(|b| !is_newline(char::from(*b)))
+			.map_or(0, |idx| idx + 1);
+		let bytes = &bytes[..end];
+
+		let (content, invalid_utf8) = std::str::from_utf8(bytes)
+			.map_or_else(
+				|_| (String::from_utf8_lossy(bytes), true),
+				|content| (content.into(), false),
+			);
+		let sanitized = sanitize_control_chars(&content);
+		let raw = (invalid_utf8 || sanitized.is_some())
+			.then(|| Box::from(bytes));
+
+		Self {
+			invalid_utf8,
+			sanitized: sanitized.is_some(),
+			content: sanitized.unwrap_or_else(|| content.into()),
+			ending,
+			raw,
+			line_type,
+			position,
+			changes: None,
+			has_trailing_whitespace: false,
+			tab_widths: Vec::new(),
+		}
+	}
+}
+
+impl From<&git2::DiffLine<'_>> for DiffLine {
+	fn from(line: &git2::DiffLine<'_>) -> Self {
+		Self::from_bytes(
+			line.content(),
+			line.origin_value().into(),
+			DiffLinePosition::from(line),
+		)
+	}
 }
 
 ///
@@ -89,11 +200,16 @@ impl From<&git2::DiffLine<'_>> for DiffLinePosition {
 	}
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Hash)]
-pub(crate) struct HunkHeader {
+/// header of a hunk: `@@ -old_start,old_lines +new_start,new_lines @@`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HunkHeader {
+	/// first line of the hunk in the old file
 	pub old_start: u32,
+	/// number of lines of the hunk in the old file
 	pub old_lines: u32,
+	/// first line of the hunk in the new file
 	pub new_start: u32,
+	/// number of lines of the hunk in the new file
 	pub new_lines: u32,
 }
 
@@ -111,31 +227,194 @@ impl From<DiffHunk<'_>> for HunkHeader {
 /// single diff hunk
 #[derive(Default, Clone, Hash, Debug)]
 pub struct Hunk {
+	/// hunk header
+	pub header: HunkHeader,
 	/// hash of the hunk header
 	pub header_hash: u64,
 	/// list of `DiffLine`s
 	pub lines: Vec<DiffLine>,
+	/// staged state of the changes (see `get_diff_combined`)
+	pub stage: Option<HunkStage>,
+	/// stable across runs, see `hunk_id`
+	pub id: u64,
+}
+
+/// [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function),
+/// unlike `DefaultHasher` it yields the same hashes in every run
+struct StableHasher(u64);
+
+impl StableHasher {
+	const fn new() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for StableHasher {
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+
+	fn finish(&self) -> u64 {
+		self.0
+	}
+}
+
+/// id of a hunk for keeping track of it across refreshes (and in
+/// cache keys), made of the file path, the header and the changed lines.
+/// context lines are left out, but the header holds the line numbers:
+/// edits that move the hunk up or down change its id.
+pub fn hunk_id(
+	file_path: &str,
+	header: &HunkHeader,
+	lines: &[DiffLine],
+) -> u64 {
+	let mut hasher = StableHasher::new();
+	file_path.hash(&mut hasher);
+	header.hash(&mut hasher);
+	for line in lines.iter().filter(|line| {
+		matches!(
+			line.line_type,
+			DiffLineType::Add | DiffLineType::Delete
+		)
+	}) {
+		line.line_type.hash(&mut hasher);
+		line.bytes().hash(&mut hasher);
+	}
+	hasher.finish()
+}
+
+fn set_hunk_ids(diff: &mut FileDiff, file_path: &str) {
+	for hunk in &mut diff.hunks {
+		hunk.id = hunk_id(file_path, &hunk.header, &hunk.lines);
+	}
+}
+
+fn delta_status(delta: &DiffDelta) -> StatusItemType {
+	match delta.status() {
+		Delta::Untracked => StatusItemType::New,
+		Delta::Conflicted => StatusItemType::Conflicted,
+		status => status.into(),
+	}
+}
+
+/// new (or for deletions old) path of `delta`
+fn delta_path(delta: &DiffDelta) -> String {
+	delta
+		.new_file()
+		.path()
+		.or_else(|| delta.old_file().path())
+		.map(|path| path.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
+
+/// whether the changes of a hunk are in the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HunkStage {
+	/// all changes are staged
+	Staged,
+	/// no change is staged
+	Unstaged,
+	/// some changes are staged
+	Mixed,
+}
+
+/// commits a submodule pointed to before and after
+#[derive(Default, Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct SubmoduleDiff {
+	/// `None` for an added submodule
+	pub old: Option<CommitId>,
+	/// `None` for a removed submodule
+	pub new: Option<CommitId>,
+	/// modified or untracked files inside the submodule
+	pub dirty: bool,
 }
 
 /// collection of hunks, sum of all diff lines
 #[derive(Default, Clone, Hash, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct FileDiff {
+	/// path of the file (new path of a renamed file)
+	pub path: String,
+	/// how the file changed
+	pub status: Option<StatusItemType>,
 	/// list of hunks
 	pub hunks: Vec<Hunk>,
 	/// lines total summed up over hunks
 	pub lines: usize,
 	///
 	pub untracked: bool,
+	/// binary file, no lines are collected
+	pub binary: bool,
+	/// file exceeds `DiffOptions::max_size`, no lines are collected
+	pub too_large: bool,
+	/// old or new version is a symlink, lines are the link targets
+	pub symlink: bool,
+	/// unresolved merge conflict, lines are the workdir content
+	/// with conflict regions tagged (see `DiffLineType::ConflictOurs`)
+	pub conflicted: bool,
+	/// old and new file mode (e.g. `0o100644` and `0o100755`)
+	/// if they differ
+	pub mode_change: Option<(u32, u32)>,
+	/// old and new file mode if the kind of file changed
+	/// (e.g. file to symlink), see `file_kind`
+	pub typechange: Option<(u32, u32)>,
+	/// the file is a submodule, there are no hunks
+	pub submodule: Option<SubmoduleDiff>,
+	/// original path of a renamed file
+	pub old_path: Option<String>,
+	/// source path of a copied file (see `DiffOptions::detect_copies`)
+	pub copied_from: Option<String>,
 	/// old and new file size in bytes
 	pub sizes: (u64, u64),
 	/// size delta in bytes
 	pub size_delta: i64,
+	/// the file is a git lfs pointer, lines are the pointer content
+	pub is_lfs_pointer: bool,
+	/// object sizes of the old and new lfs pointer
+	pub lfs_sizes: (Option<u64>, Option<u64>),
+}
+
+/// tab width the diff lines are annotated with by default,
+/// the diff view expands every tab to this many spaces
+pub const DEFAULT_TAB_WIDTH: usize = 2;
+
+/// diffs with more lines than this are not passed through `enrich_diff`
+const MAX_ENRICH_LINES: usize = 10_000;
+
+/// files bigger than this (in bytes) are not previewed by default
+pub const DEFAULT_MAX_DIFF_SIZE: u64 = 1024 * 1024;
+
+/// algorithm used to compute the diff
+#[derive(
+	Debug,
+	Default,
+	Hash,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Serialize,
+	Deserialize,
+)]
+pub enum DiffAlgorithm {
+	/// git's default
+	#[default]
+	Myers,
+	/// myers but spend extra time to find the smallest diff
+	Minimal,
+	/// see `git diff --patience`
+	Patience,
 }
 
 /// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
 #[derive(
 	Debug, Hash, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
 )]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct DiffOptions {
 	/// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
 	pub ignore_whitespace: bool,
@@ -143,6 +422,18 @@ pub struct DiffOptions {
 	pub context: u32,
 	/// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
 	pub interhunk_lines: u32,
+	/// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
+	pub ignore_whitespace_change: bool,
+	/// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
+	pub ignore_whitespace_eol: bool,
+	/// see <https://libgit2.org/libgit2/#HEAD/type/git_diff_options>
+	/// (also limits the preview of untracked files)
+	pub max_size: u64,
+	/// diff a new file against a similar existing one instead
+	/// of showing it as entirely added
+	pub detect_copies: bool,
+	///
+	pub algorithm: DiffAlgorithm,
 }
 
 impl Default for DiffOptions {
@@ -151,10 +442,38 @@ impl Default for DiffOptions {
 			ignore_whitespace: false,
 			context: 3,
 			interhunk_lines: 0,
+			ignore_whitespace_change: false,
+			ignore_whitespace_eol: false,
+			max_size: DEFAULT_MAX_DIFF_SIZE,
+			detect_copies: false,
+			algorithm: DiffAlgorithm::Myers,
 		}
 	}
 }
 
+impl DiffOptions {
+	/// applies these options onto the libgit2 diff options
+	pub(crate) fn apply(
+		self,
+		opt: &mut git2::DiffOptions,
+	) -> Result<()> {
+		opt.context_lines(self.context);
+		opt.ignore_whitespace(self.ignore_whitespace);
+		opt.ignore_whitespace_change(self.ignore_whitespace_change);
+		opt.ignore_whitespace_eol(self.ignore_whitespace_eol);
+		opt.interhunk_lines(self.interhunk_lines);
+		opt.max_size(i64::try_from(self.max_size)?);
+		opt.minimal(self.algorithm == DiffAlgorithm::Minimal);
+		opt.patience(self.algorithm == DiffAlgorithm::Patience);
+
+		Ok(())
+	}
+}
+
+/// diff of `p` either in `stage` or workdir.
+/// if `p` is the target of a rename (or a copy, see
+/// `DiffOptions::detect_copies`) the diff contains a single
+/// renamed/copied delta instead of an added file.
 pub(crate) fn get_diff_raw<'a>(
 	repo: &'a Repository,
 	p: &str,
@@ -164,14 +483,160 @@ pub(crate) fn get_diff_raw<'a>(
 ) -> Result<Diff<'a>> {
 	// scope_time!("get_diff_raw");
 
+	let diff = diff_pathspecs(
+		repo,
+		&[p],
+		PathspecMatch::Literal,
+		stage,
+		reverse,
+		options,
+		false,
+	)?;
+
+	let possibly_renamed = diff.deltas().len() == 1
+		&& diff.deltas().next().is_some_and(|delta| {
+			matches!(delta.status(), Delta::Added | Delta::Untracked)
+		});
+
+	if possibly_renamed {
+		if let Some(old_path) =
+			find_similar_source(repo, p, stage, options)?
+		{
+			let mut diff = diff_pathspecs(
+				repo,
+				&[old_path.as_str(), p],
+				PathspecMatch::Literal,
+				stage,
+				reverse,
+				options,
+				detect_copies(options),
+			)?;
+			diff.find_similar(Some(&mut similar_find_options(
+				options,
+			)))?;
+
+			return Ok(diff);
+		}
+	}
+
+	Ok(diff)
+}
+
+fn detect_copies(options: Option<DiffOptions>) -> bool {
+	options.is_some_and(|options| options.detect_copies)
+}
+
+fn similar_find_options(
+	options: Option<DiffOptions>,
+) -> DiffFindOptions {
+	let mut opt = DiffFindOptions::new();
+	opt.renames(true).for_untracked(true);
+	if detect_copies(options) {
+		opt.copies(true)
+			.copies_from_unmodified(true)
+			.remove_unmodified(true);
+	}
+	opt
+}
+
+/// the file `p` got renamed (or copied) from. only `p` and the deleted
+/// files are compared, copies can come from any file though.
+fn find_similar_source(
+	repo: &Repository,
+	p: &str,
+	stage: bool,
+	options: Option<DiffOptions>,
+) -> Result<Option<String>> {
+	let deleted;
+	let pathspecs = if detect_copies(options) {
+		Vec::new()
+	} else {
+		deleted = deleted_paths(repo, stage)?;
+		if deleted.is_empty() {
+			return Ok(None);
+		}
+		std::iter::once(p)
+			.chain(deleted.iter().map(String::as_str))
+			.collect::<Vec<_>>()
+	};
+
+	let mut diff = diff_pathspecs(
+		repo,
+		&pathspecs,
+		PathspecMatch::Literal,
+		stage,
+		false,
+		options,
+		detect_copies(options),
+	)?;
+	diff.find_similar(Some(&mut similar_find_options(options)))?;
+
+	let old_path = diff
+		.deltas()
+		.filter(|delta| {
+			matches!(delta.status(), Delta::Renamed | Delta::Copied)
+		})
+		.find(|delta| delta.new_file().path() == Some(Path::new(p)))
+		.and_then(|delta| {
+			delta.old_file().path().and_then(Path::to_str)
+		})
+		.map(String::from);
+
+	Ok(old_path)
+}
+
+/// paths deleted either in `stage` or workdir,
+/// untracked files are not walked
+fn deleted_paths(
+	repo: &Repository,
+	stage: bool,
+) -> Result<Vec<String>> {
+	let diff = if stage {
+		let tree = get_head_repo(repo)
+			.ok()
+			.map(|id| repo.find_commit(id.into())?.tree())
+			.transpose()?;
+		repo.diff_tree_to_index(tree.as_ref(), None, None)?
+	} else {
+		repo.diff_index_to_workdir(None, None)?
+	};
+
+	Ok(diff
+		.deltas()
+		.filter(|delta| delta.status() == Delta::Deleted)
+		.filter_map(|delta| {
+			delta.old_file().path().and_then(Path::to_str)
+		})
+		.map(String::from)
+		.collect())
+}
+
+/// how pathspecs are matched against paths
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PathspecMatch {
+	/// exact path (or directory prefix), `foo[1].txt` is just that
+	Literal,
+	/// fnmatch patterns like `*.rs`
+	Glob,
+}
+
+fn diff_pathspecs<'a>(
+	repo: &'a Repository,
+	pathspecs: &[&str],
+	matching: PathspecMatch,
+	stage: bool,
+	reverse: bool,
+	options: Option<DiffOptions>,
+	include_unmodified: bool,
+) -> Result<Diff<'a>> {
 	let mut opt = git2::DiffOptions::new();
-	if let Some(options) = options {
-		opt.context_lines(options.context);
-		opt.ignore_whitespace(options.ignore_whitespace);
-		opt.interhunk_lines(options.interhunk_lines);
+	options.unwrap_or_default().apply(&mut opt)?;
+	for p in pathspecs.iter().filter(|p| !p.is_empty()) {
+		opt.pathspec(p);
 	}
-	opt.pathspec(p);
+	opt.disable_pathspec_match(matching == PathspecMatch::Literal);
 	opt.reverse(reverse);
+	opt.include_unmodified(include_unmodified);
 
 	let diff = if stage {
 		// diff against head
@@ -210,354 +675,3092 @@ pub fn get_diff(
 	scope_time!("get_diff");
 
 	let repo = repo(repo_path)?;
-	let work_dir = work_dir(&repo)?;
 	let diff = get_diff_raw(&repo, p, stage, false, options)?;
 
-	raw_diff_to_file_diff(&diff, work_dir)
+	raw_diff_to_file_diff(&repo, &diff, options)
 }
 
-/// returns diff of a specific file inside a commit
-/// see `get_commit_diff`
-pub fn get_diff_commit(
+/// diffs of all files matching `pathspec` (e.g. a directory) either in
+/// `stage` or workdir, one `FileDiff` per file
+pub fn get_diff_files(
 	repo_path: &RepoPath,
-	id: CommitId,
-	p: String,
+	pathspec: &str,
+	stage: bool,
 	options: Option<DiffOptions>,
-) -> Result<FileDiff> {
-	scope_time!("get_diff_commit");
+) -> Result<Vec<FileDiff>> {
+	scope_time!("get_diff_files");
 
 	let repo = repo(repo_path)?;
-	let work_dir = work_dir(&repo)?;
-	let diff = get_commit_diff(
+	let diff = diff_pathspecs(
 		&repo,
-		id,
-		Some(p),
+		&[pathspec],
+		PathspecMatch::Literal,
+		stage,
+		false,
 		options,
-		Some(&get_stashes(repo_path)?.into_iter().collect()),
+		false,
 	)?;
 
-	raw_diff_to_file_diff(&diff, work_dir)
+	raw_diff_to_file_diffs(&repo, &diff, options)
 }
 
-/// get file changes of a diff between two commits
-pub fn get_diff_commits(
+/// diff of `p` from HEAD to the workdir, staged and unstaged changes
+/// combined. each hunk is tagged with its `HunkStage` and carries the
+/// `header_hash` of the overlapping workdir hunk (or staged hunk if
+/// fully staged), so `stage_hunk`/`unstage_hunk` keep working on it.
+pub fn get_diff_combined(
 	repo_path: &RepoPath,
-	ids: OldNew<CommitId>,
-	p: String,
+	p: &str,
 	options: Option<DiffOptions>,
 ) -> Result<FileDiff> {
-	scope_time!("get_diff_commits");
+	scope_time!("get_diff_combined");
 
-	let repo = repo(repo_path)?;
-	let work_dir = work_dir(&repo)?;
-	let diff =
-		get_compare_commits_diff(&repo, ids, Some(p), options)?;
+	let staged = get_diff(repo_path, p, true, options)?;
+	let mut unstaged = get_diff(repo_path, p, false, options)?;
 
-	raw_diff_to_file_diff(&diff, work_dir)
-}
-
-///
-//TODO: refactor into helper type with the inline closures as dedicated functions
-#[allow(clippy::too_many_lines)]
-fn raw_diff_to_file_diff(
-	diff: &Diff,
-	work_dir: &Path,
-) -> Result<FileDiff> {
-	let res = Rc::new(RefCell::new(FileDiff::default()));
-	{
-		let mut current_lines = Vec::new();
-		let mut current_hunk: Option<HunkHeader> = None;
+	if unstaged.untracked || unstaged.conflicted {
+		for hunk in &mut unstaged.hunks {
+			hunk.stage = Some(HunkStage::Unstaged);
+		}
+		return Ok(unstaged);
+	}
 
-		let res_cell = Rc::clone(&res);
-		let adder = move |header: &HunkHeader,
-		                  lines: &Vec<DiffLine>| {
-			let mut res = res_cell.borrow_mut();
-			res.hunks.push(Hunk {
-				header_hash: hash(header),
-				lines: lines.clone(),
-			});
-			res.lines += lines.len();
-		};
+	let repo = repo(repo_path)?;
 
-		let res_cell = Rc::clone(&res);
-		let mut put = |delta: DiffDelta,
-		               hunk: Option<DiffHunk>,
-		               line: git2::DiffLine| {
-			{
-				let mut res = res_cell.borrow_mut();
-				res.sizes = (
-					delta.old_file().size(),
-					delta.new_file().size(),
-				);
-				//TODO: use try_conv
-				res.size_delta = (i64::conv(res.sizes.1))
-					.saturating_sub(i64::conv(res.sizes.0));
-			}
-			if let Some(hunk) = hunk {
-				let hunk_header = HunkHeader::from(hunk);
+	let mut opt = git2::DiffOptions::new();
+	options.unwrap_or_default().apply(&mut opt)?;
+	opt.pathspec(p);
+	opt.disable_pathspec_match(true);
 
-				match current_hunk {
-					None => current_hunk = Some(hunk_header),
-					Some(h) => {
-						if h != hunk_header {
-							adder(&h, &current_lines);
-							current_lines.clear();
-							current_hunk = Some(hunk_header);
-						}
-					}
-				}
+	let tree = match get_head_repo(&repo) {
+		Ok(id) => Some(repo.find_commit(id.into())?.tree()?),
+		Err(_) => None,
+	};
+	let diff = repo.diff_tree_to_workdir_with_index(
+		tree.as_ref(),
+		Some(&mut opt),
+	)?;
 
-				let diff_line = DiffLine {
-					position: DiffLinePosition::from(&line),
-					content: String::from_utf8_lossy(line.content())
-						//Note: trim await trailing newline characters
-						.trim_matches(is_newline)
-						.into(),
-					line_type: line.origin_value().into(),
-				};
+	let mut res = raw_diff_to_file_diff(&repo, &diff, options)?;
+
+	// a deleted head line is staged if the index lacks it too,
+	// an added workdir line is unstaged if the index lacks it
+	let staged_deletes =
+		changed_lines(&staged, DiffLineType::Delete, |pos| {
+			pos.old_lineno
+		});
+	let unstaged_adds =
+		changed_lines(&unstaged, DiffLineType::Add, |pos| {
+			pos.new_lineno
+		});
+
+	for hunk in &mut res.hunks {
+		let (mut any_staged, mut any_unstaged) = (false, false);
+		for line in &hunk.lines {
+			let staged_line = match line.line_type {
+				DiffLineType::Delete => line
+					.position
+					.old_lineno
+					.is_some_and(|n| staged_deletes.contains(&n)),
+				DiffLineType::Add => line
+					.position
+					.new_lineno
+					.map_or(true, |n| !unstaged_adds.contains(&n)),
+				_ => continue,
+			};
+			any_staged |= staged_line;
+			any_unstaged |= !staged_line;
+		}
 
-				current_lines.push(diff_line);
-			}
+		let stage = match (any_staged, any_unstaged) {
+			(true, false) => HunkStage::Staged,
+			(true, true) => HunkStage::Mixed,
+			_ => HunkStage::Unstaged,
 		};
 
-		let new_file_diff = if diff.deltas().len() == 1 {
-			if let Some(delta) = diff.deltas().next() {
-				if delta.status() == Delta::Untracked {
-					let relative_path =
-						delta.new_file().path().ok_or_else(|| {
-							Error::Generic(
-								"new file path is unspecified."
-									.to_string(),
-							)
-						})?;
-
-					let newfile_path = work_dir.join(relative_path);
-
-					if let Some(newfile_content) =
-						new_file_content(&newfile_path)
-					{
-						let mut patch = Patch::from_buffers(
-							&[],
-							None,
-							newfile_content.as_slice(),
-							Some(&newfile_path),
-							None,
-						)?;
-
-						patch.print(
-							&mut |delta,
-							      hunk: Option<DiffHunk>,
-							      line: git2::DiffLine| {
-								put(delta, hunk, line);
-								true
-							},
-						)?;
-
-						true
-					} else {
-						false
-					}
-				} else {
-					false
-				}
-			} else {
-				false
-			}
+		let source = if stage == HunkStage::Staged {
+			staged.hunks.iter().find(|h| {
+				ranges_overlap(
+					(h.header.old_start, h.header.old_lines),
+					(hunk.header.old_start, hunk.header.old_lines),
+				)
+			})
 		} else {
-			false
+			unstaged.hunks.iter().find(|h| {
+				ranges_overlap(
+					(h.header.new_start, h.header.new_lines),
+					(hunk.header.new_start, hunk.header.new_lines),
+				)
+			})
 		};
-
-		if !new_file_diff {
-			diff.print(
-				DiffFormat::Patch,
-				move |delta, hunk, line: git2::DiffLine| {
-					put(delta, hunk, line);
-					true
-				},
-			)?;
-		}
-
-		if !current_lines.is_empty() {
-			adder(
-				&current_hunk.map_or_else(
-					|| Err(Error::Generic("invalid hunk".to_owned())),
-					Ok,
-				)?,
-				&current_lines,
-			);
-		}
-
-		if new_file_diff {
-			res.borrow_mut().untracked = true;
+		if let Some(source) = source {
+			hunk.header_hash = source.header_hash;
 		}
+		hunk.stage = Some(stage);
 	}
-	let res = Rc::try_unwrap(res)
-		.map_err(|_| Error::Generic("rc unwrap error".to_owned()))?;
-	Ok(res.into_inner())
-}
 
-const fn is_newline(c: char) -> bool {
-	c == '\n' || c == '\r'
+	Ok(res)
 }
 
-fn new_file_content(path: &Path) -> Option<Vec<u8>> {
-	if let Ok(meta) = fs::symlink_metadata(path) {
-		if meta.file_type().is_symlink() {
-			if let Ok(path) = fs::read_link(path) {
-				return Some(
-					path.to_str()?.to_string().as_bytes().into(),
-				);
-			}
-		} else if !meta.file_type().is_dir() {
-			if let Ok(content) = fs::read(path) {
-				return Some(content);
-			}
-		}
-	}
+fn changed_lines(
+	diff: &FileDiff,
+	line_type: DiffLineType,
+	lineno: fn(&DiffLinePosition) -> Option<u32>,
+) -> HashSet<u32> {
+	diff.hunks
+		.iter()
+		.flat_map(|hunk| hunk.lines.iter())
+		.filter(|line| line.line_type == line_type)
+		.filter_map(|line| lineno(&line.position))
+		.collect()
+}
 
-	None
+/// whether two `(start, lines)` ranges of hunk headers overlap
+const fn ranges_overlap(a: (u32, u32), b: (u32, u32)) -> bool {
+	let a_end = a.0.saturating_add(a.1.saturating_sub(1));
+	let b_end = b.0.saturating_add(b.1.saturating_sub(1));
+	a.0 <= b_end && b.0 <= a_end
 }
 
-#[cfg(test)]
-mod tests {
-	use super::{get_diff, get_diff_commit};
-	use crate::{
-		error::Result,
-		sync::{
-			commit, stage_add_file,
-			status::{get_status, StatusType},
-			tests::{get_statuses, repo_init, repo_init_empty},
-			RepoPath,
-		},
-	};
-	use std::{
-		fs::{self, File},
-		io::Write,
-		path::Path,
+/// workdir content of a conflicted file with the conflict regions
+/// tagged. a missing file (e.g. deleted on one side) or a directory
+/// has no hunks, a binary or too large one no lines
+fn conflicted_file_diff(
+	repo: &Repository,
+	p: &str,
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	let mut res = FileDiff {
+		conflicted: true,
+		path: p.to_string(),
+		status: Some(StatusItemType::Conflicted),
+		..FileDiff::default()
 	};
 
-	#[test]
-	fn test_untracked_subfolder() {
-		let (_td, repo) = repo_init().unwrap();
-		let root = repo.path().parent().unwrap();
-		let repo_path: &RepoPath =
-			&root.as_os_str().to_str().unwrap().into();
+	let path = work_dir(repo)?.join(p);
+	let size = match fs::symlink_metadata(&path) {
+		Ok(meta) if meta.is_file() => meta.len(),
+		_ => return Ok(res),
+	};
+	res.sizes = (0, size);
 
-		assert_eq!(get_statuses(repo_path), (0, 0));
+	if size > options.unwrap_or_default().max_size {
+		res.too_large = true;
+		return Ok(res);
+	}
 
-		fs::create_dir(root.join("foo")).unwrap();
-		File::create(root.join("foo/bar.txt"))
-			.unwrap()
-			.write_all(b"test\nfoo")
-			.unwrap();
+	let content = fs::read(&path)?;
+	if content.contains(&0)
+		|| diff_disabled_by_attributes(repo, Path::new(p))
+	{
+		res.binary = true;
+		return Ok(res);
+	}
 
-		assert_eq!(get_statuses(repo_path), (1, 0));
+	let mut line_type = DiffLineType::None;
+	let lines = content
+		.split_inclusive(|b| *b == b'\n')
+		.enumerate()
+		.map(|(idx, raw_line)| {
+			let mut line = DiffLine::from_bytes(
+				raw_line,
+				line_type,
+				DiffLinePosition {
+					old_lineno: None,
+					new_lineno: Some(u32::try_from(idx + 1)?),
+				},
+			);
+			let bytes = line.bytes();
 
+			let marker = if bytes.starts_with(b"<<<<<<<") {
+				Some(DiffLineType::ConflictOurs)
+			} else if bytes.starts_with(b"|||||||")
+				&& line_type == DiffLineType::ConflictOurs
+			{
+				Some(DiffLineType::ConflictBase)
+			} else if bytes == b"======="
+				&& line_type != DiffLineType::None
+			{
+				Some(DiffLineType::ConflictTheirs)
+			} else if bytes.starts_with(b">>>>>>>")
+				&& line_type == DiffLineType::ConflictTheirs
+			{
+				Some(DiffLineType::None)
+			} else {
+				None
+			};
+
+			if let Some(next) = marker {
+				line_type = next;
+				line.line_type = DiffLineType::ConflictMarker;
+			}
+
+			Ok(line)
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let header = HunkHeader {
+		old_start: 0,
+		old_lines: 0,
+		new_start: 1,
+		new_lines: u32::try_from(lines.len())?,
+	};
+
+	res.lines = lines.len();
+	res.hunks = vec![Hunk {
+		header,
+		header_hash: hash(&header),
+		id: hunk_id(p, &header, &lines),
+		lines,
+		stage: None,
+	}];
+
+	Ok(res)
+}
+
+/// writes the diff of `pathspec` (or of everything) either in
+/// `stage` or workdir as `git diff` compatible patch into `out`
+pub fn export_patch(
+	repo_path: &RepoPath,
+	pathspec: Option<String>,
+	stage: bool,
+	out: &Path,
+) -> Result<()> {
+	scope_time!("export_patch");
+
+	let repo = repo(repo_path)?;
+	let work_dir = work_dir(&repo)?;
+	let pathspec = pathspec.unwrap_or_default();
+	// big files would otherwise be exported as binary
+	let options = DiffOptions {
+		max_size: i64::MAX.unsigned_abs(),
+		..DiffOptions::default()
+	};
+	let diff = diff_pathspecs(
+		&repo,
+		&[pathspec.as_str()],
+		PathspecMatch::Literal,
+		stage,
+		false,
+		Some(options),
+		false,
+	)?;
+
+	let mut buf = Vec::new();
+
+	diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+		if delta.status() != Delta::Untracked {
+			put_patch_line(&mut buf, &line);
+		}
+		true
+	})?;
+
+	// untracked files have no content in `diff`
+	for delta in diff
+		.deltas()
+		.filter(|delta| delta.status() == Delta::Untracked)
+	{
+		let Some(path) = delta.new_file().path() else {
+			continue;
+		};
+		let Some(content) = new_file_content(&work_dir.join(path))
+		else {
+			continue;
+		};
+
+		let mut patch = Patch::from_buffers(
+			&[],
+			None,
+			content.as_slice(),
+			Some(path),
+			None,
+		)?;
+
+		let path = path.to_string_lossy();
+		let id = git2::Oid::hash_object(
+			git2::ObjectType::Blob,
+			content.as_slice(),
+		)?
+		.to_string();
+		buf.extend_from_slice(
+			format!(
+				"diff --git a/{path} b/{path}\n\
+				 new file mode {:o}\n\
+				 index 0000000..{}\n\
+				 --- /dev/null\n\
+				 +++ b/{path}\n",
+				u32::from(delta.new_file().mode()),
+				&id[..7],
+			)
+			.as_bytes(),
+		);
+
+		// file header of `patch` is that of a modification
+		patch.print(
+			&mut |_delta,
+			      _hunk: Option<DiffHunk>,
+			      line: git2::DiffLine| {
+				if line.origin_value()
+					!= git2::DiffLineType::FileHeader
+				{
+					put_patch_line(&mut buf, &line);
+				}
+				true
+			},
+		)?;
+	}
+
+	fs::write(out, buf)?;
+
+	Ok(())
+}
+
+/// hunk of a patch that does not apply (see `apply_patch`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedHunk {
+	/// path of the file the hunk targets
+	pub path: String,
+	///
+	pub header: HunkHeader,
+}
+
+impl std::fmt::Display for FailedHunk {
+	fn fmt(
+		&self,
+		f: &mut std::fmt::Formatter<'_>,
+	) -> std::fmt::Result {
+		write!(
+			f,
+			"{} @@ -{},{} +{},{} @@",
+			self.path,
+			self.header.old_start,
+			self.header.old_lines,
+			self.header.new_start,
+			self.header.new_lines
+		)
+	}
+}
+
+/// applies `patch_text` (e.g. created by `export_patch`) onto the
+/// workdir and/or index. nothing is applied if any hunk fails, those
+/// are reported in `Error::PatchDoesNotApply`.
+pub fn apply_patch(
+	repo_path: &RepoPath,
+	patch_text: &str,
+	location: ApplyLocation,
+) -> Result<()> {
+	scope_time!("apply_patch");
+
+	let repo = repo(repo_path)?;
+	let diff = Diff::from_buffer(patch_text.as_bytes())?;
+
+	let conflicts = repo
+		.index()?
+		.conflicts()?
+		.filter_map(std::result::Result::ok)
+		.flat_map(|c| [c.ancestor, c.our, c.their])
+		.flatten()
+		.map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+		.collect::<HashSet<_>>();
+	if let Some(path) = diff
+		.deltas()
+		.flat_map(|delta| {
+			[delta.old_file().path(), delta.new_file().path()]
+		})
+		.flatten()
+		.filter_map(Path::to_str)
+		.find(|path| conflicts.contains(*path))
+	{
+		return Err(Error::Generic(format!(
+			"patch targets conflicted file: {path}"
+		)));
+	}
+
+	let mut opt = ApplyOptions::new();
+	opt.check(true);
+	if let Err(e) = repo.apply(&diff, location, Some(&mut opt)) {
+		let failed = failed_hunks(&repo, &diff, location)?;
+		return Err(if failed.is_empty() {
+			e.into()
+		} else {
+			Error::PatchDoesNotApply(failed)
+		});
+	}
+
+	repo.apply(&diff, location, None)?;
+
+	Ok(())
+}
+
+/// checks each hunk of `diff` on its own
+fn failed_hunks(
+	repo: &Repository,
+	diff: &Diff,
+	location: ApplyLocation,
+) -> Result<Vec<FailedHunk>> {
+	let mut failed = Vec::new();
+
+	for (idx, delta) in diff.deltas().enumerate() {
+		let Some(patch) = Patch::from_diff(diff, idx)? else {
+			continue;
+		};
+		let path = delta_path(&delta);
+
+		for hunk_idx in 0..patch.num_hunks() {
+			let (hunk, _) = patch.hunk(hunk_idx)?;
+			let header = HunkHeader::from(hunk);
+
+			let mut delta_idx = 0;
+			let mut opt = ApplyOptions::new();
+			opt.check(true);
+			opt.delta_callback(|_delta| {
+				let res = delta_idx == idx;
+				delta_idx += 1;
+				res
+			});
+			opt.hunk_callback(|hunk| {
+				hunk.is_some_and(|hunk| {
+					HunkHeader::from(hunk) == header
+				})
+			});
+
+			if repo.apply(diff, location, Some(&mut opt)).is_err() {
+				failed.push(FailedHunk {
+					path: path.clone(),
+					header,
+				});
+			}
+		}
+	}
+
+	Ok(failed)
+}
+
+/// appends `line` the way `git diff` prints it
+fn put_patch_line(buf: &mut Vec<u8>, line: &git2::DiffLine) {
+	if matches!(
+		line.origin_value(),
+		git2::DiffLineType::Context
+			| git2::DiffLineType::Addition
+			| git2::DiffLineType::Deletion
+	) {
+		buf.extend_from_slice(
+			line.origin().encode_utf8(&mut [0; 4]).as_bytes(),
+		);
+	}
+	buf.extend_from_slice(line.content());
+}
+
+/// added/removed line counts of a single file
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct FileDiffStat {
+	/// path of the file
+	pub path: String,
+	/// number of added lines
+	pub insertions: usize,
+	/// number of removed lines
+	pub deletions: usize,
+	/// binary (or too large) file, no lines are counted
+	pub binary: bool,
+}
+
+/// returns the line stats of all changed files either in `stage` or workdir.
+/// `glob` optionally restricts this to files matching a pattern like `*.rs`
+pub fn get_diff_stats(
+	repo_path: &RepoPath,
+	glob: Option<&str>,
+	stage: bool,
+	options: Option<DiffOptions>,
+) -> Result<Vec<FileDiffStat>> {
+	scope_time!("get_diff_stats");
+
+	let repo = repo(repo_path)?;
+	let work_dir = work_dir(&repo)?;
+	let diff = diff_pathspecs(
+		&repo,
+		&glob.into_iter().collect::<Vec<_>>(),
+		PathspecMatch::Glob,
+		stage,
+		false,
+		options,
+		false,
+	)?;
+	let max_size = options.unwrap_or_default().max_size;
+
+	let mut res = Vec::with_capacity(diff.deltas().len());
+	for (idx, delta) in diff.deltas().enumerate() {
+		let path = delta_path(&delta);
+
+		let stat = if delta.status() == Delta::Untracked {
+			let newfile_path = work_dir.join(&path);
+			let too_large = fs::symlink_metadata(&newfile_path)
+				.is_ok_and(|meta| meta.len() > max_size);
+
+			let content = if too_large
+				|| diff_disabled_by_attributes(
+					&repo,
+					Path::new(&path),
+				) {
+				None
+			} else {
+				new_file_content(&newfile_path)
+			};
+
+			match content {
+				Some(content) if !content.contains(&0) => {
+					FileDiffStat {
+						path,
+						insertions: String::from_utf8_lossy(&content)
+							.lines()
+							.count(),
+						..FileDiffStat::default()
+					}
+				}
+				_ => FileDiffStat {
+					path,
+					binary: true,
+					..FileDiffStat::default()
+				},
+			}
+		} else {
+			patch_stat(&diff, idx, path)?
+		};
+
+		res.push(stat);
+	}
+
+	Ok(res)
+}
+
+/// line stats of every file in a diff between trees
+pub(crate) fn tree_diff_stats(
+	diff: &Diff<'_>,
+) -> Result<Vec<FileDiffStat>> {
+	diff.deltas()
+		.enumerate()
+		.map(|(idx, delta)| patch_stat(diff, idx, delta_path(&delta)))
+		.collect()
+}
+
+fn patch_stat(
+	diff: &Diff<'_>,
+	idx: usize,
+	path: String,
+) -> Result<FileDiffStat> {
+	// binary flag is only reliable once the patch is loaded
+	let file_patch = Patch::from_diff(diff, idx)?;
+	let binary = file_patch.as_ref().map_or_else(
+		|| diff.get_delta(idx).is_some_and(|d| d.flags().is_binary()),
+		|patch| patch.delta().flags().is_binary(),
+	);
+
+	if binary {
+		return Ok(FileDiffStat {
+			path,
+			binary: true,
+			..FileDiffStat::default()
+		});
+	}
+
+	let (_, insertions, deletions) = file_patch
+		.map_or(Ok((0, 0, 0)), |patch| patch.line_stats())?;
+
+	Ok(FileDiffStat {
+		path,
+		insertions,
+		deletions,
+		binary: false,
+	})
+}
+
+/// returns diff of a specific file inside a commit
+/// see `get_commit_diff`
+pub fn get_diff_commit(
+	repo_path: &RepoPath,
+	id: CommitId,
+	p: String,
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	scope_time!("get_diff_commit");
+
+	let repo = repo(repo_path)?;
+	let diff = get_commit_diff(
+		&repo,
+		id,
+		Some(p),
+		options,
+		Some(&get_stashes(repo_path)?.into_iter().collect()),
+	)?;
+
+	raw_diff_to_file_diff(&repo, &diff, options)
+}
+
+/// diff of the stash entry `stash@{stash_index}` against the commit it
+/// was created on, files stashed as untracked are included as added
+pub fn get_stash_diff(
+	repo_path: &RepoPath,
+	stash_index: usize,
+	pathspec: Option<String>,
+) -> Result<FileDiff> {
+	scope_time!("get_stash_diff");
+
+	let repo = repo(repo_path)?;
+	let id = CommitId::new(
+		repo.revparse_single(&format!("stash@{{{stash_index}}}"))?
+			.id(),
+	);
+	let diff = get_commit_diff(
+		&repo,
+		id,
+		pathspec,
+		None,
+		Some(&HashSet::from([id])),
+	)?;
+
+	raw_diff_to_file_diff(&repo, &diff, None)
+}
+
+/// returns diff of a specific file inside a (merge) commit
+/// against its parent at `parent_index`
+pub fn get_diff_commit_parent(
+	repo_path: &RepoPath,
+	id: CommitId,
+	parent_index: usize,
+	p: String,
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	scope_time!("get_diff_commit_parent");
+
+	let repo = repo(repo_path)?;
+	let diff = get_commit_parent_diff(
+		&repo,
+		id,
+		parent_index,
+		Some(p),
+		options,
+	)?;
+
+	raw_diff_to_file_diff(&repo, &diff, options)
+}
+
+/// get file changes of a diff between two commits
+pub fn get_diff_commits(
+	repo_path: &RepoPath,
+	ids: OldNew<CommitId>,
+	p: String,
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	scope_time!("get_diff_commits");
+
+	let repo = repo(repo_path)?;
+	let diff =
+		get_compare_commits_diff(&repo, ids, Some(p), options)?;
+
+	raw_diff_to_file_diff(&repo, &diff, options)
+}
+
+/// what kind of file `mode` (as in `FileDiff::typechange`) is
+pub const fn file_kind(mode: u32) -> &'static str {
+	match mode & 0o170_000 {
+		0o120_000 => "symlink",
+		0o160_000 => "submodule",
+		0o040_000 => "folder",
+		_ => "file",
+	}
+}
+
+/// a typechange is either a single delta or, without
+/// `include_typechange`, split into a deletion and an addition
+fn typechange(deltas: &[DiffDelta]) -> Option<(u32, u32)> {
+	let mode = |status: &[Delta], new: bool| {
+		deltas
+			.iter()
+			.find(|delta| status.contains(&delta.status()))
+			.map(|delta| {
+				u32::from(if new {
+					delta.new_file().mode()
+				} else {
+					delta.old_file().mode()
+				})
+			})
+	};
+
+	let old = mode(&[Delta::Typechange, Delta::Deleted], false)?;
+	let new = mode(&[Delta::Typechange, Delta::Added], true)?;
+
+	(file_kind(old) != file_kind(new)).then_some((old, new))
+}
+
+///
+pub(crate) fn raw_diff_to_file_diff(
+	repo: &Repository,
+	diff: &Diff,
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	let indices = (0..diff.deltas().len()).collect::<Vec<_>>();
+	deltas_to_file_diff(repo, diff, &indices, options)
+}
+
+/// one `FileDiff` of every file in `diff`, in the order of the diff
+pub(crate) fn raw_diff_to_file_diffs(
+	repo: &Repository,
+	diff: &Diff,
+	options: Option<DiffOptions>,
+) -> Result<Vec<FileDiff>> {
+	// a typechange can be split into two deltas of the same path
+	let mut files: Vec<(String, Vec<usize>)> = Vec::new();
+	for (idx, delta) in diff.deltas().enumerate() {
+		let path = delta_path(&delta);
+		match files.last_mut() {
+			Some((last, indices)) if *last == path => {
+				indices.push(idx);
+			}
+			_ => files.push((path, vec![idx])),
+		}
+	}
+
+	files
+		.iter()
+		.map(|(_, indices)| {
+			deltas_to_file_diff(repo, diff, indices, options)
+		})
+		.collect()
+}
+
+/// the `FileDiff` of the deltas at `indices` of `diff`, all of the
+/// same file
+fn deltas_to_file_diff(
+	repo: &Repository,
+	diff: &Diff,
+	indices: &[usize],
+	options: Option<DiffOptions>,
+) -> Result<FileDiff> {
+	let deltas = indices
+		.iter()
+		.filter_map(|idx| diff.get_delta(*idx))
+		.collect::<Vec<_>>();
+
+	if let Some(delta) = deltas
+		.iter()
+		.find(|delta| delta.status() == Delta::Conflicted)
+	{
+		return conflicted_file_diff(
+			repo,
+			&delta_path(delta),
+			options,
+		);
+	}
+
+	if let [delta] = deltas.as_slice() {
+		if delta.status() == Delta::Untracked {
+			if let Some(res) =
+				untracked_file_diff(repo, &deltas, options)?
+			{
+				return Ok(res);
+			}
+		}
+	}
+
+	let mut patches = Vec::with_capacity(indices.len());
+	for idx in indices {
+		if let Some(patch) = Patch::from_diff(diff, *idx)? {
+			patches.push(patch);
+		}
+	}
+
+	patches_to_file_diff(
+		&deltas,
+		&mut patches,
+		options.unwrap_or_default().max_size,
+	)
+}
+
+/// the single untracked delta in `deltas` diffed as an entirely
+/// added file, `None` if it can not be read
+fn untracked_file_diff(
+	repo: &Repository,
+	deltas: &[DiffDelta],
+	options: Option<DiffOptions>,
+) -> Result<Option<FileDiff>> {
+	let max_size = options.unwrap_or_default().max_size;
+	let Some(delta) = deltas.first() else {
+		return Ok(None);
+	};
+	let relative_path = delta.new_file().path().ok_or_else(|| {
+		Error::Generic("new file path is unspecified.".to_string())
+	})?;
+
+	let newfile_path = work_dir(repo)?.join(relative_path);
+	let newfile_size = fs::symlink_metadata(&newfile_path)
+		.map_or(0, |meta| meta.len());
+
+	let mut res = if newfile_size > max_size
+		|| diff_disabled_by_attributes(repo, relative_path)
+	{
+		let mut res =
+			patches_to_file_diff(deltas, &mut [], max_size)?;
+		res.sizes = (0, newfile_size);
+		res.size_delta = i64::try_from(newfile_size)?;
+		res.too_large = newfile_size > max_size;
+		res.binary = !res.too_large;
+		res
+	} else if let Some(newfile_content) =
+		new_file_content(&newfile_path)
+	{
+		let mut opt = git2::DiffOptions::new();
+		options.unwrap_or_default().apply(&mut opt)?;
+
+		let patch = Patch::from_buffers(
+			&[],
+			None,
+			newfile_content.as_slice(),
+			Some(&newfile_path),
+			Some(&mut opt),
+		)?;
+
+		patches_to_file_diff(deltas, &mut [patch], max_size)?
+	} else {
+		return Ok(None);
+	};
+
+	res.untracked = true;
+
+	Ok(Some(res))
+}
+
+/// the `FileDiff` of `deltas` (all of the same file) with the hunks
+/// of `patches`, which may also come from buffers or blobs
+//TODO: refactor into helper type with the inline closures as dedicated functions
+#[allow(clippy::too_many_lines)]
+pub(crate) fn patches_to_file_diff(
+	deltas: &[DiffDelta],
+	patches: &mut [Patch],
+	max_size: u64,
+) -> Result<FileDiff> {
+	let symlink = deltas.iter().any(|delta| {
+		delta.old_file().mode() == FileMode::Link
+			|| delta.new_file().mode() == FileMode::Link
+	});
+	let res = Rc::new(RefCell::new(FileDiff {
+		symlink,
+		..FileDiff::default()
+	}));
+	{
+		let mut current_lines = Vec::new();
+		let mut current_hunk: Option<HunkHeader> = None;
+
+		let res_cell = Rc::clone(&res);
+		let adder = move |header: &HunkHeader,
+		                  lines: Vec<DiffLine>| {
+			let mut res = res_cell.borrow_mut();
+			res.lines += lines.len();
+			res.hunks.push(Hunk {
+				header: *header,
+				header_hash: hash(header),
+				lines,
+				stage: None,
+				id: 0,
+			});
+		};
+
+		let res_cell = Rc::clone(&res);
+		let mut put = |delta: DiffDelta,
+		               hunk: Option<DiffHunk>,
+		               line: git2::DiffLine| {
+			{
+				let mut res = res_cell.borrow_mut();
+				res.sizes = (
+					delta.old_file().size(),
+					delta.new_file().size(),
+				);
+				//TODO: use try_conv
+				res.size_delta = (i64::conv(res.sizes.1))
+					.saturating_sub(i64::conv(res.sizes.0));
+				res.binary = delta.flags().is_binary();
+				res.too_large = res.binary
+					&& cmp::max(res.sizes.0, res.sizes.1) > max_size;
+			}
+			// link targets never end in a newline, no point in saying so
+			if symlink && is_eofnl(line.origin_value()) {
+				return;
+			}
+
+			if let Some(hunk) = hunk {
+				let hunk_header = HunkHeader::from(hunk);
+
+				match current_hunk {
+					None => current_hunk = Some(hunk_header),
+					Some(h) => {
+						if h != hunk_header {
+							adder(&h, mem::take(&mut current_lines));
+							current_hunk = Some(hunk_header);
+						}
+					}
+				}
+
+				current_lines.push(DiffLine::from(&line));
+			}
+		};
+
+		for patch in patches {
+			patch.print(
+				&mut |delta,
+				      hunk: Option<DiffHunk>,
+				      line: git2::DiffLine| {
+					put(delta, hunk, line);
+					true
+				},
+			)?;
+		}
+
+		if !current_lines.is_empty() {
+			adder(
+				&current_hunk.map_or_else(
+					|| Err(Error::Generic("invalid hunk".to_owned())),
+					Ok,
+				)?,
+				current_lines,
+			);
+		}
+
+		let source_path = |status: Delta| {
+			deltas
+				.iter()
+				.find(|delta| delta.status() == status)
+				.and_then(|delta| {
+					delta.old_file().path().and_then(Path::to_str)
+				})
+				.map(String::from)
+		};
+		res.borrow_mut().mode_change =
+			deltas.iter().find_map(|delta| {
+				let modes = (
+					u32::from(delta.old_file().mode()),
+					u32::from(delta.new_file().mode()),
+				);
+				(modes.0 != 0 && modes.1 != 0 && modes.0 != modes.1)
+					.then_some(modes)
+			});
+		res.borrow_mut().typechange = typechange(deltas);
+		res.borrow_mut().old_path = source_path(Delta::Renamed);
+		res.borrow_mut().copied_from = source_path(Delta::Copied);
+	}
+	let mut res = Rc::try_unwrap(res)
+		.map_err(|_| Error::Generic("rc unwrap error".to_owned()))?
+		.into_inner();
+	if let Some(delta) = deltas
+		.iter()
+		.find(|delta| delta.status() != Delta::Unmodified)
+	{
+		let path = delta_path(delta);
+		set_hunk_ids(&mut res, &path);
+		res.path = path;
+		res.status = Some(delta_status(delta));
+	}
+	res.submodule = submodule_diff(deltas, &res);
+	if res.submodule.is_some() {
+		res.hunks.clear();
+		res.lines = 0;
+	}
+	detect_lfs_pointer(&mut res);
+	annotate_whitespace(&mut res, DEFAULT_TAB_WIDTH);
+	if res.lines <= MAX_ENRICH_LINES {
+		enrich_diff(&mut res);
+	}
+	Ok(res)
+}
+
+/// libgit2 diffs submodules as a "Subproject commit <id>" line,
+/// suffixed with "-dirty" for uncommitted changes in the submodule
+fn submodule_diff(
+	deltas: &[DiffDelta],
+	res: &FileDiff,
+) -> Option<SubmoduleDiff> {
+	let delta = deltas.iter().find(|delta| {
+		delta.old_file().mode() == FileMode::Commit
+			|| delta.new_file().mode() == FileMode::Commit
+	})?;
+
+	let id = |file: git2::DiffFile| {
+		(!file.id().is_zero()).then_some(CommitId::new(file.id()))
+	};
+
+	let dirty =
+		res.hunks.iter().flat_map(|hunk| hunk.lines.iter()).any(
+			|line| {
+				line.line_type == DiffLineType::Add
+					&& line.content.trim_end().ends_with("-dirty")
+			},
+		);
+
+	Some(SubmoduleDiff {
+		old: id(delta.old_file()),
+		new: id(delta.new_file()),
+		dirty,
+	})
+}
+
+/// sets `DiffLine::has_trailing_whitespace` and
+/// `DiffLine::tab_widths` for tabs stopping every `tab_width` columns
+pub fn annotate_whitespace(diff: &mut FileDiff, tab_width: usize) {
+	let tab_width = tab_width.max(1);
+
+	for line in
+		diff.hunks.iter_mut().flat_map(|hunk| hunk.lines.iter_mut())
+	{
+		line.has_trailing_whitespace = line.line_type
+			== DiffLineType::Add
+			&& line.content.ends_with([' ', '\t']);
+
+		line.tab_widths.clear();
+		let mut column = 0;
+		for c in line.content.chars() {
+			let width = if c == '\t' {
+				let width = tab_width - column % tab_width;
+				line.tab_widths.push(width);
+				width
+			} else {
+				1
+			};
+			column += width;
+		}
+	}
+}
+
+/// flags `diff` as lfs pointer if all its lines are lines of
+/// a pointer file (see <https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md>)
+fn detect_lfs_pointer(diff: &mut FileDiff) {
+	const KEYS: [&str; 4] = [
+		"version https://git-lfs.github.com/spec/",
+		"oid ",
+		"size ",
+		"ext-",
+	];
+
+	let lines = || {
+		diff.hunks
+			.iter()
+			.flat_map(|hunk| hunk.lines.iter())
+			.filter(|line| line.line_type != DiffLineType::Header)
+	};
+
+	let is_pointer = lines()
+		.any(|line| line.content.starts_with("oid sha256:"))
+		&& lines().all(|line| {
+			KEYS.iter().any(|key| line.content.starts_with(key))
+		});
+	if !is_pointer {
+		return;
+	}
+
+	let size = |line_type: DiffLineType| {
+		lines()
+			.filter(|line| {
+				line.line_type == line_type
+					|| line.line_type == DiffLineType::None
+			})
+			.find_map(|line| line.content.strip_prefix("size "))
+			.and_then(|size| size.parse::<u64>().ok())
+	};
+
+	diff.lfs_sizes =
+		(size(DiffLineType::Delete), size(DiffLineType::Add));
+	diff.is_lfs_pointer = true;
+}
+
+/// pairs the n-th line of a run of deletions with the n-th line of the
+/// additions following it and marks where the two differ in
+/// `DiffLine::changes`
+pub fn enrich_diff(diff: &mut FileDiff) {
+	scope_time!("enrich_diff");
+
+	for hunk in &mut diff.hunks {
+		let run = |lines: &[DiffLine], line_type: DiffLineType| {
+			lines
+				.iter()
+				.take_while(|line| line.line_type == line_type)
+				.count()
+		};
+
+		let mut idx = 0;
+		while idx < hunk.lines.len() {
+			let deletes =
+				run(&hunk.lines[idx..], DiffLineType::Delete);
+			let adds =
+				run(&hunk.lines[idx + deletes..], DiffLineType::Add);
+
+			for pair in 0..cmp::min(deletes, adds) {
+				let (old, new) = changed_ranges(
+					&hunk.lines[idx + pair].content,
+					&hunk.lines[idx + deletes + pair].content,
+				);
+				hunk.lines[idx + pair].changes = Some(vec![old]);
+				hunk.lines[idx + deletes + pair].changes =
+					Some(vec![new]);
+			}
+
+			idx += cmp::max(deletes + adds, 1);
+		}
+	}
+}
+
+/// hunk of a `SideBySideDiff`
+#[derive(Default, Clone, Debug)]
+pub struct SideBySideHunk {
+	///
+	pub header: HunkHeader,
+	/// old and new line per visual row, context lines on both sides
+	pub rows: Vec<(Option<DiffLine>, Option<DiffLine>)>,
+}
+
+/// split view of a `FileDiff` (see `to_side_by_side`)
+#[derive(Default, Clone, Debug)]
+pub struct SideBySideDiff {
+	///
+	pub hunks: Vec<SideBySideHunk>,
+}
+
+/// aligns the lines of `diff` in rows of old and new line: the n-th line
+/// of a run of deletions shares a row with the n-th line of the additions
+/// following it, the leftovers of the longer run are paired with `None`
+pub fn to_side_by_side(diff: &FileDiff) -> SideBySideDiff {
+	let hunks = diff
+		.hunks
+		.iter()
+		.map(|hunk| {
+			let mut rows = Vec::with_capacity(hunk.lines.len());
+			let mut deletes = Vec::new();
+			let mut adds = Vec::new();
+
+			for line in &hunk.lines {
+				match line.line_type {
+					DiffLineType::Header => (),
+					DiffLineType::Delete => {
+						// deletions after additions start a new run
+						if !adds.is_empty() {
+							flush_rows(
+								&mut rows,
+								&mut deletes,
+								&mut adds,
+							);
+						}
+						deletes.push(line.clone());
+					}
+					DiffLineType::Add => adds.push(line.clone()),
+					_ => {
+						flush_rows(
+							&mut rows,
+							&mut deletes,
+							&mut adds,
+						);
+						rows.push((
+							Some(line.clone()),
+							Some(line.clone()),
+						));
+					}
+				}
+			}
+			flush_rows(&mut rows, &mut deletes, &mut adds);
+
+			SideBySideHunk {
+				header: hunk.header,
+				rows,
+			}
+		})
+		.collect();
+
+	SideBySideDiff { hunks }
+}
+
+fn flush_rows(
+	rows: &mut Vec<(Option<DiffLine>, Option<DiffLine>)>,
+	deletes: &mut Vec<DiffLine>,
+	adds: &mut Vec<DiffLine>,
+) {
+	let len = cmp::max(deletes.len(), adds.len());
+	let mut deletes = mem::take(deletes).into_iter();
+	let mut adds = mem::take(adds).into_iter();
+	rows.extend((0..len).map(|_| (deletes.next(), adds.next())));
+}
+
+/// byte ranges between the common prefix and suffix of both lines
+fn changed_ranges(
+	old: &str,
+	new: &str,
+) -> (Range<usize>, Range<usize>) {
+	let prefix: usize = old
+		.chars()
+		.zip(new.chars())
+		.take_while(|(a, b)| a == b)
+		.map(|(c, _)| c.len_utf8())
+		.sum();
+	let suffix: usize = old[prefix..]
+		.chars()
+		.rev()
+		.zip(new[prefix..].chars().rev())
+		.take_while(|(a, b)| a == b)
+		.map(|(c, _)| c.len_utf8())
+		.sum();
+
+	(prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// `-diff` (e.g. via the `binary` macro) set in `.gitattributes`
+fn diff_disabled_by_attributes(
+	repo: &Repository,
+	path: &Path,
+) -> bool {
+	repo.get_attr(path, "diff", AttrCheckFlags::FILE_THEN_INDEX)
+		.is_ok_and(|value| {
+			matches!(AttrValue::from_string(value), AttrValue::False)
+		})
+}
+
+/// replaces control characters but tabs, which would otherwise reach
+/// the terminal, by visible placeholders. `None` if there are none.
+fn sanitize_control_chars(content: &str) -> Option<Box<str>> {
+	let is_unsafe = |c: char| c.is_control() && c != '\t';
+
+	if !content.chars().any(is_unsafe) {
+		return None;
+	}
+
+	Some(
+		content
+			.chars()
+			.map(|c| match c {
+				// control pictures block: `␀` to `␟`
+				'\0'..='\x1f' if c != '\t' => {
+					char::from_u32(0x2400 + u32::from(c))
+						.unwrap_or(char::REPLACEMENT_CHARACTER)
+				}
+				'\x7f' => '\u{2421}',
+				c if is_unsafe(c) => char::REPLACEMENT_CHARACTER,
+				c => c,
+			})
+			.collect(),
+	)
+}
+
+const fn is_newline(c: char) -> bool {
+	c == '\n' || c == '\r'
+}
+
+const fn is_eofnl(line_type: git2::DiffLineType) -> bool {
+	matches!(
+		line_type,
+		git2::DiffLineType::ContextEOFNL
+			| git2::DiffLineType::AddEOFNL
+			| git2::DiffLineType::DeleteEOFNL
+	)
+}
+
+fn new_file_content(path: &Path) -> Option<Vec<u8>> {
+	if let Ok(meta) = fs::symlink_metadata(path) {
+		if meta.file_type().is_symlink() {
+			if let Ok(path) = fs::read_link(path) {
+				return Some(
+					path.to_str()?.to_string().as_bytes().into(),
+				);
+			}
+		} else if !meta.file_type().is_dir() {
+			if let Ok(content) = fs::read(path) {
+				return Some(content);
+			}
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		apply_patch, export_patch, get_diff, get_diff_combined,
+		get_diff_commit, get_diff_commit_parent, get_diff_commits,
+		get_diff_stats, hunk_id, DiffAlgorithm, DiffLineType,
+		DiffOptions, FailedHunk, FileDiff, FileDiffStat, Hunk,
+		HunkHeader, LineEnding, DEFAULT_MAX_DIFF_SIZE,
+	};
+	use crate::{
+		error::{Error, Result},
+		sync::{
+			checkout_branch, commit,
+			commit_files::OldNew,
+			create_branch, merge_branch, merge_commit,
+			repository::repo,
+			stage_add_file, stage_addremoved, stage_hunk, stash_save,
+			status::{get_status, StatusItemType, StatusType},
+			tests::{
+				get_commit_ids, get_statuses, repo_init,
+				repo_init_empty, write_commit_file,
+			},
+			CommitId, RepoPath,
+		},
+	};
+	use git2::{ApplyLocation, BranchType, Patch};
+	use std::{
+		fs::{self, File},
+		io::Write,
+		path::Path,
+	};
+	use tempfile::TempDir;
+
+	#[test]
+	fn test_untracked_subfolder() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		fs::create_dir(root.join("foo")).unwrap();
+		File::create(root.join("foo/bar.txt"))
+			.unwrap()
+			.write_all(b"test\nfoo")
+			.unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+
+		let diff =
+			get_diff(repo_path, "foo/bar.txt", false, None).unwrap();
+
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(&*diff.hunks[0].lines[1].content, "test");
+
+		File::create(root.join("foo/baz.txt"))
+			.unwrap()
+			.write_all(b"baz\n")
+			.unwrap();
+
+		let diffs =
+			get_diff_files(repo_path, "foo", false, None).unwrap();
+
+		assert_eq!(
+			diffs
+				.iter()
+				.map(|diff| (
+					diff.path.as_str(),
+					diff.status,
+					diff.hunks[0].lines[1].content.to_string()
+				))
+				.collect::<Vec<_>>(),
+			vec![
+				(
+					"foo/bar.txt",
+					Some(StatusItemType::New),
+					String::from("test")
+				),
+				(
+					"foo/baz.txt",
+					Some(StatusItemType::New),
+					String::from("baz")
+				),
+			]
+		);
+
+		fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+		fs::write(root.join("foo/ignored.log"), "log\n").unwrap();
+
+		assert_eq!(
+			stage_add_file(repo_path, Path::new("foo")).unwrap(),
+			2
+		);
+
+		let staged =
+			get_status(repo_path, &StatusType::Stage.into()).unwrap();
+		assert_eq!(
+			staged
+				.iter()
+				.map(|i| i.path.as_str())
+				.collect::<Vec<_>>(),
+			vec!["foo/bar.txt", "foo/baz.txt"]
+		);
+		assert!(staged
+			.iter()
+			.all(|i| i.status == StatusItemType::New));
+	}
+
+	#[test]
+	fn test_empty_repo() {
+		let file_path = Path::new("foo.txt");
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		File::create(root.join(file_path))
+			.unwrap()
+			.write_all(b"test\nfoo")
+			.unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+
+		stage_add_file(repo_path, file_path).unwrap();
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		let diff = get_diff(
+			repo_path,
+			file_path.to_str().unwrap(),
+			true,
+			None,
+		)
+		.unwrap();
+
+		assert_eq!(diff.hunks.len(), 1);
+		let lines = &diff.hunks[0].lines;
+		assert_eq!(lines.len(), 4);
+		assert_eq!(lines[1].line_type, DiffLineType::Add);
+		assert_eq!(&*lines[1].content, "test");
+		assert_eq!(lines[2].line_type, DiffLineType::Add);
+		assert_eq!(&*lines[2].content, "foo");
+	}
+
+	#[test]
+	fn test_unborn_head() -> Result<()> {
+		let (_td, repo) = repo_init_empty()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("foo.txt"))?.write_all(b"test")?;
+
+		let diff = get_diff(repo_path, "foo.txt", true, None)?;
+		assert!(diff.hunks.is_empty());
+
+		let diff = get_diff(repo_path, "foo.txt", false, None)?;
+		assert_eq!(diff.hunks.len(), 1);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_deleted_index() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "test", "c1");
+
+		fs::remove_file(repo.path().join("index"))?;
+
+		let diff = get_diff(repo_path, "foo.txt", true, None)?;
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(&*diff.hunks[0].lines[1].content, "test");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_corrupt_index() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "test", "c1");
+
+		File::create(repo.path().join("index"))?
+			.write_all(b"garbage")?;
+
+		assert!(get_diff(repo_path, "foo.txt", true, None).is_err());
+		assert!(get_diff(repo_path, "foo.txt", false, None).is_err());
+
+		Ok(())
+	}
+
+	static HUNK_A: &str = r"
+1   start
+2
+3
+4
+5
+6   middle
+7
+8
+9
+0
+1   end";
+
+	static HUNK_B: &str = r"
+1   start
+2   newa
+3
+4
+5
+6   middle
+7
+8
+9
+0   newb
+1   end";
+
+	#[test]
+	fn test_hunks() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		let file_path = root.join("bar.txt");
+
+		{
+			File::create(&file_path)
+				.unwrap()
+				.write_all(HUNK_A.as_bytes())
+				.unwrap();
+		}
+
+		let res =
+			get_status(repo_path, &StatusType::WorkingDir.into())
+				.unwrap();
+		assert_eq!(res.len(), 1);
+		assert_eq!(res[0].path, "bar.txt");
+
+		stage_add_file(repo_path, Path::new("bar.txt")).unwrap();
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		// overwrite with next content
+		{
+			File::create(&file_path)
+				.unwrap()
+				.write_all(HUNK_B.as_bytes())
+				.unwrap();
+		}
+
+		assert_eq!(get_statuses(repo_path), (1, 1));
+
+		let res =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
+
+		assert_eq!(res.hunks.len(), 2);
+
+		let positions = |hunk: &Hunk| {
+			hunk.lines
+				.iter()
+				.map(|line| {
+					(
+						line.line_type,
+						line.position.old_lineno,
+						line.position.new_lineno,
+					)
+				})
+				.collect::<Vec<_>>()
+		};
+
+		let first = positions(&res.hunks[0]);
+		assert_eq!(first[0], (DiffLineType::Header, None, None));
+		assert_eq!(first[3], (DiffLineType::Delete, Some(3), None));
+		assert_eq!(first[4], (DiffLineType::Add, None, Some(3)));
+		assert_eq!(first[5], (DiffLineType::None, Some(4), Some(4)));
+
+		assert_eq!(
+			res.hunks[0].header,
+			HunkHeader {
+				old_start: 1,
+				old_lines: 6,
+				new_start: 1,
+				new_lines: 6,
+			}
+		);
+		assert_eq!(
+			res.hunks[1].header,
+			HunkHeader {
+				old_start: 8,
+				old_lines: 5,
+				new_start: 8,
+				new_lines: 5,
+			}
+		);
+
+		let second = positions(&res.hunks[1]);
+		assert_eq!(second[0], (DiffLineType::Header, None, None));
+		assert_eq!(second[1], (DiffLineType::None, Some(8), Some(8)));
+		assert_eq!(second[4], (DiffLineType::Delete, Some(11), None));
+		assert_eq!(second[5], (DiffLineType::Add, None, Some(11)));
+		assert_eq!(
+			second[6],
+			(DiffLineType::None, Some(12), Some(12))
+		);
+	}
+
+	fn repo_with_hunk_fixture() -> (TempDir, RepoPath) {
+		let (td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(HUNK_B.as_bytes())
+			.unwrap();
+
+		(td, repo_path)
+	}
+
+	#[test]
+	fn test_combined_diff() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+		let root = repo_path.gitpath();
+
+		let stages = || {
+			get_diff_combined(&repo_path, "bar.txt", None)
+				.unwrap()
+				.hunks
+				.iter()
+				.map(|hunk| hunk.stage)
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			stages(),
+			vec![
+				Some(HunkStage::Unstaged),
+				Some(HunkStage::Unstaged)
+			]
+		);
+
+		let first = get_diff(&repo_path, "bar.txt", false, None)
+			.unwrap()
+			.hunks[0]
+			.header_hash;
+		stage_hunk(&repo_path, "bar.txt", first, None).unwrap();
+
+		assert_eq!(
+			stages(),
+			vec![Some(HunkStage::Staged), Some(HunkStage::Unstaged)]
+		);
+
+		// hashes of the combined view are valid staging keys
+		let second = get_diff_combined(&repo_path, "bar.txt", None)
+			.unwrap()
+			.hunks[1]
+			.header_hash;
+		stage_hunk(&repo_path, "bar.txt", second, None).unwrap();
+
+		assert_eq!(
+			stages(),
+			vec![Some(HunkStage::Staged), Some(HunkStage::Staged)]
+		);
+
+		// further edit next to the staged change
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(
+				HUNK_B.replace("\n3\n", "\n3   edit\n").as_bytes(),
+			)
+			.unwrap();
+
+		assert_eq!(
+			stages(),
+			vec![Some(HunkStage::Mixed), Some(HunkStage::Staged)]
+		);
+		assert!(get_diff(&repo_path, "bar.txt", true, None)
+			.unwrap()
+			.hunks
+			.iter()
+			.all(|hunk| hunk.stage.is_none()));
+	}
+
+	#[test]
+	fn test_export_patch() {
+		let (td, repo_path) = repo_with_hunk_fixture();
+		let root = repo_path.gitpath();
+		File::create(root.join("new.txt"))
+			.unwrap()
+			.write_all(b"new\nfile\n")
+			.unwrap();
+
+		let out = td.path().join("changes.patch");
+		export_patch(&repo_path, None, false, &out).unwrap();
+
+		let patch = fs::read(&out).unwrap();
+		assert!(
+			patch.starts_with(b"diff --git a/bar.txt b/bar.txt\n")
+		);
+
+		let clone_dir = TempDir::new().unwrap();
+		let clone = git2::Repository::clone(
+			root.to_str().unwrap(),
+			clone_dir.path(),
+		)
+		.unwrap();
+		clone
+			.apply(
+				&git2::Diff::from_buffer(&patch).unwrap(),
+				git2::ApplyLocation::WorkDir,
+				None,
+			)
+			.unwrap();
+
+		assert_eq!(
+			fs::read_to_string(clone_dir.path().join("bar.txt"))
+				.unwrap(),
+			HUNK_B
+		);
+		assert_eq!(
+			fs::read_to_string(clone_dir.path().join("new.txt"))
+				.unwrap(),
+			"new\nfile\n"
+		);
+	}
+
+	fn hunk_patch_text() -> String {
+		let mut patch = Patch::from_buffers(
+			HUNK_A.as_bytes(),
+			Some(Path::new("bar.txt")),
+			HUNK_B.as_bytes(),
+			Some(Path::new("bar.txt")),
+			None,
+		)
+		.unwrap();
+
+		patch.to_buf().unwrap().as_str().unwrap().to_string()
+	}
+
+	#[test]
+	fn test_apply_patch() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+
+		apply_patch(
+			repo_path,
+			&hunk_patch_text(),
+			ApplyLocation::Index,
+		)
+		.unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 1));
+		assert_eq!(
+			fs::read_to_string(root.join("bar.txt")).unwrap(),
+			HUNK_A
+		);
+
+		apply_patch(
+			repo_path,
+			&hunk_patch_text(),
+			ApplyLocation::WorkDir,
+		)
+		.unwrap();
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+		assert_eq!(
+			fs::read_to_string(root.join("bar.txt")).unwrap(),
+			HUNK_B
+		);
+	}
+
+	#[test]
+	fn test_apply_patch_failed_hunk() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let content = HUNK_A.replace("\n0\n", "\n0   other\n");
+		write_commit_file(&repo, "bar.txt", &content, "c1");
+
+		let res = apply_patch(
+			repo_path,
+			&hunk_patch_text(),
+			ApplyLocation::WorkDir,
+		);
+
+		let failed = match res {
+			Err(Error::PatchDoesNotApply(failed)) => failed,
+			_ => Vec::new(),
+		};
+		assert_eq!(
+			failed,
+			vec![FailedHunk {
+				path: String::from("bar.txt"),
+				header: HunkHeader {
+					old_start: 8,
+					old_lines: 5,
+					new_start: 8,
+					new_lines: 5,
+				},
+			}]
+		);
+		assert_eq!(
+			fs::read_to_string(root.join("bar.txt")).unwrap(),
+			content
+		);
+	}
+
+	#[test]
+	fn test_apply_patch_conflicted() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "bar.txt", "foo", "c2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "bar.txt", "master", "c3");
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		assert!(apply_patch(
+			repo_path,
+			&hunk_patch_text(),
+			ApplyLocation::WorkDir,
+		)
+		.is_err());
+	}
+
+	#[test]
+	fn test_hunk_ids() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+
+		let diff =
+			get_diff(&repo_path, "bar.txt", false, None).unwrap();
+		let ids =
+			diff.hunks.iter().map(|hunk| hunk.id).collect::<Vec<_>>();
+
+		assert_eq!(ids.len(), 2);
+		assert_ne!(ids[0], ids[1]);
+		assert_eq!(
+			get_diff(&repo_path, "bar.txt", false, None)
+				.unwrap()
+				.hunks
+				.iter()
+				.map(|hunk| hunk.id)
+				.collect::<Vec<_>>(),
+			ids
+		);
+
+		let hunk = &diff.hunks[0];
+		assert_eq!(
+			hunk_id("bar.txt", &hunk.header, &hunk.lines),
+			ids[0]
+		);
+		assert_ne!(
+			hunk_id("foo.txt", &hunk.header, &hunk.lines),
+			ids[0]
+		);
+	}
+
+	#[test]
+	fn test_context_lines() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+
+		let diff = |context| {
+			get_diff(
+				&repo_path,
+				"bar.txt",
+				false,
+				Some(DiffOptions {
+					context,
+					..DiffOptions::default()
+				}),
+			)
+			.unwrap()
+		};
+
+		let res = diff(0);
+		assert_eq!(res.hunks.len(), 2);
+		// header, deleted and added line only
+		assert_eq!(res.hunks[0].lines.len(), 3);
+		assert_eq!(res.hunks[1].lines.len(), 3);
+
+		let res = diff(10);
+		assert_eq!(res.hunks.len(), 1);
+	}
+
+	#[test]
+	fn test_diff_stats() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		write_commit_file(&repo, "foo.txt", "a\nb\n", "c2");
+
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(HUNK_B.as_bytes())
+			.unwrap();
+		File::create(root.join("foo.txt"))
+			.unwrap()
+			.write_all(b"a\n")
+			.unwrap();
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+		File::create(root.join("new.txt"))
+			.unwrap()
+			.write_all(b"1\n2\n3")
+			.unwrap();
+		File::create(root.join("new.bin"))
+			.unwrap()
+			.write_all(b"\x00\x01")
+			.unwrap();
+
+		let mut stats =
+			get_diff_stats(repo_path, None, false, None).unwrap();
+		stats.sort_by(|a, b| a.path.cmp(&b.path));
+
+		assert_eq!(
+			stats,
+			vec![
+				FileDiffStat {
+					path: String::from("bar.txt"),
+					insertions: 2,
+					deletions: 2,
+					binary: false,
+				},
+				FileDiffStat {
+					path: String::from("new.bin"),
+					insertions: 0,
+					deletions: 0,
+					binary: true,
+				},
+				FileDiffStat {
+					path: String::from("new.txt"),
+					insertions: 3,
+					deletions: 0,
+					binary: false,
+				},
+			]
+		);
+
+		let stats =
+			get_diff_stats(repo_path, None, true, None).unwrap();
+
+		assert_eq!(
+			stats,
+			vec![FileDiffStat {
+				path: String::from("foo.txt"),
+				insertions: 0,
+				deletions: 1,
+				binary: false,
+			}]
+		);
+	}
+
+	fn hunk_contents(
+		diff: &FileDiff,
+	) -> Vec<(HunkHeader, Vec<(DiffLineType, String)>)> {
+		diff.hunks
+			.iter()
+			.map(|hunk| {
+				(
+					hunk.header,
+					hunk.lines
+						.iter()
+						.map(|line| {
+							(line.line_type, line.content.to_string())
+						})
+						.collect(),
+				)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_commit_diff_matches_staged_diff() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+
+		stage_add_file(&repo_path, Path::new("bar.txt")).unwrap();
+		let staged =
+			get_diff(&repo_path, "bar.txt", true, None).unwrap();
+
+		let id = commit(&repo_path, "c2").unwrap();
+		let committed = get_diff_commit(
+			&repo_path,
+			id,
+			String::from("bar.txt"),
+			None,
+		)
+		.unwrap();
+
+		assert_eq!(committed.hunks.len(), 2);
+		assert_eq!(committed.lines, staged.lines);
+		assert_eq!(hunk_contents(&committed), hunk_contents(&staged));
+	}
+
+	#[test]
+	fn test_stash_diff() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+		let root = repo_path.gitpath();
+
+		let workdir =
+			get_diff(&repo_path, "bar.txt", false, None).unwrap();
+		stash_save(&repo_path, None, false, false).unwrap();
+
+		File::create(root.join("new.txt"))
+			.unwrap()
+			.write_all(b"new\nfile\n")
+			.unwrap();
+		let untracked =
+			get_diff(&repo_path, "new.txt", false, None).unwrap();
+		stash_save(&repo_path, None, true, false).unwrap();
+
+		let stashed = get_stash_diff(
+			&repo_path,
+			1,
+			Some(String::from("bar.txt")),
+		)
+		.unwrap();
+		assert_eq!(stashed.hunks.len(), 2);
+		assert_eq!(hunk_contents(&stashed), hunk_contents(&workdir));
+
+		let stashed = get_stash_diff(
+			&repo_path,
+			0,
+			Some(String::from("new.txt")),
+		)
+		.unwrap();
+		assert_eq!(
+			hunk_contents(&stashed),
+			hunk_contents(&untracked)
+		);
+
+		assert!(get_stash_diff(&repo_path, 2, None).is_err());
+	}
+
+	#[test]
+	fn test_merge_commit_parent_diff() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		write_commit_file(&repo, "foo.txt", "b\n", "c2");
+
+		File::create(root.join("foo.txt"))
+			.unwrap()
+			.write_all(b"c\n")
+			.unwrap();
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+		let merge = merge_commit(repo_path, "merge", &[c1]).unwrap();
+
+		let diff = |parent_index| {
+			get_diff_commit_parent(
+				repo_path,
+				merge,
+				parent_index,
+				String::from("foo.txt"),
+				None,
+			)
+		};
+
+		let first = diff(0).unwrap();
+		assert_eq!(
+			hunk_contents(&first)[0].1[1..],
+			[
+				(DiffLineType::Delete, String::from("b")),
+				(DiffLineType::Add, String::from("c")),
+			]
+		);
+		assert_eq!(
+			hunk_contents(
+				&get_diff_commit(
+					repo_path,
+					merge,
+					String::from("foo.txt"),
+					None
+				)
+				.unwrap()
+			),
+			hunk_contents(&first)
+		);
+
+		let second = diff(1).unwrap();
+		assert_eq!(
+			hunk_contents(&second)[0].1[1..],
+			[
+				(DiffLineType::Delete, String::from("a")),
+				(DiffLineType::Add, String::from("c")),
+			]
+		);
+
+		assert!(diff(2).is_err());
+	}
+
+	#[test]
+	fn test_diff_commits() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+		let root = repo_path.gitpath().to_path_buf();
+
+		let old = get_commit_ids(&repo(&repo_path).unwrap(), 1)[0];
+
+		File::create(root.join("foo.txt"))
+			.unwrap()
+			.write_all(b"foo\n")
+			.unwrap();
+		stage_add_file(&repo_path, Path::new("bar.txt")).unwrap();
+		stage_add_file(&repo_path, Path::new("foo.txt")).unwrap();
+		let staged =
+			get_diff(&repo_path, "bar.txt", true, None).unwrap();
+		let new = commit(&repo_path, "c2").unwrap();
+
+		let diff = |ids: OldNew<CommitId>, p: &str| {
+			get_diff_commits(&repo_path, ids, String::from(p), None)
+				.unwrap()
+		};
+
+		let res = diff(OldNew { old, new }, "bar.txt");
+		assert_eq!(res.hunks.len(), 2);
+		assert_eq!(hunk_contents(&res), hunk_contents(&staged));
+
+		let res = diff(OldNew { old, new }, "foo.txt");
+		assert_eq!(res.hunks.len(), 1);
+		assert_eq!(res.lines, 2);
+
+		let res = diff(OldNew { old: new, new }, "bar.txt");
+		assert!(res.hunks.is_empty());
+		assert_eq!(res.lines, 0);
+	}
+
+	#[test]
+	fn test_diff_algorithm() {
+		static OLD: &str = r#"#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+"#;
+		static NEW: &str = r#"#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+"#;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.c", OLD, "c1");
+		File::create(root.join("foo.c"))
+			.unwrap()
+			.write_all(NEW.as_bytes())
+			.unwrap();
+
+		let headers = |algorithm| {
+			get_diff(
+				repo_path,
+				"foo.c",
+				false,
+				Some(DiffOptions {
+					context: 1,
+					algorithm,
+					..DiffOptions::default()
+				}),
+			)
+			.unwrap()
+			.hunks
+			.iter()
+			.map(|hunk| hunk.header)
+			.collect::<Vec<_>>()
+		};
+
+		let myers = headers(DiffAlgorithm::Myers);
+		let patience = headers(DiffAlgorithm::Patience);
+
+		assert_eq!(myers.len(), 2);
+		assert_eq!(patience.len(), 3);
+		assert_eq!(
+			patience[0],
+			HunkHeader {
+				old_start: 2,
+				old_lines: 2,
+				new_start: 2,
+				new_lines: 11,
+			}
+		);
+		assert_ne!(myers, patience);
+	}
+
+	#[test]
+	fn test_deleted_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "a\nb\nc\n", "c1");
+		fs::remove_file(root.join("bar.txt")).unwrap();
+
+		let assert_deleted = |stage| {
+			let diff =
+				get_diff(repo_path, "bar.txt", stage, None).unwrap();
+
+			assert!(!diff.untracked);
+			assert_eq!(diff.sizes, (6, 0));
+			assert_eq!(diff.hunks.len(), 1);
+			assert_eq!(
+				hunk_contents(&diff)[0].1[1..],
+				[
+					(DiffLineType::Delete, String::from("a")),
+					(DiffLineType::Delete, String::from("b")),
+					(DiffLineType::Delete, String::from("c")),
+				]
+			);
+		};
+
+		assert_deleted(false);
+		assert!(get_diff(repo_path, "bar.txt", true, None)
+			.unwrap()
+			.hunks
+			.is_empty());
+
+		stage_addremoved(repo_path, Path::new("bar.txt")).unwrap();
+
+		assert_deleted(true);
+		assert!(get_diff(repo_path, "bar.txt", false, None)
+			.unwrap()
+			.hunks
+			.is_empty());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_symlinks() {
+		use std::os::unix::fs::symlink;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let diff = |p| get_diff(repo_path, p, false, None).unwrap();
+
+		symlink("foo", root.join("link")).unwrap();
+
+		let res = diff("link");
+		assert!(res.symlink);
+		assert!(res.untracked);
+		assert_eq!(
+			hunk_contents(&res)[0].1[1..],
+			[(DiffLineType::Add, String::from("foo"))]
+		);
+
+		stage_add_file(repo_path, Path::new("link")).unwrap();
+		commit(repo_path, "c1").unwrap();
+
+		fs::remove_file(root.join("link")).unwrap();
+		symlink("bar", root.join("link")).unwrap();
+
+		let res = diff("link");
+		assert!(res.symlink);
+		assert_eq!(res.hunks.len(), 1);
+		assert_eq!(
+			hunk_contents(&res)[0].1[1..],
+			[
+				(DiffLineType::Delete, String::from("foo")),
+				(DiffLineType::Add, String::from("bar")),
+			]
+		);
+
+		write_commit_file(&repo, "file", "a\n", "c2");
+		fs::remove_file(root.join("file")).unwrap();
+		symlink("bar", root.join("file")).unwrap();
+
+		// typechanges are split into a deletion and an addition
+		let res = diff("file");
+		assert!(res.symlink);
+		assert_eq!(res.typechange, Some((0o100_644, 0o120_000)));
+		assert_eq!(file_kind(0o120_000), "symlink");
+		assert_eq!(res.hunks.len(), 2);
+		assert_eq!(
+			hunk_contents(&res)[0].1[1..],
+			[(DiffLineType::Delete, String::from("a"))]
+		);
+		assert_eq!(
+			hunk_contents(&res)[1].1[1..],
+			[(DiffLineType::Add, String::from("bar"))]
+		);
+
+		assert!(!diff("bar.txt").symlink);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_mode_change() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.sh", "echo\n", "c1");
+		fs::set_permissions(
+			root.join("foo.sh"),
+			fs::Permissions::from_mode(0o755),
+		)
+		.unwrap();
+
+		let diff = |stage| {
+			get_diff(repo_path, "foo.sh", stage, None).unwrap()
+		};
+
+		let res = diff(false);
+		assert!(res.hunks.is_empty());
+		assert_eq!(res.mode_change, Some((0o100_644, 0o100_755)));
+		assert_eq!(diff(true).mode_change, None);
+
+		stage_add_file(repo_path, Path::new("foo.sh")).unwrap();
+
+		assert_eq!(diff(false).mode_change, None);
+		assert_eq!(
+			diff(true).mode_change,
+			Some((0o100_644, 0o100_755))
+		);
+	}
+
+	#[test]
+	fn test_crlf_line_endings() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\r\nb\nc\r\n", "c1");
+		File::create(root.join("foo.txt"))
+			.unwrap()
+			.write_all(b"a\r\nB\r\nc\n")
+			.unwrap();
+
+		let diff =
+			get_diff(repo_path, "foo.txt", false, None).unwrap();
+		let lines = &diff.hunks[0].lines;
+
+		assert!(lines.iter().all(|l| !l.content.contains('\r')));
+		assert_eq!(
+			lines[1..]
+				.iter()
+				.map(|l| (l.line_type, &*l.content, l.ending))
+				.collect::<Vec<_>>(),
+			vec![
+				(DiffLineType::None, "a", LineEnding::CrLf),
+				(DiffLineType::Delete, "b", LineEnding::Lf),
+				(DiffLineType::Delete, "c", LineEnding::CrLf),
+				(DiffLineType::Add, "B", LineEnding::CrLf),
+				(DiffLineType::Add, "c", LineEnding::Lf),
+			]
+		);
+	}
+
+	#[test]
+	fn test_eol_only_changes() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\nb\n", "c1");
+		File::create(root.join("foo.txt"))
+			.unwrap()
+			.write_all(b"a\r\nb\r\n")
+			.unwrap();
+
+		let diff = |options| {
+			get_diff(repo_path, "foo.txt", false, options).unwrap()
+		};
+
+		assert_eq!(diff(None).hunks.len(), 1);
+		assert!(diff(Some(DiffOptions {
+			ignore_whitespace_eol: true,
+			..DiffOptions::default()
+		}))
+		.hunks
+		.is_empty());
+
+		repo.config()
+			.unwrap()
+			.set_bool("core.autocrlf", true)
+			.unwrap();
+
+		assert!(diff(None).hunks.is_empty());
+	}
+
+	#[test]
+	fn test_binary_by_attributes() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(
+			&repo,
+			".gitattributes",
+			"*.dat binary\n",
+			"c1",
+		);
+		write_commit_file(&repo, "a.dat", "a\n", "c2");
+
+		File::create(root.join("a.dat"))
+			.unwrap()
+			.write_all(b"b\n")
+			.unwrap();
+		File::create(root.join("new.dat"))
+			.unwrap()
+			.write_all(b"new\n")
+			.unwrap();
+		File::create(root.join("new.txt"))
+			.unwrap()
+			.write_all(b"new\n")
+			.unwrap();
+
+		let diff = |p| get_diff(repo_path, p, false, None).unwrap();
+
+		let res = diff("a.dat");
+		assert!(res.binary);
+		assert!(res.hunks.is_empty());
+
+		let res = diff("new.dat");
+		assert!(res.binary);
+		assert!(!res.too_large);
+		assert!(res.untracked);
+		assert!(res.hunks.is_empty());
+		assert_eq!(res.sizes, (0, 4));
+
+		assert!(!diff("new.txt").binary);
+
+		let mut stats =
+			get_diff_stats(repo_path, None, false, None).unwrap();
+		stats.sort_by(|a, b| a.path.cmp(&b.path));
+		assert_eq!(
+			stats.iter().map(|s| s.binary).collect::<Vec<_>>(),
+			vec![true, true, false]
+		);
+	}
+
+	#[test]
+	fn test_glob_metacharacters_in_path() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo[1].txt", "a\n", "c1");
+		write_commit_file(&repo, "foo1.txt", "a\n", "c2");
+		File::create(root.join("foo[1].txt"))
+			.unwrap()
+			.write_all(b"b\n")
+			.unwrap();
+		File::create(root.join("foo1.txt"))
+			.unwrap()
+			.write_all(b"c\n")
+			.unwrap();
+		fs::create_dir(root.join("d[1]")).unwrap();
+		File::create(root.join("d[1]/bar?.txt"))
+			.unwrap()
+			.write_all(b"new\n")
+			.unwrap();
+
+		let diff =
+			|p, stage| get_diff(repo_path, p, stage, None).unwrap();
+
+		let res = diff("foo[1].txt", false);
+		assert_eq!(res.hunks.len(), 1);
+		assert_eq!(
+			hunk_contents(&res)[0].1[1..],
+			[
+				(DiffLineType::Delete, String::from("a")),
+				(DiffLineType::Add, String::from("b")),
+			]
+		);
+
+		let res = diff("d[1]/bar?.txt", false);
+		assert!(res.untracked);
+		assert_eq!(res.lines, 2);
+
+		stage_add_file(repo_path, Path::new("foo[1].txt")).unwrap();
+		stage_add_file(repo_path, Path::new("d[1]/bar?.txt"))
+			.unwrap();
+
+		assert_eq!(diff("foo[1].txt", true).lines, 3);
+		assert_eq!(diff("d[1]/bar?.txt", true).lines, 2);
+		assert!(diff("foo[1].txt", false).hunks.is_empty());
+
+		let stats = get_diff_stats(
+			repo_path,
+			Some("foo[1].txt"),
+			false,
+			None,
+		)
+		.unwrap();
+		assert_eq!(stats.len(), 1);
+		assert_eq!(stats[0].path, "foo1.txt");
+
+		let stats =
+			get_diff_stats(repo_path, Some("*.txt"), true, None)
+				.unwrap();
+		assert_eq!(stats.len(), 2);
+	}
+
+	#[test]
+	fn test_conflicted_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "a\nb\nc\n", "c1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "test.txt", "a\nfoo\nc\n", "c2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "a\nmaster\nc\n", "c3");
+
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		let diff =
+			get_diff(repo_path, "test.txt", false, None).unwrap();
+
+		assert!(diff.conflicted);
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(
+			diff.hunks[0]
+				.lines
+				.iter()
+				.map(|l| l.line_type)
+				.collect::<Vec<_>>(),
+			vec![
+				DiffLineType::None,
+				DiffLineType::ConflictMarker,
+				DiffLineType::ConflictOurs,
+				DiffLineType::ConflictMarker,
+				DiffLineType::ConflictTheirs,
+				DiffLineType::ConflictMarker,
+				DiffLineType::None,
+			]
+		);
+		assert_eq!(&*diff.hunks[0].lines[2].content, "master");
+		assert_eq!(&*diff.hunks[0].lines[4].content, "foo");
+		assert_eq!(
+			diff.hunks[0].lines[6].position.new_lineno,
+			Some(7)
+		);
+	}
+
+	#[test]
+	fn test_conflicted_file_content() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "a\nb\nc\n", "c1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "test.txt", "a\nfoo\nc\n", "c2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "a\nmaster\nc\n", "c3");
+
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+
+		File::create(root.join("test.txt"))
+			.unwrap()
+			.write_all(b"a\n\xff\n")
+			.unwrap();
+		let diff =
+			get_diff(repo_path, "test.txt", false, None).unwrap();
+		let line = &diff.hunks[0].lines[1];
+		assert!(line.invalid_utf8);
+		assert_eq!(line.bytes(), b"\xff");
+
+		let diff = get_diff(
+			repo_path,
+			"test.txt",
+			false,
+			Some(DiffOptions {
+				max_size: 1,
+				..DiffOptions::default()
+			}),
+		)
+		.unwrap();
+		assert!(diff.conflicted);
+		assert!(diff.too_large);
+		assert!(diff.hunks.is_empty());
+
+		File::create(root.join("test.txt"))
+			.unwrap()
+			.write_all(b"a\0b\n")
+			.unwrap();
 		let diff =
-			get_diff(repo_path, "foo/bar.txt", false, None).unwrap();
+			get_diff(repo_path, "test.txt", false, None).unwrap();
+		assert!(diff.binary);
+		assert!(diff.hunks.is_empty());
 
-		assert_eq!(diff.hunks.len(), 1);
-		assert_eq!(&*diff.hunks[0].lines[1].content, "test");
+		fs::remove_file(root.join("test.txt")).unwrap();
+		let diff =
+			get_diff(repo_path, "test.txt", false, None).unwrap();
+		assert!(diff.conflicted);
+		assert!(diff.hunks.is_empty());
 	}
 
 	#[test]
-	fn test_empty_repo() {
-		let file_path = Path::new("foo.txt");
-		let (_td, repo) = repo_init_empty().unwrap();
+	fn test_detect_copies() {
+		let (_td, repo) = repo_init().unwrap();
 		let root = repo.path().parent().unwrap();
 		let repo_path: &RepoPath =
 			&root.as_os_str().to_str().unwrap().into();
 
-		assert_eq!(get_statuses(repo_path), (0, 0));
+		let content: String =
+			(0..50).map(|i| format!("line {i}\n")).collect();
+		write_commit_file(&repo, "a.txt", &content, "c1");
 
-		File::create(root.join(file_path))
+		File::create(root.join("b.txt"))
 			.unwrap()
-			.write_all(b"test\nfoo")
+			.write_all(
+				content.replace("line 25\n", "changed\n").as_bytes(),
+			)
 			.unwrap();
 
-		assert_eq!(get_statuses(repo_path), (1, 0));
+		let opt = DiffOptions {
+			detect_copies: true,
+			..DiffOptions::default()
+		};
 
-		stage_add_file(repo_path, file_path).unwrap();
+		let diff = get_diff(repo_path, "b.txt", false, None).unwrap();
+		assert_eq!(diff.copied_from, None);
+		assert_eq!(diff.lines, 51);
 
-		assert_eq!(get_statuses(repo_path), (0, 1));
+		for stage in [false, true] {
+			if stage {
+				stage_add_file(repo_path, Path::new("b.txt"))
+					.unwrap();
+			}
 
-		let diff = get_diff(
-			repo_path,
-			file_path.to_str().unwrap(),
-			true,
-			None,
-		)
-		.unwrap();
+			let diff = get_diff(repo_path, "b.txt", stage, Some(opt))
+				.unwrap();
 
-		assert_eq!(diff.hunks.len(), 1);
+			assert_eq!(diff.copied_from.as_deref(), Some("a.txt"));
+			assert_eq!(diff.old_path, None);
+			assert_eq!(diff.hunks.len(), 1);
+			assert_eq!(
+				diff.hunks[0].header,
+				HunkHeader {
+					old_start: 23,
+					old_lines: 7,
+					new_start: 23,
+					new_lines: 7,
+				}
+			);
+			assert_eq!(diff.lines, 9);
+		}
 	}
 
-	static HUNK_A: &str = r"
-1   start
-2
-3
-4
-5
-6   middle
-7
-8
-9
-0
-1   end";
+	#[test]
+	fn test_large_diff_line_count() {
+		const LINES: usize = 70_000;
 
-	static HUNK_B: &str = r"
-1   start
-2   newa
-3
-4
-5
-6   middle
-7
-8
-9
-0   newb
-1   end";
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let content = |prefix: &str| {
+			(0..LINES)
+				.map(|i| format!("{prefix}{i}\n"))
+				.collect::<String>()
+		};
+
+		write_commit_file(&repo, "bar.txt", &content("a"), "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(content("b").as_bytes())
+			.unwrap();
+
+		let res =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
+
+		assert_eq!(res.hunks.len(), 1);
+		// header + every line removed and added
+		assert_eq!(res.lines, 1 + 2 * LINES);
+		assert_eq!(res.lines, res.hunks[0].lines.len());
+	}
 
 	#[test]
-	fn test_hunks() {
+	fn test_changed_ranges() {
 		let (_td, repo) = repo_init().unwrap();
 		let root = repo.path().parent().unwrap();
 		let repo_path: &RepoPath =
 			&root.as_os_str().to_str().unwrap().into();
 
-		assert_eq!(get_statuses(repo_path), (0, 0));
+		write_commit_file(
+			&repo,
+			"bar.txt",
+			"the quick brown fox\ngrüße\n",
+			"c1",
+		);
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all("the quick red fox\ngrüsse\n".as_bytes())
+			.unwrap();
 
-		let file_path = root.join("bar.txt");
+		let res =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
 
-		{
-			File::create(&file_path)
-				.unwrap()
-				.write_all(HUNK_A.as_bytes())
-				.unwrap();
-		}
+		let changes = res.hunks[0]
+			.lines
+			.iter()
+			.map(|line| (line.line_type, line.changes.clone()))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			changes,
+			vec![
+				(DiffLineType::Header, None),
+				(DiffLineType::Delete, Some(vec![10..15])),
+				(DiffLineType::Delete, Some(vec![4..6])),
+				(DiffLineType::Add, Some(vec![10..13])),
+				(DiffLineType::Add, Some(vec![4..6])),
+			]
+		);
+	}
+
+	#[test]
+	fn test_side_by_side() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "a\nb\nc\nd\ne\n", "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(b"a\nx\ne\n")
+			.unwrap();
+
+		let diff =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
+		let res = to_side_by_side(&diff);
+
+		let side = |line: &Option<DiffLine>| {
+			line.as_ref().map(|line| {
+				(
+					line.content.to_string(),
+					line.position.old_lineno,
+					line.position.new_lineno,
+				)
+			})
+		};
+		let rows = res.hunks[0]
+			.rows
+			.iter()
+			.map(|(old, new)| (side(old), side(new)))
+			.collect::<Vec<_>>();
+
+		let line = |content: &str, old, new| {
+			Some((String::from(content), old, new))
+		};
+		assert_eq!(
+			rows,
+			vec![
+				(
+					line("a", Some(1), Some(1)),
+					line("a", Some(1), Some(1))
+				),
+				(line("b", Some(2), None), line("x", None, Some(2))),
+				(line("c", Some(3), None), None),
+				(line("d", Some(4), None), None),
+				(
+					line("e", Some(5), Some(3)),
+					line("e", Some(5), Some(3))
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn test_lfs_pointer() {
+		let pointer = |size| {
+			format!(
+				"version https://git-lfs.github.com/spec/v1\n\
+				 oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+				 size {size}\n"
+			)
+		};
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
 
-		let res = get_status(repo_path, StatusType::WorkingDir, None)
+		write_commit_file(&repo, "big.bin", &pointer(120), "c1");
+		File::create(root.join("big.bin"))
+			.unwrap()
+			.write_all(
+				pointer(121).replace("4d7a", "0000").as_bytes(),
+			)
 			.unwrap();
-		assert_eq!(res.len(), 1);
-		assert_eq!(res[0].path, "bar.txt");
 
-		stage_add_file(repo_path, Path::new("bar.txt")).unwrap();
-		assert_eq!(get_statuses(repo_path), (0, 1));
+		let diff =
+			get_diff(repo_path, "big.bin", false, None).unwrap();
+		assert!(diff.is_lfs_pointer);
+		assert_eq!(diff.lfs_sizes, (Some(120), Some(121)));
 
-		// overwrite with next content
-		{
-			File::create(&file_path)
-				.unwrap()
-				.write_all(HUNK_B.as_bytes())
-				.unwrap();
-		}
+		File::create(root.join("new.bin"))
+			.unwrap()
+			.write_all(pointer(7).as_bytes())
+			.unwrap();
 
-		assert_eq!(get_statuses(repo_path), (1, 1));
+		let diff =
+			get_diff(repo_path, "new.bin", false, None).unwrap();
+		assert!(diff.untracked);
+		assert!(diff.is_lfs_pointer);
+		assert_eq!(diff.lfs_sizes, (None, Some(7)));
+
+		File::create(root.join("big.bin"))
+			.unwrap()
+			.write_all(b"not a pointer\n")
+			.unwrap();
+
+		let diff =
+			get_diff(repo_path, "big.bin", false, None).unwrap();
+		assert!(!diff.is_lfs_pointer);
+	}
+
+	#[test]
+	fn test_whitespace() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "old  \n", "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(b"spaces  \ntab\t\na\tb\n")
+			.unwrap();
+
+		let mut diff =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
+
+		let whitespace = |diff: &FileDiff| {
+			diff.hunks[0]
+				.lines
+				.iter()
+				.map(|line| {
+					(
+						line.line_type,
+						line.has_trailing_whitespace,
+						line.tab_widths.clone(),
+					)
+				})
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			whitespace(&diff),
+			vec![
+				(DiffLineType::Header, false, vec![]),
+				(DiffLineType::Delete, false, vec![]),
+				(DiffLineType::Add, true, vec![]),
+				(DiffLineType::Add, true, vec![1]),
+				(DiffLineType::Add, false, vec![1]),
+			]
+		);
+
+		annotate_whitespace(&mut diff, 4);
+
+		assert_eq!(whitespace(&diff)[3].2, vec![1]);
+		assert_eq!(whitespace(&diff)[4].2, vec![3]);
+	}
+
+	#[test]
+	fn test_control_characters() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "plain\n", "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(b"\x1b[31mred\x1b[0m\tx\n")
+			.unwrap();
+
+		let diff =
+			get_diff(repo_path, "bar.txt", false, None).unwrap();
+
+		let plain = &diff.hunks[0].lines[1];
+		assert!(!plain.sanitized);
+		assert!(plain.raw.is_none());
+
+		let line = &diff.hunks[0].lines[2];
+		assert_eq!(line.line_type, DiffLineType::Add);
+		assert!(line.sanitized);
+		assert!(!line.invalid_utf8);
+		assert!(!line.content.contains('\x1b'));
+		assert_eq!(&*line.content, "\u{241B}[31mred\u{241B}[0m\tx");
+		assert_eq!(line.bytes(), b"\x1b[31mred\x1b[0m\tx");
+	}
+
+	#[test]
+	fn test_many_hunks_line_count() {
+		const LINES: usize = 20_000;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		// every 20th line changes, far enough apart for own hunks
+		let content = |prefix: &str| {
+			(0..LINES)
+				.map(|i| {
+					if i % 20 == 10 {
+						format!("{prefix}{i}\n")
+					} else {
+						format!("{i}\n")
+					}
+				})
+				.collect::<String>()
+		};
+
+		write_commit_file(&repo, "bar.txt", &content("a"), "c1");
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(content("b").as_bytes())
+			.unwrap();
 
 		let res =
 			get_diff(repo_path, "bar.txt", false, None).unwrap();
 
+		assert_eq!(res.hunks.len(), LINES / 20);
+		// header + 3 context + removed + added + 3 context
+		assert!(res.hunks.iter().all(|hunk| hunk.lines.len() == 9));
+		assert_eq!(res.lines, 9 * LINES / 20);
+	}
+
+	#[test]
+	fn test_interhunk_lines() {
+		let (_td, repo_path) = repo_with_hunk_fixture();
+
+		let diff = |interhunk_lines| {
+			get_diff(
+				&repo_path,
+				"bar.txt",
+				false,
+				Some(DiffOptions {
+					interhunk_lines,
+					..DiffOptions::default()
+				}),
+			)
+			.unwrap()
+		};
+
+		let res = diff(0);
 		assert_eq!(res.hunks.len(), 2);
+		assert_eq!(
+			res.lines,
+			res.hunks.iter().map(|h| h.lines.len()).sum::<usize>()
+		);
+
+		let res = diff(10);
+		assert_eq!(res.hunks.len(), 1);
+		assert_eq!(res.lines, res.hunks[0].lines.len());
+	}
+
+	#[test]
+	fn test_ignore_whitespace_options() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "a b\nc\n", "c1");
+
+		let diff = |options| {
+			get_diff(repo_path, "bar.txt", false, Some(options))
+				.unwrap()
+		};
+
+		// trailing whitespace only
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(b"a b  \nc\n")
+			.unwrap();
+
+		assert!(!diff(DiffOptions::default()).hunks.is_empty());
+		assert!(diff(DiffOptions {
+			ignore_whitespace_eol: true,
+			..DiffOptions::default()
+		})
+		.hunks
+		.is_empty());
+
+		// amount of whitespace changed
+		File::create(root.join("bar.txt"))
+			.unwrap()
+			.write_all(b"a    b\nc\n")
+			.unwrap();
+
+		let res = diff(DiffOptions {
+			ignore_whitespace_eol: true,
+			..DiffOptions::default()
+		});
+		assert_eq!(res.hunks.len(), 1);
+		assert!(diff(DiffOptions {
+			ignore_whitespace_change: true,
+			..DiffOptions::default()
+		})
+		.hunks
+		.is_empty());
 	}
 
 	#[test]
@@ -612,6 +3815,7 @@ mod tests {
 		dbg!(&diff);
 		assert_eq!(diff.sizes, (1, 2));
 		assert_eq!(diff.size_delta, 1);
+		assert!(diff.binary);
 
 		Ok(())
 	}
@@ -637,6 +3841,96 @@ mod tests {
 		dbg!(&diff);
 		assert_eq!(diff.sizes, (0, 2));
 		assert_eq!(diff.size_delta, 2);
+		assert!(diff.binary);
+		assert!(diff.hunks.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_untracked_file_too_large() -> Result<()> {
+		let file_path = Path::new("bar");
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join(file_path))?
+			.write_all(&[b'a'; 1025])?;
+
+		let options = DiffOptions {
+			max_size: 1024,
+			..DiffOptions::default()
+		};
+
+		let diff = get_diff(
+			repo_path,
+			file_path.to_str().unwrap(),
+			false,
+			Some(options),
+		)?;
+
+		assert!(diff.untracked);
+		assert!(diff.too_large);
+		assert!(diff.hunks.is_empty());
+		assert_eq!(diff.sizes, (0, 1025));
+
+		let diff = get_diff(
+			repo_path,
+			file_path.to_str().unwrap(),
+			false,
+			None,
+		)?;
+
+		assert!(!diff.too_large);
+		assert_eq!(diff.hunks.len(), 1);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_tracked_file_too_large() -> Result<()> {
+		let file_path = Path::new("bar");
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join(file_path))?.write_all(b"a\n")?;
+		stage_add_file(repo_path, file_path)?;
+		commit(repo_path, "commit")?;
+
+		File::create(root.join(file_path))?
+			.write_all(&[b'b'; 1025])?;
+
+		let diff = get_diff(
+			repo_path,
+			file_path.to_str().unwrap(),
+			false,
+			Some(DiffOptions {
+				max_size: 1024,
+				..DiffOptions::default()
+			}),
+		)?;
+
+		assert!(diff.too_large);
+		assert!(diff.hunks.is_empty());
+
+		// the default applies without options as well
+		File::create(root.join(file_path))?.write_all(&vec![
+			b'b';
+			usize::try_from(DEFAULT_MAX_DIFF_SIZE)? + 1
+		])?;
+
+		let diff = get_diff(
+			repo_path,
+			file_path.to_str().unwrap(),
+			false,
+			None,
+		)?;
+
+		assert!(diff.too_large);
+		assert!(diff.hunks.is_empty());
 
 		Ok(())
 	}
@@ -653,13 +3947,13 @@ mod tests {
 
 		stage_add_file(repo_path, file_path).unwrap();
 
-		commit(repo_path, "").unwrap();
+		commit(repo_path, "c1").unwrap();
 
 		File::create(root.join(file_path))?.write_all(b"\x00\x02")?;
 
 		stage_add_file(repo_path, file_path).unwrap();
 
-		let id = commit(repo_path, "").unwrap();
+		let id = commit(repo_path, "c2").unwrap();
 
 		let diff =
 			get_diff_commit(repo_path, id, String::new(), None)
@@ -0,0 +1,104 @@
+//! commits changing a single file
+
+use super::{commit_filter::diff_contains_file, CommitId, RepoPath};
+use crate::{error::Result, sync::repository::repo};
+use git2::Sort;
+use scopetime::scope_time;
+
+/// up to `limit` commits reachable from HEAD (newest first) that change
+/// `path` compared to their first parent, like `git log -- path`.
+/// pass the last returned id as `start` to get the next page, which
+/// continues with the ancestors of `start`
+pub fn file_history(
+	repo_path: &RepoPath,
+	path: &str,
+	limit: usize,
+	start: Option<CommitId>,
+) -> Result<Vec<CommitId>> {
+	scope_time!("file_history");
+
+	let repo = repo(repo_path)?;
+	let filter = diff_contains_file(path.to_string());
+
+	let mut walk = repo.revwalk()?;
+	walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+	if let Some(start) = start {
+		for parent in repo.find_commit(start.into())?.parent_ids() {
+			walk.push(parent)?;
+		}
+	} else {
+		walk.push_head()?;
+	}
+
+	let mut res = Vec::new();
+
+	for id in walk {
+		if res.len() == limit {
+			break;
+		}
+
+		let id = CommitId::new(id?);
+
+		if filter(&repo, &id)? {
+			res.push(id);
+		}
+	}
+
+	Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::file_history;
+	use crate::sync::{
+		tests::{repo_init_empty, write_commit_file},
+		RepoPath,
+	};
+
+	#[test]
+	fn test_file_history() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "foo", "a", "c1");
+		write_commit_file(&repo, "bar", "a", "c2");
+		let c3 = write_commit_file(&repo, "foo", "b", "c3");
+
+		assert_eq!(
+			file_history(repo_path, "foo", 10, None).unwrap(),
+			vec![c3, c1]
+		);
+		assert_eq!(
+			file_history(repo_path, "bar", 10, None).unwrap().len(),
+			1
+		);
+		assert!(file_history(repo_path, "baz", 10, None)
+			.unwrap()
+			.is_empty());
+	}
+
+	#[test]
+	fn test_file_history_pages() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "foo", "a", "c1");
+		write_commit_file(&repo, "bar", "a", "c2");
+		let c3 = write_commit_file(&repo, "foo", "b", "c3");
+
+		let page = file_history(repo_path, "foo", 1, None).unwrap();
+		assert_eq!(page, vec![c3]);
+
+		let page =
+			file_history(repo_path, "foo", 1, Some(c3)).unwrap();
+		assert_eq!(page, vec![c1]);
+
+		assert!(file_history(repo_path, "foo", 1, Some(c1))
+			.unwrap()
+			.is_empty());
+	}
+}
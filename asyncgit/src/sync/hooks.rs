@@ -174,4 +174,60 @@ mod tests {
 
 		assert_eq!(msg, String::from("msg\n"));
 	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_hooks_commit_msg_reject_wip() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let hook = b"#!/bin/sh
+	if grep -q WIP \"$1\"; then
+		echo 'no WIP commits'
+		exit 1
+	fi
+	        ";
+
+		git2_hooks::create_hook(
+			&repo,
+			git2_hooks::HOOK_COMMIT_MSG,
+			hook,
+		);
+
+		let mut msg = String::from("WIP: foo");
+		assert_eq!(
+			hooks_commit_msg(repo_path, &mut msg).unwrap(),
+			HookResult::NotOk(String::from("no WIP commits\n"))
+		);
+
+		let mut msg = String::from("foo");
+		assert_eq!(
+			hooks_commit_msg(repo_path, &mut msg).unwrap(),
+			HookResult::Ok
+		);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_hooks_skip_non_executable() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let hooks = repo.path().join("hooks");
+		std::fs::create_dir_all(&hooks).unwrap();
+		std::fs::write(
+			hooks.join(git2_hooks::HOOK_PRE_COMMIT),
+			"#!/bin/sh\nexit 1\n",
+		)
+		.unwrap();
+
+		assert_eq!(
+			hooks_pre_commit(repo_path).unwrap(),
+			HookResult::Ok
+		);
+	}
 }
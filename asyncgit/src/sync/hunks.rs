@@ -1,5 +1,7 @@
 use super::{
-	diff::{get_diff_raw, DiffOptions, HunkHeader},
+	diff::{
+		get_diff_raw, raw_diff_to_file_diff, DiffOptions, HunkHeader,
+	},
 	RepoPath,
 };
 use crate::{
@@ -7,10 +9,13 @@ use crate::{
 	hash,
 	sync::repository::repo,
 };
-use git2::{ApplyLocation, ApplyOptions, Diff};
+use git2::{ApplyLocation, ApplyOptions, Delta, Diff};
 use scopetime::scope_time;
+use std::path::Path;
 
-///
+/// stages a single hunk of the workdir diff of `file_path`.
+/// an untracked file consists of a single hunk and is staged as a whole.
+/// fails if the hunk is not part of the current diff anymore.
 pub fn stage_hunk(
 	repo_path: &RepoPath,
 	file_path: &str,
@@ -23,6 +28,32 @@ pub fn stage_hunk(
 
 	let diff = get_diff_raw(&repo, file_path, false, false, options)?;
 
+	if diff.deltas().len() == 1
+		&& diff
+			.deltas()
+			.next()
+			.is_some_and(|delta| delta.status() == Delta::Untracked)
+	{
+		let file_diff = raw_diff_to_file_diff(&repo, &diff, options)?;
+		if !file_diff
+			.hunks
+			.iter()
+			.any(|hunk| hunk.header_hash == hunk_hash)
+		{
+			return Err(Error::Generic("hunk not found".to_string()));
+		}
+
+		let mut index = repo.index()?;
+		index.add_path(Path::new(file_path))?;
+		index.write()?;
+
+		return Ok(());
+	}
+
+	if find_hunk_index(&diff, hunk_hash).is_none() {
+		return Err(Error::Generic("hunk not found".to_string()));
+	}
+
 	let mut opt = ApplyOptions::new();
 	opt.hunk_callback(|hunk| {
 		hunk.is_some_and(|hunk| {
@@ -36,6 +67,8 @@ pub fn stage_hunk(
 	Ok(())
 }
 
+/// discards a single hunk of the workdir diff of `file_path` on disk.
+/// fails if the hunk is not part of the current diff anymore.
 /// this will fail for an all untracked file
 pub fn reset_hunk(
 	repo_path: &RepoPath,
@@ -59,7 +92,8 @@ pub fn reset_hunk(
 			res
 		});
 
-		let diff = get_diff_raw(&repo, file_path, false, true, None)?;
+		let diff =
+			get_diff_raw(&repo, file_path, false, true, options)?;
 
 		repo.apply(&diff, ApplyLocation::WorkDir, Some(&mut opt))?;
 
@@ -153,7 +187,14 @@ mod tests {
 	use super::*;
 	use crate::{
 		error::Result,
-		sync::{diff::get_diff, tests::repo_init_empty},
+		sync::{
+			diff::get_diff,
+			stage_add_file,
+			tests::{
+				get_statuses, repo_init, repo_init_empty,
+				write_commit_file,
+			},
+		},
 	};
 	use std::{
 		fs::{self, File},
@@ -191,4 +232,231 @@ mod tests {
 
 		Ok(())
 	}
+
+	static HUNK_A: &str = r"
+1   start
+2
+3
+4
+5
+6   middle
+7
+8
+9
+0
+1   end";
+
+	static HUNK_B: &str = r"
+1   start
+2   newa
+3
+4
+5
+6   middle
+7
+8
+9
+0   newb
+1   end";
+
+	fn repo_with_two_hunks() -> Result<(tempfile::TempDir, RepoPath)>
+	{
+		let (td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: RepoPath =
+			root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("bar.txt"))?
+			.write_all(HUNK_A.as_bytes())?;
+		stage_add_file(&repo_path, Path::new("bar.txt"))?;
+		File::create(root.join("bar.txt"))?
+			.write_all(HUNK_B.as_bytes())?;
+
+		Ok((td, repo_path))
+	}
+
+	#[test]
+	fn stage_single_hunk() -> Result<()> {
+		let (_td, repo_path) = repo_with_two_hunks()?;
+
+		let diff = get_diff(&repo_path, "bar.txt", false, None)?;
+		assert_eq!(diff.hunks.len(), 2);
+
+		stage_hunk(
+			&repo_path,
+			"bar.txt",
+			diff.hunks[1].header_hash,
+			None,
+		)?;
+
+		let staged = get_diff(&repo_path, "bar.txt", true, None)?;
+		let workdir = get_diff(&repo_path, "bar.txt", false, None)?;
+
+		// the whole file is new in the index
+		assert_eq!(staged.hunks.len(), 1);
+		assert!(staged.hunks[0]
+			.lines
+			.iter()
+			.any(|line| &*line.content == "0   newb"));
+		assert!(!staged.hunks[0]
+			.lines
+			.iter()
+			.any(|line| &*line.content == "2   newa"));
+
+		assert_eq!(workdir.hunks.len(), 1);
+		assert_eq!(workdir.hunks[0].header, diff.hunks[0].header);
+
+		Ok(())
+	}
+
+	#[test]
+	fn stage_stale_hunk_fails() -> Result<()> {
+		let (_td, repo_path) = repo_with_two_hunks()?;
+
+		let diff = get_diff(&repo_path, "bar.txt", false, None)?;
+
+		// file changes on disk after the diff was computed
+		File::create(repo_path.gitpath().join("bar.txt"))?
+			.write_all(b"something else")?;
+
+		assert!(stage_hunk(
+			&repo_path,
+			"bar.txt",
+			diff.hunks[0].header_hash,
+			None,
+		)
+		.is_err());
+
+		assert_eq!(get_statuses(&repo_path), (1, 1));
+
+		Ok(())
+	}
+
+	#[test]
+	fn stage_hunk_of_untracked_file() -> Result<()> {
+		let (_td, repo) = repo_init_empty()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("foo.txt"))?.write_all(b"test")?;
+
+		let diff = get_diff(repo_path, "foo.txt", false, None)?;
+		assert!(diff.untracked);
+
+		stage_hunk(
+			repo_path,
+			"foo.txt",
+			diff.hunks[0].header_hash,
+			None,
+		)?;
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		Ok(())
+	}
+
+	#[test]
+	fn stage_stale_hunk_of_untracked_file() -> Result<()> {
+		let (_td, repo) = repo_init_empty()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		File::create(root.join("foo.txt"))?.write_all(b"test")?;
+
+		let diff = get_diff(repo_path, "foo.txt", false, None)?;
+
+		File::create(root.join("foo.txt"))?
+			.write_all(b"test\nmore\n")?;
+
+		assert!(stage_hunk(
+			repo_path,
+			"foo.txt",
+			diff.hunks[0].header_hash,
+			None,
+		)
+		.is_err());
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+
+		Ok(())
+	}
+
+	#[test]
+	fn unstage_single_hunk() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		File::create(root.join("bar.txt"))?
+			.write_all(HUNK_B.as_bytes())?;
+		stage_add_file(repo_path, Path::new("bar.txt"))?;
+
+		let staged = get_diff(repo_path, "bar.txt", true, None)?;
+		assert_eq!(staged.hunks.len(), 2);
+
+		assert!(unstage_hunk(
+			repo_path,
+			"bar.txt",
+			staged.hunks[0].header_hash,
+			None,
+		)?);
+
+		let staged_after =
+			get_diff(repo_path, "bar.txt", true, None)?;
+		let workdir = get_diff(repo_path, "bar.txt", false, None)?;
+
+		assert_eq!(staged_after.hunks.len(), 1);
+		assert_eq!(
+			staged_after.hunks[0].header,
+			staged.hunks[1].header
+		);
+
+		assert_eq!(workdir.hunks.len(), 1);
+		assert_eq!(workdir.hunks[0].header, staged.hunks[0].header);
+
+		assert_eq!(fs::read_to_string(root.join("bar.txt"))?, HUNK_B);
+
+		Ok(())
+	}
+
+	#[test]
+	fn reset_single_hunk() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", HUNK_A, "c1");
+		File::create(root.join("bar.txt"))?
+			.write_all(HUNK_B.as_bytes())?;
+
+		let diff = get_diff(repo_path, "bar.txt", false, None)?;
+		assert_eq!(diff.hunks.len(), 2);
+
+		reset_hunk(
+			repo_path,
+			"bar.txt",
+			diff.hunks[0].header_hash,
+			None,
+		)?;
+
+		let content = fs::read_to_string(root.join("bar.txt"))?;
+		assert!(!content.contains("newa"));
+		assert!(content.contains("0   newb"));
+
+		// the hunk is gone now, so resetting it again must fail
+		assert!(reset_hunk(
+			repo_path,
+			"bar.txt",
+			diff.hunks[0].header_hash,
+			None,
+		)
+		.is_err());
+
+		Ok(())
+	}
 }
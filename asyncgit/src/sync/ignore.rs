@@ -5,14 +5,15 @@ use crate::{
 };
 use scopetime::scope_time;
 use std::{
-	fs::{File, OpenOptions},
-	io::{Read, Seek, SeekFrom, Write},
+	fs::{self, OpenOptions},
+	io::Write,
 	path::Path,
 };
 
 static GITIGNORE: &str = ".gitignore";
 
-/// add file or path to root ignore file
+/// add file or path to root ignore file, fails if the exact
+/// pattern is already in there
 pub fn add_to_ignore(
 	repo_path: &RepoPath,
 	path_to_ignore: &str,
@@ -31,8 +32,23 @@ pub fn add_to_ignore(
 
 	let ignore_file = work_dir(&repo)?.join(GITIGNORE);
 
-	let optional_newline = ignore_file.exists()
-		&& !file_ends_with_newline(&ignore_file)?;
+	let content = if ignore_file.exists() {
+		fs::read(&ignore_file)?
+	} else {
+		Vec::new()
+	};
+
+	if content.split(|c| *c == b'\n').any(|line| {
+		line.strip_suffix(b"\r").unwrap_or(line)
+			== path_to_ignore.as_bytes()
+	}) {
+		return Err(Error::Generic(format!(
+			"already in gitignore: `{path_to_ignore}`"
+		)));
+	}
+
+	let optional_newline =
+		!content.is_empty() && !content.ends_with(b"\n");
 
 	let mut file = OpenOptions::new()
 		.append(true)
@@ -49,21 +65,27 @@ pub fn add_to_ignore(
 	Ok(())
 }
 
-fn file_ends_with_newline(file: &Path) -> Result<bool> {
-	let mut file = File::open(file)?;
-	let size = file.metadata()?.len();
+/// whether `path` is ignored by any ignore file or rule
+/// (it does not need to exist)
+pub fn is_path_ignored(
+	repo_path: &RepoPath,
+	path: &str,
+) -> Result<bool> {
+	scope_time!("is_path_ignored");
 
-	file.seek(SeekFrom::Start(size.saturating_sub(1)))?;
-	let mut last_char = String::with_capacity(1);
-	file.read_to_string(&mut last_char)?;
+	let repo = repo(repo_path)?;
 
-	Ok(last_char == "\n")
+	Ok(repo.status_should_ignore(Path::new(path))?)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::sync::{tests::repo_init, utils::repo_write_file};
+	use crate::sync::{
+		status::{get_status, StatusType},
+		tests::repo_init,
+		utils::repo_write_file,
+	};
 	use io::BufRead;
 	use pretty_assertions::assert_eq;
 	use std::{fs::File, io, path::Path};
@@ -156,4 +178,48 @@ mod tests {
 		let lines = read_lines(root.join(ignore_file_path)).unwrap();
 		assert_eq!(lines.count(), 1);
 	}
+
+	#[test]
+	fn test_duplicate() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		repo_write_file(&repo, ".gitignore", "foo\r\nbar").unwrap();
+
+		assert!(add_to_ignore(repo_path, "foo").is_err());
+		assert!(add_to_ignore(repo_path, "bar").is_err());
+		add_to_ignore(repo_path, "baz").unwrap();
+
+		let lines = read_lines(root.join(".gitignore")).unwrap();
+		assert_eq!(lines.count(), 3);
+	}
+
+	#[test]
+	fn test_ignore_pattern() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert!(!is_path_ignored(repo_path, "a.tmp").unwrap());
+
+		add_to_ignore(repo_path, "*.tmp").unwrap();
+		repo_write_file(&repo, "a.tmp", "a").unwrap();
+
+		assert!(is_path_ignored(repo_path, "a.tmp").unwrap());
+		assert!(!is_path_ignored(repo_path, "a.txt").unwrap());
+
+		let status =
+			get_status(repo_path, &StatusType::WorkingDir.into())
+				.unwrap();
+		assert_eq!(
+			status
+				.iter()
+				.map(|i| i.path.as_str())
+				.collect::<Vec<_>>(),
+			vec![".gitignore"]
+		);
+	}
 }
@@ -208,7 +208,7 @@ mod tests {
 		File::create(root.join(file_path))?.write_all(b"a")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		commit(repo_path, "commit1").unwrap();
-		File::create(root.join(file_path))?.write_all(b"a")?;
+		File::create(root.join(file_path))?.write_all(b"b")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		let oid2 = commit(repo_path, "commit2").unwrap();
 
@@ -233,7 +233,7 @@ mod tests {
 		File::create(root.join(file_path))?.write_all(b"a")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		commit(repo_path, "commit1").unwrap();
-		File::create(root.join(file_path))?.write_all(b"a")?;
+		File::create(root.join(file_path))?.write_all(b"b")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		let oid2 = commit(repo_path, "commit2").unwrap();
 
@@ -266,7 +266,7 @@ mod tests {
 		File::create(root.join(file_path))?.write_all(b"a")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		commit(repo_path, "commit1").unwrap();
-		File::create(root.join(file_path))?.write_all(b"a")?;
+		File::create(root.join(file_path))?.write_all(b"b")?;
 		stage_add_file(repo_path, file_path).unwrap();
 		let oid2 = commit(repo_path, "commit2").unwrap();
 
@@ -361,6 +361,7 @@ mod tests {
 				fields: SearchFields::MESSAGE_SUMMARY,
 				options: SearchOptions::FUZZY_SEARCH,
 				search_pattern: String::from("my msg"),
+				..LogFilterSearchOptions::default()
 			}),
 		);
 
@@ -378,6 +379,7 @@ mod tests {
 				fields: SearchFields::FILENAMES,
 				options: SearchOptions::FUZZY_SEARCH,
 				search_pattern: String::from("fo"),
+				..LogFilterSearchOptions::default()
 			}),
 		);
 
@@ -2,6 +2,7 @@ use crate::{
 	error::{Error, Result},
 	sync::{
 		branch::merge_commit::commit_merge_with_head,
+		conflict::conflicting_paths,
 		rebase::{
 			abort_rebase, continue_rebase, get_rebase_progress,
 		},
@@ -9,7 +10,10 @@ use crate::{
 		reset_stage, reset_workdir, CommitId,
 	},
 };
-use git2::{BranchType, Commit, MergeOptions, Repository};
+use git2::{
+	build::CheckoutBuilder, BranchType, Commit, ErrorCode,
+	MergeOptions, Repository, ResetType,
+};
 use scopetime::scope_time;
 
 use super::{
@@ -49,19 +53,47 @@ pub fn abort_pending_state(repo_path: &RepoPath) -> Result<()> {
 	Ok(())
 }
 
+/// resets index and worktree to HEAD (dropping all local changes)
+/// and removes the merge state (`MERGE_HEAD`, `MERGE_MSG`, ..)
+pub fn abort_merge(repo_path: &RepoPath) -> Result<()> {
+	scope_time!("abort_merge");
+
+	let repo = repo(repo_path)?;
+
+	let head = repo.head()?.peel_to_commit()?;
+	repo.reset(head.as_object(), ResetType::Hard, None)?;
+
+	repo.cleanup_state()?;
+
+	Ok(())
+}
+
 ///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+	/// HEAD already contains the branch
+	UpToDate,
+	/// HEAD was moved forward to this commit
+	FastForward(CommitId),
+	/// the merge (`MERGE_HEAD`, `MERGE_MSG`) is staged and waits to
+	/// be committed (see `merge_commit`)
+	Merged,
+	/// like `Merged` but with these paths conflicting in the index
+	Conflicts(Vec<String>),
+}
+
+/// merges `branch` into HEAD, see `merge_branch_repo`
 pub fn merge_branch(
 	repo_path: &RepoPath,
 	branch: &str,
 	branch_type: BranchType,
-) -> Result<()> {
+	no_ff: bool,
+) -> Result<MergeOutcome> {
 	scope_time!("merge_branch");
 
 	let repo = repo(repo_path)?;
 
-	merge_branch_repo(&repo, branch, branch_type)?;
-
-	Ok(())
+	merge_branch_repo(&repo, branch, branch_type, no_ff)
 }
 
 ///
@@ -95,29 +127,74 @@ pub fn abort_pending_rebase(repo_path: &RepoPath) -> Result<()> {
 	abort_rebase(&repo)
 }
 
-///
+/// fast-forwards HEAD to `branch` if possible (unless `no_ff` is set
+/// or `merge.ff` is `false`), otherwise merges it into the index and
+/// worktree leaving the conflicts (if any) to be resolved
 pub fn merge_branch_repo(
 	repo: &Repository,
 	branch: &str,
 	branch_type: BranchType,
-) -> Result<()> {
+	no_ff: bool,
+) -> Result<MergeOutcome> {
+	let name = branch;
 	let branch = repo.find_branch(branch, branch_type)?;
 
 	let annotated =
 		repo.reference_to_annotated_commit(&branch.into_reference())?;
 
-	let (analysis, _) = repo.merge_analysis(&[&annotated])?;
+	let (analysis, pref) = repo.merge_analysis(&[&annotated])?;
 
 	//TODO: support merge on unborn
 	if analysis.is_unborn() {
 		return Err(Error::Generic("head is unborn".into()));
 	}
 
+	if analysis.is_up_to_date() {
+		return Ok(MergeOutcome::UpToDate);
+	}
+
+	let map_conflict = |e: git2::Error| {
+		if e.code() == ErrorCode::Conflict {
+			Error::UncommittedChanges
+		} else {
+			Error::Git(e)
+		}
+	};
+
+	if analysis.is_fast_forward()
+		&& !no_ff
+		&& !pref.is_no_fast_forward()
+	{
+		let target = repo.find_commit(annotated.id())?;
+
+		repo.checkout_tree(
+			target.as_object(),
+			Some(CheckoutBuilder::new().safe()),
+		)
+		.map_err(map_conflict)?;
+		repo.head()?.set_target(
+			target.id(),
+			&format!("merge {name}: Fast-forward"),
+		)?;
+
+		return Ok(MergeOutcome::FastForward(target.id().into()));
+	}
+
+	if pref.is_fastforward_only() {
+		return Err(Error::NotFastForward);
+	}
+
 	let mut opt = MergeOptions::default();
 
-	repo.merge(&[&annotated], Some(&mut opt), None)?;
+	repo.merge(&[&annotated], Some(&mut opt), None)
+		.map_err(map_conflict)?;
 
-	Ok(())
+	let index = repo.index()?;
+	Ok(if index.has_conflicts() {
+		MergeOutcome::Conflicts(conflicting_paths(&index)?)
+	} else {
+		MergeOutcome::Merged
+	})
 }
 
 ///
@@ -155,9 +232,10 @@ pub fn merge_commit(
 mod tests {
 	use super::*;
 	use crate::sync::{
-		create_branch,
-		tests::{repo_init, write_commit_file},
-		RepoPath,
+		checkout_branch, create_branch, repo_state,
+		tests::{get_statuses, repo_init, write_commit_file},
+		utils::{get_head_repo, repo_read_file},
+		RepoPath, RepoState,
 	};
 	use pretty_assertions::assert_eq;
 
@@ -168,14 +246,20 @@ mod tests {
 		let repo_path: &RepoPath =
 			&root.as_os_str().to_str().unwrap().into();
 
-		let c1 =
-			write_commit_file(&repo, "test.txt", "test", "commit1");
+		write_commit_file(&repo, "test.txt", "test", "commit1");
 
 		create_branch(repo_path, "foo").unwrap();
+		let c2 =
+			write_commit_file(&repo, "foo.txt", "foo", "commit2");
 
-		write_commit_file(&repo, "test.txt", "test2", "commit2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "test.txt", "test2", "commit3");
 
-		merge_branch(repo_path, "master", BranchType::Local).unwrap();
+		assert_eq!(
+			merge_branch(repo_path, "foo", BranchType::Local, false)
+				.unwrap(),
+			MergeOutcome::Merged
+		);
 
 		let msg = merge_msg(repo_path).unwrap();
 
@@ -183,6 +267,108 @@ mod tests {
 
 		let mergeheads = mergehead_ids(repo_path).unwrap();
 
-		assert_eq!(mergeheads[0], c1);
+		assert_eq!(mergeheads[0], c2);
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Merge);
+
+		let id = merge_commit(repo_path, &msg, &mergeheads).unwrap();
+
+		assert_eq!(
+			repo.find_commit(id.into()).unwrap().parent_count(),
+			2
+		);
+		assert_eq!(repo_read_file(&repo, "foo.txt").unwrap(), "foo");
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+	}
+
+	#[test]
+	fn test_merge_fast_forward() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "test", "commit1");
+		create_branch(repo_path, "foo").unwrap();
+		let c2 =
+			write_commit_file(&repo, "test.txt", "foo", "commit2");
+		checkout_branch(repo_path, "master").unwrap();
+
+		assert_eq!(
+			merge_branch(repo_path, "foo", BranchType::Local, false)
+				.unwrap(),
+			MergeOutcome::FastForward(c2)
+		);
+		assert_eq!(get_head_repo(&repo).unwrap(), c2);
+		assert_eq!(repo.head().unwrap().shorthand(), Some("master"));
+		assert_eq!(repo_read_file(&repo, "test.txt").unwrap(), "foo");
+		assert_eq!(get_statuses(repo_path), (0, 0));
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+
+		assert_eq!(
+			merge_branch(repo_path, "foo", BranchType::Local, false)
+				.unwrap(),
+			MergeOutcome::UpToDate
+		);
+	}
+
+	#[test]
+	fn test_merge_no_ff() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 =
+			write_commit_file(&repo, "test.txt", "test", "commit1");
+		create_branch(repo_path, "foo").unwrap();
+		let c2 =
+			write_commit_file(&repo, "test.txt", "foo", "commit2");
+		checkout_branch(repo_path, "master").unwrap();
+
+		assert_eq!(
+			merge_branch(repo_path, "foo", BranchType::Local, true)
+				.unwrap(),
+			MergeOutcome::Merged
+		);
+		assert_eq!(get_head_repo(&repo).unwrap(), c1);
+		assert_eq!(mergehead_ids(repo_path).unwrap(), vec![c2]);
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Merge);
+	}
+
+	#[test]
+	fn test_merge_conflict_abort() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "test", "commit1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "test.txt", "foo", "commit2");
+		write_commit_file(&repo, "new.txt", "new", "commit3");
+		checkout_branch(repo_path, "master").unwrap();
+		let head =
+			write_commit_file(&repo, "test.txt", "master", "commit4");
+
+		assert_eq!(
+			merge_branch(repo_path, "foo", BranchType::Local, false)
+				.unwrap(),
+			MergeOutcome::Conflicts(vec![String::from("test.txt")])
+		);
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Merge);
+		assert!(root.join("new.txt").exists());
+
+		abort_merge(repo_path).unwrap();
+
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+		assert_eq!(get_head_repo(&repo).unwrap(), head);
+		assert_eq!(
+			repo_read_file(&repo, "test.txt").unwrap(),
+			"master"
+		);
+		assert!(!root.join("new.txt").exists());
+		assert!(!repo.path().join("MERGE_HEAD").exists());
+		assert!(!repo.path().join("MERGE_MSG").exists());
+		assert_eq!(get_statuses(repo_path), (0, 0));
 	}
 }
@@ -5,15 +5,19 @@
 
 pub mod blame;
 pub mod branch;
+mod cherry_pick;
 pub mod commit;
 mod commit_details;
 pub mod commit_files;
 mod commit_filter;
+pub mod commit_msg;
 mod commit_revert;
 mod commits_info;
 mod config;
+mod conflict;
 pub mod cred;
 pub mod diff;
+mod file_history;
 mod hooks;
 mod hunks;
 mod ignore;
@@ -21,6 +25,7 @@ mod logwalker;
 mod merge;
 mod patches;
 mod rebase;
+mod refs;
 pub mod remotes;
 mod repository;
 mod reset;
@@ -37,25 +42,37 @@ pub mod utils;
 
 pub use blame::{blame_file, BlameHunk, FileBlame};
 pub use branch::{
-	branch_compare_upstream, checkout_branch, checkout_commit,
+	ahead_behind, branch_compare_upstream, checkout_branch,
+	checkout_commit,
+	compare::diff_branch,
 	config_is_pull_rebase, create_branch, delete_branch,
 	get_branch_remote, get_branches_info,
 	merge_commit::merge_upstream_commit,
 	merge_ff::branch_merge_upstream_fastforward,
-	merge_rebase::merge_upstream_rebase, rename::rename_branch,
+	merge_rebase::merge_upstream_rebase,
+	pull::{pull, PullOutcome},
+	rename::rename_branch,
 	validate_branch_name, BranchCompare, BranchDetails, BranchInfo,
 };
-pub use commit::{amend, commit, tag_commit};
+pub use cherry_pick::{
+	cherry_pick, CherryPickOutcome, CHERRY_PICK_RECORD_ORIGIN,
+};
+pub use commit::{
+	amend, commit, commit_amend, commit_with_options,
+	get_commit_template, get_signoff, tag_commit, CommitOptions,
+};
 pub use commit_details::{
 	get_commit_details, CommitDetails, CommitMessage, CommitSignature,
 };
 pub use commit_files::get_commit_files;
 pub use commit_filter::{
-	diff_contains_file, filter_commit_by_search, LogFilterSearch,
-	LogFilterSearchOptions, SearchFields, SearchOptions,
-	SharedCommitFilterFn,
+	diff_contains_file, filter_commit_by_search, search_commits,
+	LogFilterSearch, LogFilterSearchOptions, SearchFields,
+	SearchOptions, SharedCommitFilterFn,
+};
+pub use commit_revert::{
+	commit_revert, revert_commit, revert_head, RevertOutcome,
 };
-pub use commit_revert::{commit_revert, revert_commit, revert_head};
 pub use commits_info::{
 	get_commit_info, get_commits_info, CommitId, CommitInfo,
 };
@@ -63,36 +80,48 @@ pub use config::{
 	get_config_string, untracked_files_config,
 	ShowUntrackedFilesConfig,
 };
-pub use diff::get_diff_commit;
+pub use conflict::{
+	get_conflict, resolve_conflict, ConflictEntry, ConflictSide,
+	ConflictVersions,
+};
+pub use diff::{get_diff_commit, get_diff_commit_parent};
+pub use file_history::file_history;
 pub use git2::BranchType;
 pub use hooks::{
 	hooks_commit_msg, hooks_post_commit, hooks_pre_commit,
 	hooks_prepare_commit_msg, HookResult, PrepareCommitMsgSource,
 };
 pub use hunks::{reset_hunk, stage_hunk, unstage_hunk};
-pub use ignore::add_to_ignore;
+pub use ignore::{add_to_ignore, is_path_ignored};
 pub use logwalker::{LogWalker, LogWalkerWithoutFilter};
 pub use merge::{
-	abort_pending_rebase, abort_pending_state,
+	abort_merge, abort_pending_rebase, abort_pending_state,
 	continue_pending_rebase, merge_branch, merge_commit, merge_msg,
-	mergehead_ids, rebase_progress,
+	mergehead_ids, rebase_progress, MergeOutcome,
 };
 pub use rebase::rebase_branch;
+pub use refs::{get_refs_map, RefKind, RefLabel};
 pub use remotes::{
 	add_remote, delete_remote, get_default_remote,
 	get_default_remote_for_fetch, get_default_remote_for_push,
-	get_remote_url, get_remotes, push::AsyncProgress, rename_remote,
-	tags::PushTagsProgress, update_remote_url, validate_remote_name,
+	get_remote_url, get_remotes, get_remotes_info, prune,
+	push::AsyncProgress, rename_remote, tags::PushTagsProgress,
+	update_remote_url, validate_remote_name, RemoteInfo,
 };
 pub(crate) use repository::repo;
 pub use repository::{RepoPath, RepoPathRef};
-pub use reset::{reset_repo, reset_stage, reset_workdir};
+pub use reset::{
+	remove_untracked_file, reset_repo, reset_stage, reset_stage_all,
+	reset_workdir, reset_workdir_all, reset_workdir_all_paths,
+	reset_workdir_file,
+};
 pub use reword::reword;
 pub use staging::{discard_lines, stage_lines};
 pub use stash::{
-	get_stashes, stash_apply, stash_drop, stash_pop, stash_save,
+	get_stashes, stash_apply, stash_drop, stash_list, stash_pop,
+	stash_save, StashApplyOutcome, StashInfo,
 };
-pub use state::{repo_state, RepoState};
+pub use state::{get_head_info, repo_state, HeadInfo, RepoState};
 pub use status::is_workdir_clean;
 pub use submodules::{
 	get_submodules, submodule_parent_info, update_submodule,
@@ -108,7 +137,7 @@ pub use utils::{
 	stage_add_all, stage_add_file, stage_addremoved, Head,
 };
 
-pub use git2::ResetType;
+pub use git2::{ApplyLocation, ResetType};
 
 /// test utils
 #[cfg(test)]
@@ -232,10 +261,10 @@ pub mod tests {
 	/// helper returning amount of files with changes in the (wd,stage)
 	pub fn get_statuses(repo_path: &RepoPath) -> (usize, usize) {
 		(
-			get_status(repo_path, StatusType::WorkingDir, None)
+			get_status(repo_path, &StatusType::WorkingDir.into())
 				.unwrap()
 				.len(),
-			get_status(repo_path, StatusType::Stage, None)
+			get_status(repo_path, &StatusType::Stage.into())
 				.unwrap()
 				.len(),
 		)
@@ -0,0 +1,148 @@
+//! decorations of the log: branches, tags and HEAD per commit
+
+use super::{CommitId, RepoPath};
+use crate::{error::Result, sync::repository::repo};
+use git2::{Reference, ReferenceType};
+use scopetime::scope_time;
+use std::collections::HashMap;
+
+/// what a `RefLabel` is, in the order labels are sorted by
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum RefKind {
+	///
+	Head,
+	///
+	LocalBranch,
+	///
+	RemoteBranch,
+	/// lightweight or annotated tag
+	Tag,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RefLabel {
+	///
+	pub kind: RefKind,
+	/// short name like `master`, `origin/master` or `v1.0`
+	pub name: String,
+}
+
+fn ref_kind(reference: &Reference<'_>) -> Option<RefKind> {
+	if reference.is_branch() {
+		Some(RefKind::LocalBranch)
+	} else if reference.is_remote() {
+		// skip symbolic refs like `origin/HEAD`
+		(reference.kind() == Some(ReferenceType::Direct))
+			.then_some(RefKind::RemoteBranch)
+	} else if reference.is_tag() {
+		Some(RefKind::Tag)
+	} else {
+		None
+	}
+}
+
+/// labels of all branches, remote branches and tags grouped by the
+/// commit they point to (annotated tags are peeled), refs that do not
+/// resolve to a commit are skipped
+pub fn get_refs_map(
+	repo_path: &RepoPath,
+) -> Result<HashMap<CommitId, Vec<RefLabel>>> {
+	scope_time!("get_refs_map");
+
+	let repo = repo(repo_path)?;
+	let mut res: HashMap<CommitId, Vec<RefLabel>> = HashMap::new();
+
+	let mut add = |reference: &Reference<'_>, label: RefLabel| {
+		if let Ok(commit) = reference.peel_to_commit() {
+			res.entry(commit.id().into()).or_default().push(label);
+		}
+	};
+
+	if let Ok(head) = repo.head() {
+		add(
+			&head,
+			RefLabel {
+				kind: RefKind::Head,
+				name: String::from("HEAD"),
+			},
+		);
+	}
+
+	for reference in repo.references()?.flatten() {
+		if let Some(kind) = ref_kind(&reference) {
+			let name =
+				String::from_utf8_lossy(reference.shorthand_bytes())
+					.to_string();
+			add(&reference, RefLabel { kind, name });
+		}
+	}
+
+	for labels in res.values_mut() {
+		labels.sort();
+	}
+
+	Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{get_refs_map, RefKind, RefLabel};
+	use crate::sync::{
+		create_branch, tests::repo_init, utils::get_head_repo,
+		RepoPath,
+	};
+
+	fn label(kind: RefKind, name: &str) -> RefLabel {
+		RefLabel {
+			kind,
+			name: name.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_refs_map() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		create_branch(repo_path, "foo").unwrap();
+
+		let head = get_head_repo(&repo).unwrap();
+		let target = repo.find_object(head.into(), None).unwrap();
+		let sig = repo.signature().unwrap();
+		repo.tag_lightweight("light", &target, false).unwrap();
+		repo.tag("annotated", &target, &sig, "msg", false).unwrap();
+
+		// dangling refs are skipped
+		std::fs::write(
+			repo.path().join("refs/tags/dangling"),
+			format!("{}\n", "1".repeat(40)),
+		)
+		.unwrap();
+		repo.reference_symbolic(
+			"refs/remotes/origin/HEAD",
+			"refs/remotes/origin/master",
+			false,
+			"",
+		)
+		.unwrap();
+
+		let refs = get_refs_map(repo_path).unwrap();
+
+		assert_eq!(refs.len(), 1);
+		assert_eq!(
+			refs[&head],
+			vec![
+				label(RefKind::Head, "HEAD"),
+				label(RefKind::LocalBranch, "foo"),
+				label(RefKind::LocalBranch, "master"),
+				label(RefKind::Tag, "annotated"),
+				label(RefKind::Tag, "light"),
+			]
+		);
+	}
+}
@@ -1,16 +1,28 @@
 use super::push::ProgressNotification;
-use crate::{error::Result, sync::cred::BasicAuthCredential};
+use crate::{
+	error::Result,
+	sync::cred::{
+		credential_approve, credential_fill, credential_reject,
+		prompt_credential, ssh_key_files, ssh_key_is_encrypted,
+		BasicAuthCredential, CredentialMethod, CredentialPrompt,
+		CredentialState,
+	},
+};
 use crossbeam_channel::Sender;
-use git2::{Cred, Error as GitError, RemoteCallbacks};
-use std::sync::{
-	atomic::{AtomicBool, Ordering},
-	Arc, Mutex,
+use git2::{Cred, Error as GitError, RemoteCallbacks, Repository};
+use std::{
+	path::PathBuf,
+	sync::{Arc, Mutex},
 };
 
+/// url, username and password handed out by `credential_fill`
+type HelperCredential = (String, String, String);
+
 ///
 #[derive(Default, Clone)]
 pub struct CallbackStats {
 	pub push_rejected_msg: Option<(String, String)>,
+	pub updated_refs: usize,
 }
 
 ///
@@ -19,12 +31,16 @@ pub struct Callbacks {
 	sender: Option<Sender<ProgressNotification>>,
 	basic_credential: Option<BasicAuthCredential>,
 	stats: Arc<Mutex<CallbackStats>>,
-	first_call_to_credentials: Arc<AtomicBool>,
+	credential_state: Arc<Mutex<CredentialState>>,
+	/// the credential helpers run in here to see the repo config
+	repo_dir: PathBuf,
+	helper_credential: Arc<Mutex<Option<HelperCredential>>>,
 }
 
 impl Callbacks {
 	///
 	pub fn new(
+		repo: &Repository,
 		sender: Option<Sender<ProgressNotification>>,
 		basic_credential: Option<BasicAuthCredential>,
 	) -> Self {
@@ -34,12 +50,32 @@ impl Callbacks {
 			sender,
 			basic_credential,
 			stats,
-			first_call_to_credentials: Arc::new(AtomicBool::new(
-				true,
+			credential_state: Arc::new(Mutex::new(
+				CredentialState::new(ssh_key_files(repo)),
 			)),
+			repo_dir: repo
+				.workdir()
+				.unwrap_or_else(|| repo.path())
+				.to_path_buf(),
+			helper_credential: Arc::new(Mutex::new(None)),
+		}
+	}
+
+	/// tells the credential helpers that the credential they handed
+	/// out was accepted, call once the remote operation succeeded
+	pub fn approve_credential(&self) {
+		if let Some((url, user, pwd)) = self.take_helper_credential()
+		{
+			credential_approve(&self.repo_dir, &url, &user, &pwd);
 		}
 	}
 
+	fn take_helper_credential(&self) -> Option<HelperCredential> {
+		self.helper_credential.lock().ok().and_then(
+			|mut helper_credential| helper_credential.take(),
+		)
+	}
+
 	///
 	pub fn get_stats(&self) -> Result<CallbackStats> {
 		let stats = self.stats.lock()?;
@@ -145,12 +181,18 @@ impl Callbacks {
 			sender.send(ProgressNotification::Transfer {
 				objects: p.received_objects(),
 				total_objects: p.total_objects(),
+				received_bytes: p.received_bytes(),
+				indexed_deltas: p.indexed_deltas(),
+				total_deltas: p.total_deltas(),
 			})
 		});
 	}
 
 	fn update_tips(&self, name: &str, a: git2::Oid, b: git2::Oid) {
 		log::debug!("update tips: '{}' [{}] [{}]", name, a, b);
+		if let Ok(mut stats) = self.stats.lock() {
+			stats.updated_refs += 1;
+		}
 		self.sender.clone().map(|sender| {
 			sender.send(ProgressNotification::UpdateTips {
 				name: name.to_string(),
@@ -176,9 +218,9 @@ impl Callbacks {
 		});
 	}
 
-	// If credentials are bad, we don't ask the user to re-fill their creds. We push an error and they will be able to restart their action (for example a push) and retype their creds.
-	// This behavior is explained in a issue on git2-rs project : https://github.com/rust-lang/git2-rs/issues/347
-	// An implementation reference is done in cargo : https://github.com/rust-lang/cargo/blob/9fb208dddb12a3081230a5fd8f470e01df8faa25/src/cargo/sources/git/utils.rs#L588
+	// libgit2 calls this again after every rejected attempt (see https://github.com/rust-lang/git2-rs/issues/347),
+	// `CredentialState` hands out every method only once so a bad credential ends in an error instead of a loop.
+	// Passphrases of encrypted keys and, as a last resort, username and password are asked through `prompt_credential`.
 	// There is also a guide about libgit2 authentication : https://libgit2.org/docs/guides/authentication/
 	fn credentials(
 		&self,
@@ -193,36 +235,110 @@ impl Callbacks {
 			allowed_types
 		);
 
-		// This boolean is used to avoid multiple calls to credentials callback.
-		if self.first_call_to_credentials.load(Ordering::Relaxed) {
-			self.first_call_to_credentials
-				.store(false, Ordering::Relaxed);
-		} else {
-			return Err(GitError::from_str("Bad credentials."));
+		// asked again, the credential of the helpers was refused
+		if let Some((url, user, pwd)) = self.take_helper_credential()
+		{
+			credential_reject(&self.repo_dir, &url, &user, &pwd);
 		}
 
-		match &self.basic_credential {
-			_ if allowed_types.is_ssh_key() => username_from_url
-				.map_or_else(
-					|| {
-						Err(GitError::from_str(
-							" Couldn't extract username from url.",
-						))
-					},
-					Cred::ssh_key_from_agent,
-				),
-			Some(BasicAuthCredential {
-				username: Some(user),
-				password: Some(pwd),
-			}) if allowed_types.is_user_pass_plaintext() => {
-				Cred::userpass_plaintext(user, pwd)
+		let basic_credential =
+			self.basic_credential.clone().unwrap_or_default();
+		let username = username_from_url
+			.map(ToOwned::to_owned)
+			.or_else(|| basic_credential.username.clone());
+
+		let Ok(mut state) = self.credential_state.lock() else {
+			return Err(GitError::from_str("Bad credentials."));
+		};
+
+		while let Some(method) = state.next(
+			allowed_types,
+			username.is_some(),
+			basic_credential.is_complete(),
+		) {
+			log::debug!("creds: trying {:?}", method);
+
+			let cred = match (&method, username.as_deref()) {
+				(CredentialMethod::Username, Some(user)) => {
+					Cred::username(user)
+				}
+				(CredentialMethod::SshAgent, Some(user)) => {
+					Cred::ssh_key_from_agent(user)
+				}
+				(CredentialMethod::SshKey(path), Some(user)) => {
+					if ssh_key_is_encrypted(path) {
+						let Some(passphrase) = prompt_credential(
+							CredentialPrompt::Passphrase(
+								path.clone(),
+							),
+						)
+						.and_then(|cred| cred.password) else {
+							continue;
+						};
+						Cred::ssh_key(
+							user,
+							None,
+							path,
+							Some(&passphrase),
+						)
+					} else {
+						Cred::ssh_key(user, None, path, None)
+					}
+				}
+				(CredentialMethod::CredentialHelper, user) => {
+					match credential_fill(&self.repo_dir, url, user) {
+						Some((user, pwd)) => {
+							let cred =
+								Cred::userpass_plaintext(&user, &pwd);
+							if let Ok(mut helper_credential) =
+								self.helper_credential.lock()
+							{
+								*helper_credential = Some((
+									url.to_string(),
+									user,
+									pwd,
+								));
+							}
+							cred
+						}
+						None => continue,
+					}
+				}
+				(CredentialMethod::UserPass, _) => {
+					match &basic_credential {
+						BasicAuthCredential {
+							username: Some(user),
+							password: Some(pwd),
+						} => Cred::userpass_plaintext(user, pwd),
+						_ => continue,
+					}
+				}
+				(CredentialMethod::Prompt, user) => {
+					match prompt_credential(
+						CredentialPrompt::UserPass {
+							url: url.to_string(),
+							username: user.map(ToOwned::to_owned),
+						},
+					) {
+						Some(BasicAuthCredential {
+							username: Some(user),
+							password: Some(pwd),
+						}) => Cred::userpass_plaintext(&user, &pwd),
+						_ => continue,
+					}
+				}
+				(CredentialMethod::Default, _) => Cred::default(),
+				_ => continue,
+			};
+
+			match cred {
+				Ok(cred) => return Ok(cred),
+				Err(e) => {
+					log::debug!("creds: {:?} failed: {}", method, e)
+				}
 			}
-			Some(BasicAuthCredential {
-				username: Some(user),
-				password: _,
-			}) if allowed_types.is_username() => Cred::username(user),
-			_ if allowed_types.is_default() => Cred::default(),
-			_ => Err(GitError::from_str("Couldn't find credentials")),
 		}
+
+		Err(GitError::from_str("Bad credentials."))
 	}
 }
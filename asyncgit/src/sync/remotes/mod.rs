@@ -10,13 +10,14 @@ use crate::{
 		cred::BasicAuthCredential,
 		remotes::push::ProgressNotification, repository::repo, utils,
 	},
-	ProgressPercent,
 };
 use crossbeam_channel::Sender;
 use git2::{
-	BranchType, FetchOptions, ProxyOptions, Remote, Repository,
+	BranchType, Direction, FetchOptions, ProxyOptions, ReferenceType,
+	Remote, Repository,
 };
 use scopetime::scope_time;
+use std::collections::HashSet;
 use utils::bytes2string;
 
 pub use callbacks::Callbacks;
@@ -34,6 +35,42 @@ pub fn proxy_auto<'a>() -> ProxyOptions<'a> {
 	proxy
 }
 
+/// name and urls of a remote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteInfo {
+	///
+	pub name: String,
+	/// fetch url
+	pub url: Option<String>,
+	/// only set if it differs from `url`
+	pub push_url: Option<String>,
+}
+
+/// urls are not parsed (scp-like `user@host:path` or local paths are
+/// fine), only empty or multi-line ones are refused
+fn check_remote_url(url: &str) -> Result<()> {
+	if url.trim().is_empty() || url.contains('\n') {
+		return Err(Error::InvalidRemoteUrl(url.to_string()));
+	}
+
+	Ok(())
+}
+
+fn check_new_remote_name(
+	repo: &Repository,
+	name: &str,
+) -> Result<()> {
+	if !Remote::is_valid_name(name) {
+		return Err(Error::InvalidRemoteName(name.to_string()));
+	}
+
+	if repo.find_remote(name).is_ok() {
+		return Err(Error::RemoteAlreadyExists(name.to_string()));
+	}
+
+	Ok(())
+}
+
 ///
 pub fn add_remote(
 	repo_path: &RepoPath,
@@ -41,18 +78,27 @@ pub fn add_remote(
 	url: &str,
 ) -> Result<()> {
 	let repo = repo(repo_path)?;
+	check_new_remote_name(&repo, name)?;
+	check_remote_url(url)?;
 	repo.remote(name, url)?;
 	Ok(())
 }
 
-///
+/// renames the remote, its remote tracking branches and the upstream
+/// config of the branches tracking it
 pub fn rename_remote(
 	repo_path: &RepoPath,
 	name: &str,
 	new_name: &str,
 ) -> Result<()> {
 	let repo = repo(repo_path)?;
-	repo.remote_rename(name, new_name)?;
+	check_new_remote_name(&repo, new_name)?;
+
+	let problems = repo.remote_rename(name, new_name)?;
+	for refspec in problems.iter().flatten() {
+		log::warn!("refspec not renamed: {}", refspec);
+	}
+
 	Ok(())
 }
 
@@ -63,11 +109,13 @@ pub fn update_remote_url(
 	new_url: &str,
 ) -> Result<()> {
 	let repo = repo(repo_path)?;
+	check_remote_url(new_url)?;
 	repo.remote_set_url(name, new_url)?;
 	Ok(())
 }
 
-///
+/// deletes the remote with its remote tracking branches and the
+/// upstream config of the branches tracking it
 pub fn delete_remote(
 	repo_path: &RepoPath,
 	remote_name: &str,
@@ -94,6 +142,33 @@ pub fn get_remotes(repo_path: &RepoPath) -> Result<Vec<String>> {
 	Ok(remotes)
 }
 
+/// like `get_remotes` including the urls
+pub fn get_remotes_info(
+	repo_path: &RepoPath,
+) -> Result<Vec<RemoteInfo>> {
+	scope_time!("get_remotes_info");
+
+	let repo = repo(repo_path)?;
+	let mut res = Vec::new();
+
+	for name in repo.remotes()?.iter().flatten() {
+		let remote = repo.find_remote(name)?;
+		let url = remote.url().map(String::from);
+		let push_url = remote
+			.pushurl()
+			.filter(|push_url| Some(*push_url) != url.as_deref())
+			.map(String::from);
+
+		res.push(RemoteInfo {
+			name: name.to_string(),
+			url,
+			push_url,
+		});
+	}
+
+	Ok(res)
+}
+
 ///
 pub fn get_remote_url(
 	repo_path: &RepoPath,
@@ -266,66 +341,155 @@ pub(crate) fn get_default_remote_in_repo(
 	Err(Error::NoDefaultRemoteFound)
 }
 
-///
+/// result of fetching one or more remotes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FetchStats {
+	/// refs created, moved or pruned
+	pub updated_refs: usize,
+	///
+	pub received_objects: usize,
+	///
+	pub received_bytes: usize,
+}
+
+impl std::ops::AddAssign for FetchStats {
+	fn add_assign(&mut self, other: Self) {
+		self.updated_refs += other.updated_refs;
+		self.received_objects += other.received_objects;
+		self.received_bytes += other.received_bytes;
+	}
+}
+
+/// pruning follows `fetch.prune`/`remote.<name>.prune`
 fn fetch_from_remote(
 	repo_path: &RepoPath,
 	remote: &str,
 	basic_credential: Option<BasicAuthCredential>,
 	progress_sender: Option<Sender<ProgressNotification>>,
-) -> Result<()> {
+) -> Result<FetchStats> {
 	let repo = repo(repo_path)?;
 
 	let mut remote = repo.find_remote(remote)?;
+	let mut stats = FetchStats::default();
+
+	// the second pass fetches tags (also removing remotely deleted ones
+	// if pruning), every connection tries the credentials on its own
+	for refspecs in [&[] as &[&str], &["refs/tags/*:refs/tags/*"]] {
+		let mut options = FetchOptions::new();
+		let callbacks = Callbacks::new(
+			&repo,
+			progress_sender.clone(),
+			basic_credential.clone(),
+		);
+		options.prune(git2::FetchPrune::Unspecified);
+		options.proxy_options(proxy_auto());
+		options.download_tags(git2::AutotagOption::All);
+		options.remote_callbacks(callbacks.callbacks());
 
-	let mut options = FetchOptions::new();
-	let callbacks = Callbacks::new(progress_sender, basic_credential);
-	options.prune(git2::FetchPrune::On);
-	options.proxy_options(proxy_auto());
-	options.download_tags(git2::AutotagOption::All);
-	options.remote_callbacks(callbacks.callbacks());
-	remote.fetch(&[] as &[&str], Some(&mut options), None)?;
-	// fetch tags (also removing remotely deleted ones)
-	remote.fetch(
-		&["refs/tags/*:refs/tags/*"],
-		Some(&mut options),
-		None,
-	)?;
+		remote.fetch(refspecs, Some(&mut options), None)?;
+		callbacks.approve_credential();
 
-	Ok(())
+		stats.received_objects += remote.stats().received_objects();
+		stats.received_bytes += remote.stats().received_bytes();
+		stats.updated_refs += callbacks.get_stats()?.updated_refs;
+	}
+
+	Ok(stats)
 }
 
-/// updates/prunes all branches from all remotes
-pub fn fetch_all(
+/// deletes the remote tracking branches of `remote` whose branch is
+/// gone on the remote (like `git remote prune`), returns the deleted refs
+pub fn prune(
 	repo_path: &RepoPath,
-	basic_credential: &Option<BasicAuthCredential>,
-	progress_sender: &Option<Sender<ProgressPercent>>,
-) -> Result<()> {
-	scope_time!("fetch_all");
+	remote: &str,
+	basic_credential: Option<BasicAuthCredential>,
+) -> Result<Vec<String>> {
+	scope_time!("prune");
 
 	let repo = repo(repo_path)?;
-	let remotes = repo
-		.remotes()?
-		.iter()
-		.flatten()
-		.map(String::from)
-		.collect::<Vec<_>>();
-	let remotes_count = remotes.len();
+	let mut remote = repo.find_remote(remote)?;
+
+	let remote_heads: HashSet<String> = {
+		let callbacks = Callbacks::new(&repo, None, basic_credential);
+		let conn = remote.connect_auth(
+			Direction::Fetch,
+			Some(callbacks.callbacks()),
+			Some(proxy_auto()),
+		)?;
+
+		let heads: HashSet<String> = conn
+			.list()?
+			.iter()
+			.map(|head| head.name().to_string())
+			.collect();
+		callbacks.approve_credential();
+
+		heads
+	};
+
+	let mut stale = Vec::new();
+	for reference in repo.references()?.flatten() {
+		let Some(name) = reference.name() else {
+			continue;
+		};
+		// skip symbolic refs like `origin/HEAD`
+		if reference.kind() != Some(ReferenceType::Direct) {
+			continue;
+		}
+
+		let sources: Vec<String> = remote
+			.refspecs()
+			.filter(|spec| {
+				spec.direction() == Direction::Fetch
+					&& spec.dst_matches(name)
+			})
+			.filter_map(|spec| {
+				spec.rtransform(name)
+					.ok()
+					.and_then(|src| src.as_str().map(String::from))
+			})
+			.collect();
+
+		if !sources.is_empty()
+			&& sources.iter().all(|src| !remote_heads.contains(src))
+		{
+			stale.push(name.to_string());
+		}
+	}
+
+	for name in &stale {
+		repo.find_reference(name)?.delete()?;
+	}
+
+	Ok(stale)
+}
+
+/// fetches `remote` or all remotes (`None`), see `fetch_from_remote`.
+/// progress of each remote is reported to `progress_sender`
+pub fn fetch_remotes(
+	repo_path: &RepoPath,
+	remote: Option<&str>,
+	basic_credential: Option<BasicAuthCredential>,
+	progress_sender: Option<Sender<ProgressNotification>>,
+) -> Result<FetchStats> {
+	scope_time!("fetch_remotes");
 
-	for (idx, remote) in remotes.into_iter().enumerate() {
-		fetch_from_remote(
+	let remotes = match remote {
+		Some(remote) => vec![remote.to_string()],
+		None => get_remotes(repo_path)?,
+	};
+
+	let mut stats = FetchStats::default();
+	for remote in remotes {
+		stats += fetch_from_remote(
 			repo_path,
 			&remote,
 			basic_credential.clone(),
-			None,
+			progress_sender.clone(),
 		)?;
-
-		if let Some(sender) = progress_sender {
-			let progress = ProgressPercent::new(idx, remotes_count);
-			sender.send(progress)?;
-		}
 	}
 
-	Ok(())
+	Ok(stats)
 }
 
 /// fetches from upstream/remote for local `branch`
@@ -348,11 +512,13 @@ pub(crate) fn fetch(
 
 	let mut options = FetchOptions::new();
 	options.download_tags(git2::AutotagOption::All);
-	let callbacks = Callbacks::new(progress_sender, basic_credential);
+	let callbacks =
+		Callbacks::new(&repo, progress_sender, basic_credential);
 	options.remote_callbacks(callbacks.callbacks());
 	options.proxy_options(proxy_auto());
 
 	remote.fetch(&[branch], Some(&mut options), None)?;
+	callbacks.approve_credential();
 
 	Ok(remote.stats().received_bytes())
 }
@@ -360,9 +526,13 @@ pub(crate) fn fetch(
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::sync::tests::{
-		debug_cmd_print, repo_clone, repo_init,
+	use crate::sync::{
+		branch::{get_branches_info, BranchInfo},
+		tests::{
+			debug_cmd_print, repo_clone, repo_init, write_commit_file,
+		},
 	};
+	use crossbeam_channel::unbounded;
 
 	#[test]
 	fn test_smoke() {
@@ -383,6 +553,238 @@ mod tests {
 		fetch(repo_path, "master", None, None).unwrap();
 	}
 
+	#[test]
+	fn test_fetch_remotes() {
+		let (remote_dir, remote) = repo_init().unwrap();
+		let remote_path = remote_dir.path().to_str().unwrap();
+		let (repo_dir, repo) = repo_init().unwrap();
+		let repo_path: &RepoPath =
+			&repo_dir.path().to_str().unwrap().into();
+
+		add_remote(repo_path, "origin", remote_path).unwrap();
+
+		let c1 = write_commit_file(&remote, "a.txt", "a", "c1");
+		remote
+			.branch(
+				"feature",
+				&remote.find_commit(c1.into()).unwrap(),
+				false,
+			)
+			.unwrap();
+
+		let (sender, receiver) = unbounded();
+		let stats =
+			fetch_remotes(repo_path, None, None, Some(sender))
+				.unwrap();
+
+		assert_eq!(
+			repo.find_reference("refs/remotes/origin/master")
+				.unwrap()
+				.target()
+				.unwrap(),
+			c1.get_oid()
+		);
+		assert!(repo
+			.find_reference("refs/remotes/origin/feature")
+			.is_ok());
+		assert!(stats.updated_refs >= 2);
+		assert!(stats.received_objects > 0);
+		assert!(receiver.try_iter().any(|notification| matches!(
+			notification,
+			ProgressNotification::UpdateTips { .. }
+		)));
+
+		// nothing new
+		let stats =
+			fetch_remotes(repo_path, Some("origin"), None, None)
+				.unwrap();
+		assert_eq!(stats.updated_refs, 0);
+		assert_eq!(stats.received_objects, 0);
+
+		// pruning only if configured
+		remote
+			.find_branch("feature", BranchType::Local)
+			.unwrap()
+			.delete()
+			.unwrap();
+		fetch_remotes(repo_path, None, None, None).unwrap();
+		assert!(repo
+			.find_reference("refs/remotes/origin/feature")
+			.is_ok());
+
+		repo.config()
+			.unwrap()
+			.set_bool("fetch.prune", true)
+			.unwrap();
+		let stats =
+			fetch_remotes(repo_path, None, None, None).unwrap();
+		assert!(repo
+			.find_reference("refs/remotes/origin/feature")
+			.is_err());
+		assert!(stats.updated_refs > 0);
+	}
+
+	#[test]
+	fn test_prune() {
+		let (remote_dir, remote) = repo_init().unwrap();
+		let remote_path = remote_dir.path().to_str().unwrap();
+		let (repo_dir, repo) = repo_init().unwrap();
+		let repo_path: &RepoPath =
+			&repo_dir.path().to_str().unwrap().into();
+
+		add_remote(repo_path, "origin", remote_path).unwrap();
+
+		let head = remote.head().unwrap().peel_to_commit().unwrap();
+		remote.branch("feature", &head, false).unwrap();
+		remote.branch("other", &head, false).unwrap();
+		fetch_remotes(repo_path, None, None, None).unwrap();
+
+		let mut feature = repo
+			.branch(
+				"feature",
+				&repo.head().unwrap().peel_to_commit().unwrap(),
+				false,
+			)
+			.unwrap();
+		feature.set_upstream(Some("origin/feature")).unwrap();
+
+		remote
+			.find_branch("feature", BranchType::Local)
+			.unwrap()
+			.delete()
+			.unwrap();
+
+		assert_eq!(
+			prune(repo_path, "origin", None).unwrap(),
+			vec![String::from("refs/remotes/origin/feature")]
+		);
+		assert!(repo
+			.find_reference("refs/remotes/origin/feature")
+			.is_err());
+		assert!(repo
+			.find_reference("refs/remotes/origin/master")
+			.is_ok());
+		assert!(prune(repo_path, "origin", None).unwrap().is_empty());
+
+		let branches = get_branches_info(repo_path, true).unwrap();
+		let gone = |name: &str| {
+			branches
+				.iter()
+				.find(|branch| branch.name == name)
+				.and_then(BranchInfo::local_details)
+				.unwrap()
+				.upstream_gone
+		};
+		assert!(gone("feature"));
+		assert!(!gone("master"));
+
+		// `remote.<name>.prune` is honored by fetch
+		remote
+			.find_branch("other", BranchType::Local)
+			.unwrap()
+			.delete()
+			.unwrap();
+		repo.config()
+			.unwrap()
+			.set_bool("remote.origin.prune", true)
+			.unwrap();
+		fetch_remotes(repo_path, None, None, None).unwrap();
+		assert!(repo
+			.find_reference("refs/remotes/origin/other")
+			.is_err());
+	}
+
+	#[test]
+	fn test_remote_management() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		add_remote(repo_path, "origin", "git@github.com:foo/bar.git")
+			.unwrap();
+		assert!(matches!(
+			add_remote(repo_path, "origin", "/tmp/other"),
+			Err(Error::RemoteAlreadyExists(_))
+		));
+		assert!(matches!(
+			add_remote(repo_path, "in valid", "/tmp/other"),
+			Err(Error::InvalidRemoteName(_))
+		));
+		assert!(matches!(
+			add_remote(repo_path, "other", " "),
+			Err(Error::InvalidRemoteUrl(_))
+		));
+
+		let head = repo.head().unwrap().target().unwrap();
+		repo.reference("refs/remotes/origin/master", head, false, "")
+			.unwrap();
+		let mut config = repo.config().unwrap();
+		config.set_str("branch.master.remote", "origin").unwrap();
+		config
+			.set_str("branch.master.merge", "refs/heads/master")
+			.unwrap();
+
+		rename_remote(repo_path, "origin", "upstream").unwrap();
+
+		let config = repo.config().unwrap().snapshot().unwrap();
+		assert_eq!(
+			config.get_str("branch.master.remote").unwrap(),
+			"upstream"
+		);
+		assert!(repo
+			.find_reference("refs/remotes/upstream/master")
+			.is_ok());
+		assert!(repo
+			.find_reference("refs/remotes/origin/master")
+			.is_err());
+
+		update_remote_url(
+			repo_path,
+			"upstream",
+			"ssh://host/bar.git",
+		)
+		.unwrap();
+		repo.remote_set_pushurl(
+			"upstream",
+			Some("ssh://host/bar.git"),
+		)
+		.unwrap();
+		add_remote(repo_path, "other", "https://host/other.git")
+			.unwrap();
+		repo.remote_set_pushurl("other", Some("git@host:other.git"))
+			.unwrap();
+
+		assert_eq!(
+			get_remotes_info(repo_path).unwrap(),
+			vec![
+				RemoteInfo {
+					name: String::from("other"),
+					url: Some(String::from("https://host/other.git")),
+					push_url: Some(String::from(
+						"git@host:other.git"
+					)),
+				},
+				RemoteInfo {
+					name: String::from("upstream"),
+					url: Some(String::from("ssh://host/bar.git")),
+					push_url: None,
+				},
+			]
+		);
+
+		delete_remote(repo_path, "upstream").unwrap();
+
+		assert_eq!(get_remotes(repo_path).unwrap(), vec!["other"]);
+		assert!(repo
+			.references_glob("refs/remotes/upstream/*")
+			.unwrap()
+			.next()
+			.is_none());
+		let config = repo.config().unwrap().snapshot().unwrap();
+		assert!(config.get_str("branch.master.remote").is_err());
+	}
+
 	#[test]
 	fn test_default_remote() {
 		let (remote_dir, _remote) = repo_init().unwrap();
@@ -1,6 +1,7 @@
 use crate::{
 	error::{Error, Result},
 	progress::ProgressPercent,
+	remote_progress::ProgressEvent,
 	sync::{
 		branch::branch_set_upstream_after_push,
 		cred::BasicAuthCredential,
@@ -10,7 +11,9 @@ use crate::{
 	},
 };
 use crossbeam_channel::Sender;
-use git2::{PackBuilderStage, PushOptions};
+use git2::{
+	Direction, ErrorCode, PackBuilderStage, PushOptions, Repository,
+};
 use scopetime::scope_time;
 
 ///
@@ -19,6 +22,10 @@ pub trait AsyncProgress: Clone + Send + Sync {
 	fn is_done(&self) -> bool;
 	///
 	fn progress(&self) -> ProgressPercent;
+	/// event sent for the ui to follow a remote operation
+	fn progress_event(&self) -> Option<ProgressEvent> {
+		None
+	}
 }
 
 ///
@@ -39,6 +46,12 @@ pub enum ProgressNotification {
 		objects: usize,
 		///
 		total_objects: usize,
+		///
+		received_bytes: usize,
+		///
+		indexed_deltas: usize,
+		///
+		total_deltas: usize,
 	},
 	///
 	PushTransfer {
@@ -89,6 +102,40 @@ impl AsyncProgress for ProgressNotification {
 			_ => ProgressPercent::full(),
 		}
 	}
+	fn progress_event(&self) -> Option<ProgressEvent> {
+		match *self {
+			Self::Transfer {
+				objects,
+				total_objects,
+				received_bytes,
+				indexed_deltas,
+				total_deltas,
+			} => Some(
+				if objects == total_objects && total_deltas > 0 {
+					ProgressEvent::Deltas {
+						resolved: indexed_deltas,
+						total: total_deltas,
+					}
+				} else {
+					ProgressEvent::Objects {
+						received: objects,
+						total: total_objects,
+						bytes: received_bytes,
+					}
+				},
+			),
+			Self::PushTransfer {
+				current,
+				total,
+				bytes,
+			} => Some(ProgressEvent::PushWrites {
+				current,
+				total,
+				bytes,
+			}),
+			_ => None,
+		}
+	}
 }
 
 ///
@@ -123,11 +170,62 @@ pub fn push_branch(
 		PushType::Branch,
 		force,
 		delete,
+		true,
 		basic_credential,
 		progress_sender,
 	)
 }
 
+/// refuses to force push `branch` if it moved on `remote` since it was
+/// last fetched, like `git push --force-with-lease`
+fn check_push_lease(
+	repo: &Repository,
+	remote_name: &str,
+	branch: &str,
+	basic_credential: Option<BasicAuthCredential>,
+) -> Result<()> {
+	let reference = format!("refs/heads/{branch}");
+	let fetched = repo
+		.find_reference(&format!(
+			"refs/remotes/{remote_name}/{branch}"
+		))
+		.ok()
+		.and_then(|tracking| tracking.target())
+		.map(CommitId::new);
+
+	let mut remote = repo.find_remote(remote_name)?;
+	let callbacks = Callbacks::new(repo, None, basic_credential);
+	let conn = remote.connect_auth(
+		Direction::Push,
+		Some(callbacks.callbacks()),
+		Some(proxy_auto()),
+	)?;
+
+	let heads = conn.list()?;
+	callbacks.approve_credential();
+
+	let current = heads
+		.iter()
+		.find(|head| head.name() == reference)
+		.map(|head| CommitId::new(head.oid()));
+
+	if current == fetched {
+		Ok(())
+	} else {
+		Err(Error::StaleRemoteRef {
+			reference,
+			fetched,
+			remote: current,
+		})
+	}
+}
+
+/// pushes `branch` (prefixed with `+` if `force`d) to `remote`.
+/// force pushing a branch fails with `Error::StaleRemoteRef` if the
+/// remote changed since the last fetch. a ref rejected by the remote
+/// (e.g. not fast-forward or declined by a hook) fails with
+/// `Error::PushRejected`. with `set_upstream` a pushed branch without
+/// upstream gets `remote/branch` as its upstream
 //TODO: clenaup
 #[allow(clippy::too_many_arguments)]
 pub fn push_raw(
@@ -137,18 +235,31 @@ pub fn push_raw(
 	ref_type: PushType,
 	force: bool,
 	delete: bool,
+	set_upstream: bool,
 	basic_credential: Option<BasicAuthCredential>,
 	progress_sender: Option<Sender<ProgressNotification>>,
 ) -> Result<()> {
 	scope_time!("push");
 
 	let repo = repo(repo_path)?;
-	let mut remote = repo.find_remote(remote)?;
+	let remote_name = remote;
+
+	if force && !delete && matches!(ref_type, PushType::Branch) {
+		check_push_lease(
+			&repo,
+			remote_name,
+			branch,
+			basic_credential.clone(),
+		)?;
+	}
+
+	let mut remote = repo.find_remote(remote_name)?;
 
 	let mut options = PushOptions::new();
 	options.proxy_options(proxy_auto());
 
-	let callbacks = Callbacks::new(progress_sender, basic_credential);
+	let callbacks =
+		Callbacks::new(&repo, progress_sender, basic_credential);
 	options.remote_callbacks(callbacks.callbacks());
 	options.packbuilder_parallelism(0);
 
@@ -158,25 +269,37 @@ pub fn push_raw(
 		(true, false) => "+",
 		(false, false) => "",
 	};
-	let ref_type = match ref_type {
+	let ref_kind = match ref_type {
 		PushType::Branch => "heads",
 		PushType::Tag => "tags",
 	};
 
-	let branch_name =
-		format!("{branch_modifier}refs/{ref_type}/{branch}");
-	remote.push(&[branch_name.as_str()], Some(&mut options))?;
+	let reference = format!("refs/{ref_kind}/{branch}");
+	let branch_name = format!("{branch_modifier}{reference}");
+	remote
+		.push(&[branch_name.as_str()], Some(&mut options))
+		.map_err(|e| {
+			// checked locally before anything is sent
+			if e.code() == ErrorCode::NotFastForward {
+				Error::PushRejected(
+					reference,
+					String::from("non-fast-forward"),
+				)
+			} else {
+				e.into()
+			}
+		})?;
+	callbacks.approve_credential();
 
 	if let Some((reference, msg)) =
 		callbacks.get_stats()?.push_rejected_msg
 	{
-		return Err(Error::Generic(format!(
-			"push to '{reference}' rejected: {msg}"
-		)));
+		return Err(Error::PushRejected(reference, msg));
 	}
 
-	if !delete {
-		branch_set_upstream_after_push(&repo, branch)?;
+	if set_upstream && !delete && matches!(ref_type, PushType::Branch)
+	{
+		branch_set_upstream_after_push(&repo, remote_name, branch)?;
 	}
 
 	Ok(())
@@ -187,6 +310,7 @@ mod tests {
 	use super::*;
 	use crate::sync::{
 		self,
+		remotes::fetch_remotes,
 		tests::{
 			get_commit_ids, repo_clone, repo_init, repo_init_bare,
 			write_commit_file,
@@ -223,6 +347,12 @@ mod tests {
 			File::create(tmp_repo_file_path).unwrap();
 		writeln!(tmp_repo_file, "TempSomething").unwrap();
 
+		sync::stage_add_file(
+			&tmp_repo_dir.path().to_str().unwrap().into(),
+			Path::new("temp_file.txt"),
+		)
+		.unwrap();
+
 		sync::commit(
 			&tmp_repo_dir.path().to_str().unwrap().into(),
 			"repo_1_commit",
@@ -246,6 +376,12 @@ mod tests {
 			File::create(tmp_other_repo_file_path).unwrap();
 		writeln!(tmp_other_repo_file, "TempElse").unwrap();
 
+		sync::stage_add_file(
+			&tmp_other_repo_dir.path().to_str().unwrap().into(),
+			Path::new("temp_file.txt"),
+		)
+		.unwrap();
+
 		sync::commit(
 			&tmp_other_repo_dir.path().to_str().unwrap().into(),
 			"repo_2_commit",
@@ -265,8 +401,15 @@ mod tests {
 		)
 		.is_err());
 
-		// Attempt force push,
+		// Attempt force push after fetching,
 		// should work as it forces the push through
+		fetch_remotes(
+			&tmp_other_repo_dir.path().to_str().unwrap().into(),
+			Some("origin"),
+			None,
+			None,
+		)
+		.unwrap();
 		assert!(!push_branch(
 			&tmp_other_repo_dir.path().to_str().unwrap().into(),
 			"origin",
@@ -393,8 +536,15 @@ mod tests {
 		let commits = get_commit_ids(&upstream, 1);
 		assert!(!commits.contains(&repo_2_commit));
 
-		// Attempt force push,
+		// Attempt force push after fetching,
 		// should work as it forces the push through
+		fetch_remotes(
+			&tmp_other_repo_dir.path().to_str().unwrap().into(),
+			Some("origin"),
+			None,
+			None,
+		)
+		.unwrap();
 
 		push_branch(
 			&tmp_other_repo_dir.path().to_str().unwrap().into(),
@@ -422,6 +572,141 @@ mod tests {
 		assert_eq!(new_upstream_parent, repo_2_parent,);
 	}
 
+	#[test]
+	fn test_push_rejected_and_upstream() {
+		let (upstream_dir, upstream) = repo_init_bare().unwrap();
+		let upstream_path = upstream_dir.path().to_str().unwrap();
+
+		let (dir1, repo1) = repo_clone(upstream_path).unwrap();
+		let path1: &RepoPath = &dir1.path().to_str().unwrap().into();
+		let (dir2, repo2) = repo_clone(upstream_path).unwrap();
+		let path2: &RepoPath = &dir2.path().to_str().unwrap().into();
+
+		// push to a second remote to check the upstream set
+		repo1.remote("second", upstream_path).unwrap();
+
+		let c1 = write_commit_file(&repo1, "a.txt", "a", "c1");
+		sync::create_branch(path1, "feature").unwrap();
+
+		// without `set_upstream` the branch stays untracked
+		push_raw(
+			path1,
+			"second",
+			"feature",
+			PushType::Branch,
+			false,
+			false,
+			false,
+			None,
+			None,
+		)
+		.unwrap();
+		assert!(repo1
+			.find_branch("feature", git2::BranchType::Local)
+			.unwrap()
+			.upstream()
+			.is_err());
+
+		let (sender, receiver) = crossbeam_channel::unbounded();
+		push_branch(
+			path1,
+			"second",
+			"feature",
+			false,
+			false,
+			None,
+			Some(sender),
+		)
+		.unwrap();
+
+		assert_eq!(
+			upstream
+				.find_reference("refs/heads/feature")
+				.unwrap()
+				.target()
+				.unwrap(),
+			c1.get_oid()
+		);
+		assert!(receiver.try_iter().next().is_some());
+
+		let config = repo1.config().unwrap().snapshot().unwrap();
+		assert_eq!(
+			config.get_str("branch.feature.remote").unwrap(),
+			"second"
+		);
+		assert_eq!(
+			config.get_str("branch.feature.merge").unwrap(),
+			"refs/heads/feature"
+		);
+
+		// diverged history is rejected
+		write_commit_file(&repo2, "a.txt", "b", "c2");
+		sync::create_branch(path2, "feature").unwrap();
+		let res = push_branch(
+			path2, "origin", "feature", false, false, None, None,
+		);
+		assert!(matches!(
+			res,
+			Err(Error::PushRejected(reference, _))
+				if reference == "refs/heads/feature"
+		));
+		assert!(repo2
+			.find_branch("feature", git2::BranchType::Local)
+			.unwrap()
+			.upstream()
+			.is_err());
+	}
+
+	#[test]
+	fn test_force_push_lease() {
+		let (upstream_dir, _upstream) = repo_init_bare().unwrap();
+		let upstream_path = upstream_dir.path().to_str().unwrap();
+
+		let (dir1, repo1) = repo_clone(upstream_path).unwrap();
+		let path1: &RepoPath = &dir1.path().to_str().unwrap().into();
+		let (dir2, repo2) = repo_clone(upstream_path).unwrap();
+		let path2: &RepoPath = &dir2.path().to_str().unwrap().into();
+
+		write_commit_file(&repo1, "a.txt", "a", "c1");
+		push_branch(
+			path1, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+		fetch_remotes(path2, Some("origin"), None, None).unwrap();
+
+		// the remote advances behind the back of repo2
+		let c2 = write_commit_file(&repo1, "a.txt", "b", "c2");
+		push_branch(
+			path1, "origin", "master", false, false, None, None,
+		)
+		.unwrap();
+
+		let fetched = repo2
+			.find_reference("refs/remotes/origin/master")
+			.unwrap()
+			.target()
+			.map(CommitId::new);
+		write_commit_file(&repo2, "a.txt", "c", "c3");
+
+		let res = push_branch(
+			path2, "origin", "master", true, false, None, None,
+		);
+		assert!(matches!(
+			res,
+			Err(Error::StaleRemoteRef { reference, fetched: f, remote })
+				if reference == "refs/heads/master"
+					&& f == fetched
+					&& remote == Some(c2)
+		));
+
+		// after fetching the new remote state force pushing works
+		fetch_remotes(path2, Some("origin"), None, None).unwrap();
+		push_branch(
+			path2, "origin", "master", true, false, None, None,
+		)
+		.unwrap();
+	}
+
 	#[test]
 	fn test_delete_remote_branch() {
 		// This test mimics the scenario of a user creating a branch, push it, and then remove it on the remote
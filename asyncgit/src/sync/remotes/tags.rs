@@ -57,7 +57,7 @@ fn remote_tag_refs(
 
 	let repo = repo(repo_path)?;
 	let mut remote = repo.find_remote(remote)?;
-	let callbacks = Callbacks::new(None, basic_credential);
+	let callbacks = Callbacks::new(&repo, None, basic_credential);
 	let conn = remote.connect_auth(
 		Direction::Fetch,
 		Some(callbacks.callbacks()),
@@ -65,6 +65,7 @@ fn remote_tag_refs(
 	)?;
 
 	let remote_heads = conn.list()?;
+	callbacks.approve_credential();
 	let remote_tags = remote_heads
 		.iter()
 		.map(|s| s.name().to_string())
@@ -132,11 +133,12 @@ pub fn push_tags(
 	for (idx, tag) in tags_missing.into_iter().enumerate() {
 		let mut options = PushOptions::new();
 		let callbacks =
-			Callbacks::new(None, basic_credential.clone());
+			Callbacks::new(&repo, None, basic_credential.clone());
 		options.remote_callbacks(callbacks.callbacks());
 		options.packbuilder_parallelism(0);
 		options.proxy_options(proxy_auto());
 		remote.push(&[tag.as_str()], Some(&mut options))?;
+		callbacks.approve_credential();
 
 		progress_sender.as_ref().map(|sender| {
 			sender.send(PushTagsProgress::Push {
@@ -160,7 +162,7 @@ mod tests {
 		sync::{
 			self, delete_tag,
 			remotes::{
-				fetch, fetch_all,
+				fetch, fetch_remotes,
 				push::{push_branch, push_raw},
 			},
 			tests::{repo_clone, repo_init_bare},
@@ -190,7 +192,8 @@ mod tests {
 		let commit1 =
 			write_commit_file(&clone1, "test.txt", "test", "commit1");
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 
 		push_branch(
 			clone1_dir, "origin", "master", false, false, None, None,
@@ -238,7 +241,8 @@ mod tests {
 		let commit1 =
 			write_commit_file(&clone1, "test.txt", "test", "commit1");
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 
 		push_branch(
 			clone1_dir, "origin", "master", false, false, None, None,
@@ -272,7 +276,8 @@ mod tests {
 		let commit1 =
 			write_commit_file(&clone1, "test.txt", "test", "commit1");
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 
 		push_branch(
 			clone1_dir, "origin", "master", false, false, None, None,
@@ -314,7 +319,8 @@ mod tests {
 
 		// clone1 - creates tag
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 
 		let tags1 = sync::get_tags(clone1_dir).unwrap();
 
@@ -354,7 +360,8 @@ mod tests {
 
 		// clone1 - creates tag
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 
 		let tags1 = sync::get_tags(clone1_dir).unwrap();
 
@@ -365,7 +372,7 @@ mod tests {
 
 		// clone 2 - pull
 
-		fetch_all(clone2_dir, &None, &None).unwrap();
+		fetch_remotes(clone2_dir, None, None, None).unwrap();
 
 		let tags2 = sync::get_tags(clone2_dir).unwrap();
 
@@ -388,18 +395,24 @@ mod tests {
 		)
 		.unwrap();
 
-		let (clone2_dir, _clone2) = repo_clone(r1_dir).unwrap();
+		let (clone2_dir, clone2) = repo_clone(r1_dir).unwrap();
 		let clone2_dir: &RepoPath =
 			&clone2_dir.path().to_str().unwrap().into();
+		clone2
+			.config()
+			.unwrap()
+			.set_bool("fetch.prune", true)
+			.unwrap();
 
 		// clone1 - creates tag
 
-		sync::tag_commit(clone1_dir, &commit1, "tag1", None).unwrap();
+		sync::tag_commit(clone1_dir, &commit1, "tag1", None, false)
+			.unwrap();
 		push_tags(clone1_dir, "origin", None, None).unwrap();
 
 		// clone 2 - pull
 
-		fetch_all(clone2_dir, &None, &None).unwrap();
+		fetch_remotes(clone2_dir, None, None, None).unwrap();
 		assert_eq!(sync::get_tags(clone2_dir).unwrap().len(), 1);
 
 		// delete on clone 1
@@ -413,6 +426,7 @@ mod tests {
 			PushType::Tag,
 			false,
 			true,
+			false,
 			None,
 			None,
 		)
@@ -422,7 +436,7 @@ mod tests {
 
 		// clone 2
 
-		fetch_all(clone2_dir, &None, &None).unwrap();
+		fetch_remotes(clone2_dir, None, None, None).unwrap();
 		assert_eq!(sync::get_tags(clone2_dir).unwrap().len(), 0);
 	}
 }
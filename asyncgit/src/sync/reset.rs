@@ -1,9 +1,25 @@
-use super::{utils::get_head_repo, CommitId, RepoPath};
-use crate::{error::Result, sync::repository::repo};
-use git2::{build::CheckoutBuilder, ObjectType, ResetType};
+use super::{
+	utils::{
+		changed_entries, get_head_repo, index_entries, work_dir,
+	},
+	CommitId, RepoPath,
+};
+use crate::{
+	error::{Error, Result},
+	sync::repository::repo,
+};
+use git2::{
+	build::CheckoutBuilder, ObjectType, Repository, ResetType,
+	Status, StatusOptions, StatusShow,
+};
 use scopetime::scope_time;
+use std::{
+	fs,
+	path::{Component, Path},
+};
 
-///
+/// resets the index entry of `path` to its HEAD version, a newly added
+/// file (or any file in a repo without commits) is dropped from the index
 pub fn reset_stage(repo_path: &RepoPath, path: &str) -> Result<()> {
 	scope_time!("reset_stage");
 
@@ -21,6 +37,34 @@ pub fn reset_stage(repo_path: &RepoPath, path: &str) -> Result<()> {
 	Ok(())
 }
 
+/// resets the whole index to HEAD (see `reset_stage`), returns the
+/// number of files that got unstaged. refuses while there are
+/// unresolved conflicts, resetting would silently drop them
+pub fn reset_stage_all(repo_path: &RepoPath) -> Result<usize> {
+	scope_time!("reset_stage_all");
+
+	let repo = repo(repo_path)?;
+
+	let before = {
+		let index = repo.index()?;
+
+		if index.has_conflicts() {
+			return Err(Error::Generic(String::from(
+				"cannot unstage all: unresolved conflicts",
+			)));
+		}
+
+		index_entries(&index)
+	};
+
+	reset_stage(repo_path, "*")?;
+
+	let mut index = repo.index()?;
+	index.read(true)?;
+
+	Ok(changed_entries(&before, &index))
+}
+
 ///
 pub fn reset_workdir(repo_path: &RepoPath, path: &str) -> Result<()> {
 	scope_time!("reset_workdir");
@@ -38,6 +82,230 @@ pub fn reset_workdir(repo_path: &RepoPath, path: &str) -> Result<()> {
 	Ok(())
 }
 
+/// throws away the workdir changes of the tracked file `path` by
+/// checking it out from the index, see `remove_untracked_file`
+/// for untracked files
+pub fn reset_workdir_file(
+	repo_path: &RepoPath,
+	path: &str,
+) -> Result<()> {
+	scope_time!("reset_workdir_file");
+
+	let repo = repo(repo_path)?;
+
+	let mut checkout_opts = CheckoutBuilder::new();
+	checkout_opts
+		.update_index(true)
+		.force()
+		.disable_pathspec_match(true)
+		.path(path);
+
+	repo.checkout_index(None, Some(&mut checkout_opts))?;
+	Ok(())
+}
+
+/// deletes the untracked file or folder `path` from disk. only what
+/// status reports as untracked is deleted (ignored files in an
+/// untracked folder are kept), fails if `path` is not untracked or
+/// does not point into the workdir
+pub fn remove_untracked_file(
+	repo_path: &RepoPath,
+	path: &str,
+) -> Result<()> {
+	scope_time!("remove_untracked_file");
+
+	let repo = repo(repo_path)?;
+	let work_dir = work_dir(&repo)?;
+
+	let path = path.trim_end_matches('/');
+	check_workdir_path(work_dir, path)?;
+
+	let mut options = StatusOptions::default();
+	options
+		.show(StatusShow::Workdir)
+		.include_untracked(true)
+		.recurse_untracked_dirs(true)
+		.include_ignored(false)
+		.exclude_submodules(true)
+		.disable_pathspec_match(true)
+		.pathspec(path);
+
+	let statuses = repo.statuses(Some(&mut options))?;
+	let untracked = statuses
+		.iter()
+		.map(|e| {
+			e.path()
+				.filter(|_| e.status() == Status::WT_NEW)
+				.map(String::from)
+		})
+		.collect::<Option<Vec<_>>>()
+		.filter(|paths| !paths.is_empty())
+		.ok_or_else(|| {
+			Error::Generic(format!("not untracked: `{path}`"))
+		})?;
+
+	for file in untracked {
+		remove_with_empty_parents(work_dir, &work_dir.join(file))?;
+	}
+
+	Ok(())
+}
+
+/// `path` has to be relative and point to something inside the
+/// workdir (but not into `.git`)
+fn check_workdir_path(work_dir: &Path, path: &str) -> Result<()> {
+	let invalid =
+		|| Error::Generic(format!("invalid workdir path: `{path}`"));
+
+	let mut components = Path::new(path).components().peekable();
+	if components.peek().is_none()
+		|| !components.all(|c| matches!(c, Component::Normal(_)))
+		|| Path::new(path).starts_with(".git")
+	{
+		return Err(invalid());
+	}
+
+	// symlinked folders could still lead outside
+	let full_path = work_dir.join(path);
+	if let Some(parent) = full_path.parent() {
+		if !parent
+			.canonicalize()?
+			.starts_with(work_dir.canonicalize()?)
+		{
+			return Err(invalid());
+		}
+	}
+
+	Ok(())
+}
+
+/// deletes the file `full_path` and all folders up to `work_dir`
+/// that are left empty
+fn remove_with_empty_parents(
+	work_dir: &Path,
+	full_path: &Path,
+) -> Result<()> {
+	// read-only files (e.g. on windows) cannot be deleted as is
+	make_writable(full_path)?;
+	fs::remove_file(full_path)?;
+
+	let mut dir = full_path.parent();
+	while let Some(parent) = dir {
+		if parent == work_dir || fs::remove_dir(parent).is_err() {
+			break;
+		}
+		dir = parent.parent();
+	}
+
+	Ok(())
+}
+
+/// paths `reset_workdir_all` would restore or delete, nothing is touched.
+/// untracked folders are listed file by file
+pub fn reset_workdir_all_paths(
+	repo_path: &RepoPath,
+	include_untracked: bool,
+	include_ignored: bool,
+) -> Result<Vec<String>> {
+	scope_time!("reset_workdir_all_paths");
+
+	let repo = repo(repo_path)?;
+
+	Ok(workdir_changes(&repo, include_untracked, include_ignored)?
+		.into_iter()
+		.map(|(path, _)| path)
+		.collect())
+}
+
+/// throws away all workdir changes by checking out the index
+/// (`git checkout -- .`), optionally deletes untracked (`git clean -fd`)
+/// and ignored files (`-x`). returns the number of affected paths,
+/// see `reset_workdir_all_paths` to list them beforehand
+pub fn reset_workdir_all(
+	repo_path: &RepoPath,
+	include_untracked: bool,
+	include_ignored: bool,
+) -> Result<usize> {
+	scope_time!("reset_workdir_all");
+
+	let repo = repo(repo_path)?;
+
+	if repo.index()?.has_conflicts() {
+		return Err(Error::Generic(String::from(
+			"cannot discard all: unresolved conflicts",
+		)));
+	}
+
+	let changes =
+		workdir_changes(&repo, include_untracked, include_ignored)?;
+
+	let (tracked, untracked): (Vec<_>, Vec<_>) =
+		changes.iter().partition(|(_, status)| {
+			!status.intersects(Status::WT_NEW | Status::IGNORED)
+		});
+
+	if !tracked.is_empty() {
+		let mut checkout_opts = CheckoutBuilder::new();
+		checkout_opts
+			.update_index(true)
+			.force()
+			.disable_pathspec_match(true);
+		for (path, _) in &tracked {
+			checkout_opts.path(path.as_str());
+		}
+
+		repo.checkout_index(None, Some(&mut checkout_opts))?;
+	}
+
+	let work_dir = work_dir(&repo)?;
+	for (path, _) in untracked {
+		remove_with_empty_parents(work_dir, &work_dir.join(path))?;
+	}
+
+	Ok(changes.len())
+}
+
+fn workdir_changes(
+	repo: &Repository,
+	include_untracked: bool,
+	include_ignored: bool,
+) -> Result<Vec<(String, Status)>> {
+	let mut options = StatusOptions::default();
+	options
+		.show(StatusShow::Workdir)
+		.include_untracked(include_untracked)
+		.recurse_untracked_dirs(true)
+		.include_ignored(include_ignored)
+		.recurse_ignored_dirs(true)
+		.exclude_submodules(true);
+
+	let statuses = repo.statuses(Some(&mut options))?;
+
+	Ok(statuses
+		.iter()
+		.filter_map(|e| Some((e.path()?.to_string(), e.status())))
+		.collect())
+}
+
+fn make_writable(path: &Path) -> Result<()> {
+	let meta = path.symlink_metadata()?;
+
+	if meta.is_dir() {
+		for entry in fs::read_dir(path)? {
+			make_writable(&entry?.path())?;
+		}
+	}
+
+	let mut permissions = meta.permissions();
+	if !meta.file_type().is_symlink() && permissions.readonly() {
+		#[allow(clippy::permissions_set_readonly_false)]
+		permissions.set_readonly(false);
+		fs::set_permissions(path, permissions)?;
+	}
+
+	Ok(())
+}
+
 ///
 pub fn reset_repo(
 	repo_path: &RepoPath,
@@ -57,17 +325,25 @@ pub fn reset_repo(
 
 #[cfg(test)]
 mod tests {
-	use super::{reset_stage, reset_workdir};
+	use super::{
+		remove_untracked_file, reset_stage, reset_stage_all,
+		reset_workdir, reset_workdir_all, reset_workdir_all_paths,
+		reset_workdir_file,
+	};
 	use crate::error::Result;
 	use crate::sync::{
-		commit,
+		checkout_branch, commit, create_branch,
+		diff::get_diff,
+		merge_branch,
 		status::{get_status, StatusType},
 		tests::{
-			debug_cmd_print, get_statuses, repo_init, repo_init_empty,
+			debug_cmd_print, get_statuses, repo_init,
+			repo_init_empty, write_commit_file,
 		},
 		utils::{stage_add_all, stage_add_file},
 		RepoPath,
 	};
+	use git2::BranchType;
 	use std::{
 		fs::{self, File},
 		io::Write,
@@ -107,8 +383,9 @@ mod tests {
 		let repo_path: &RepoPath =
 			&root.as_os_str().to_str().unwrap().into();
 
-		let res = get_status(repo_path, StatusType::WorkingDir, None)
-			.unwrap();
+		let res =
+			get_status(repo_path, &StatusType::WorkingDir.into())
+				.unwrap();
 		assert_eq!(res.len(), 0);
 
 		let file_path = root.join("bar.txt");
@@ -336,4 +613,256 @@ mod tests {
 
 		assert_eq!(get_statuses(repo_path), (0, 0));
 	}
+
+	#[test]
+	fn test_reset_stage_modified() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		reset_stage(repo_path, "foo.txt").unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+
+		let diff =
+			get_diff(repo_path, "foo.txt", false, None).unwrap();
+		assert_eq!(diff.lines, 3);
+	}
+
+	#[test]
+	fn test_reset_stage_new_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		fs::write(root.join("foo.txt"), "a\n").unwrap();
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		reset_stage(repo_path, "foo.txt").unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+		assert!(
+			get_diff(repo_path, "foo.txt", false, None)
+				.unwrap()
+				.untracked
+		);
+	}
+
+	#[test]
+	fn test_reset_stage_unborn_head() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		fs::write(root.join("foo.txt"), "a\n").unwrap();
+		stage_add_file(repo_path, Path::new("foo.txt")).unwrap();
+
+		assert_eq!(get_statuses(repo_path), (0, 1));
+
+		reset_stage(repo_path, "foo.txt").unwrap();
+
+		assert_eq!(get_statuses(repo_path), (1, 0));
+	}
+
+	#[test]
+	fn test_stage_and_reset_all() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "modified.txt", "a\n", "c1");
+		write_commit_file(&repo, "deleted.txt", "a\n", "c2");
+
+		fs::write(root.join("modified.txt"), "b\n").unwrap();
+		fs::remove_file(root.join("deleted.txt")).unwrap();
+		fs::write(root.join("new.txt"), "a\n").unwrap();
+
+		assert_eq!(get_statuses(repo_path), (3, 0));
+
+		assert_eq!(stage_add_all(repo_path, "*", None).unwrap(), 3);
+		assert_eq!(get_statuses(repo_path), (0, 3));
+
+		assert_eq!(stage_add_all(repo_path, "*", None).unwrap(), 0);
+
+		assert_eq!(reset_stage_all(repo_path).unwrap(), 3);
+		assert_eq!(get_statuses(repo_path), (3, 0));
+	}
+
+	#[test]
+	fn test_reset_all_conflicted() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		create_branch(repo_path, "other").unwrap();
+		write_commit_file(&repo, "foo.txt", "theirs\n", "c2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "foo.txt", "ours\n", "c3");
+		merge_branch(repo_path, "other", BranchType::Local, false)
+			.unwrap();
+
+		assert!(reset_stage_all(repo_path).is_err());
+
+		fs::write(root.join("foo.txt"), "resolved\n").unwrap();
+		assert_eq!(stage_add_all(repo_path, "*", None).unwrap(), 1);
+		assert!(get_status(repo_path, &StatusType::Conflicts.into())
+			.unwrap()
+			.is_empty());
+
+		assert_eq!(reset_stage_all(repo_path).unwrap(), 1);
+	}
+
+	#[test]
+	fn test_reset_workdir_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+		fs::write(root.join("new.txt"), "a\n").unwrap();
+
+		reset_workdir_file(repo_path, "foo.txt").unwrap();
+
+		assert_eq!(
+			fs::read_to_string(root.join("foo.txt")).unwrap(),
+			"a\n"
+		);
+		// untracked files are left alone
+		assert!(root.join("new.txt").exists());
+		assert!(remove_untracked_file(repo_path, "foo.txt").is_err());
+	}
+
+	#[test]
+	fn test_remove_untracked_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", "a\n", "c1");
+
+		fs::write(root.join("new.txt"), "a\n").unwrap();
+		let mut permissions =
+			fs::metadata(root.join("new.txt")).unwrap().permissions();
+		permissions.set_readonly(true);
+		fs::set_permissions(root.join("new.txt"), permissions)
+			.unwrap();
+
+		fs::create_dir_all(root.join("dir/sub")).unwrap();
+		fs::write(root.join("dir/sub/a.txt"), "a\n").unwrap();
+		fs::write(root.join("dir/b.txt"), "a\n").unwrap();
+
+		remove_untracked_file(repo_path, "new.txt").unwrap();
+		assert!(!root.join("new.txt").exists());
+
+		remove_untracked_file(repo_path, "dir/sub/a.txt").unwrap();
+		assert!(!root.join("dir/sub/a.txt").exists());
+		assert!(root.join("dir/b.txt").exists());
+
+		remove_untracked_file(repo_path, "dir/").unwrap();
+		assert!(!root.join("dir").exists());
+
+		assert_eq!(get_statuses(repo_path), (0, 0));
+	}
+
+	#[test]
+	fn test_remove_untracked_file_guards() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, ".gitignore", "*.log\n", "c1");
+		fs::create_dir_all(root.join("dir")).unwrap();
+		fs::write(root.join("dir/a.txt"), "a\n").unwrap();
+		fs::write(root.join("dir/keep.log"), "a\n").unwrap();
+
+		for path in ["", ".", "./", "..", "../x", "dir/../..", ".git"]
+		{
+			assert!(
+				remove_untracked_file(repo_path, path).is_err(),
+				"{path}"
+			);
+		}
+		assert!(
+			remove_untracked_file(repo_path, "dir/keep.log").is_err()
+		);
+		assert!(root.join(".git").exists());
+
+		// ignored files inside are kept
+		remove_untracked_file(repo_path, "dir").unwrap();
+		assert!(!root.join("dir/a.txt").exists());
+		assert!(root.join("dir/keep.log").exists());
+	}
+
+	#[test]
+	fn test_reset_workdir_all() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, ".gitignore", "*.log\n", "c1");
+		write_commit_file(&repo, "foo.txt", "a\n", "c2");
+
+		fs::write(root.join("foo.txt"), "b\n").unwrap();
+		fs::write(root.join("new.txt"), "a\n").unwrap();
+		fs::create_dir_all(root.join("dir/sub")).unwrap();
+		fs::write(root.join("dir/sub/a.txt"), "a\n").unwrap();
+		fs::write(root.join("dir/b.log"), "a\n").unwrap();
+		fs::write(root.join("c.log"), "a\n").unwrap();
+
+		let mut paths =
+			reset_workdir_all_paths(repo_path, true, false).unwrap();
+		paths.sort();
+		assert_eq!(
+			paths,
+			vec!["dir/sub/a.txt", "foo.txt", "new.txt"]
+		);
+		assert_eq!(
+			reset_workdir_all_paths(repo_path, false, false).unwrap(),
+			vec!["foo.txt"]
+		);
+		assert!(root.join("new.txt").exists());
+
+		assert_eq!(
+			reset_workdir_all(repo_path, true, false).unwrap(),
+			3
+		);
+
+		assert_eq!(
+			fs::read_to_string(root.join("foo.txt")).unwrap(),
+			"a\n"
+		);
+		assert!(!root.join("new.txt").exists());
+		assert!(!root.join("dir/sub").exists());
+		assert!(root.join("dir/b.log").exists());
+		assert!(root.join("c.log").exists());
+		assert_eq!(get_statuses(repo_path), (0, 0));
+
+		assert_eq!(
+			reset_workdir_all(repo_path, true, true).unwrap(),
+			2
+		);
+
+		assert!(!root.join("dir").exists());
+		assert!(!root.join("c.log").exists());
+		assert!(root.join(".gitignore").exists());
+	}
 }
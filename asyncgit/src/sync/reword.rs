@@ -32,7 +32,14 @@ pub fn reword(
 				.len() == 0
 			{
 				undo_last_commit(repo_path)?;
-				return super::commit(repo_path, message);
+				return super::commit_with_options(
+					repo_path,
+					message,
+					&super::CommitOptions {
+						allow_empty: true,
+						..super::CommitOptions::default()
+					},
+				);
 			}
 
 			return Err(Error::SignRewordLastCommitStaged);
@@ -1,4 +1,4 @@
-use super::{apply_selection, load_file};
+use super::{apply_selection, load_file, split_lines};
 use crate::{
 	error::Result,
 	sync::{
@@ -32,12 +32,12 @@ pub fn discard_lines(
 		)?;
 
 		let working_content = load_file(&repo, file_path)?;
-		let old_lines = working_content.lines().collect::<Vec<_>>();
+		let old_lines = split_lines(&working_content);
 
 		apply_selection(lines, &hunks, &old_lines, false, true)?
 	};
 
-	repo_write_file(&repo, file_path, new_content.as_str())?;
+	repo_write_file(&repo, file_path, new_content)?;
 
 	Ok(())
 }
@@ -95,7 +95,7 @@ mod test {
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	#[test]
@@ -134,7 +134,7 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	#[test]
@@ -179,7 +179,7 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	#[test]
@@ -226,7 +226,7 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	#[test]
@@ -269,7 +269,7 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	//this test shows that we require at least a diff context around add/removes of 1
@@ -308,7 +308,7 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 
 	#[test]
@@ -341,6 +341,6 @@ end
 
 		let result_file = load_file(&repo, "test.txt").unwrap();
 
-		assert_eq!(result_file.as_str(), FILE_3);
+		assert_eq!(String::from_utf8(result_file).unwrap(), FILE_3);
 	}
 }
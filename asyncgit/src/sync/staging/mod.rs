@@ -9,74 +9,79 @@ use super::{
 };
 use crate::error::Result;
 use git2::{DiffLine, DiffLineType, Repository};
-use std::{collections::HashSet, fs::File, io::Read};
+use std::{collections::HashSet, fs};
 
-const NEWLINE: char = '\n';
+const NEWLINE: u8 = b'\n';
 
 #[derive(Default)]
 struct NewFromOldContent {
-	lines: Vec<String>,
+	lines: Vec<Vec<u8>>,
 	old_index: usize,
 }
 
 impl NewFromOldContent {
-	fn add_from_hunk(&mut self, line: &DiffLine) -> Result<()> {
-		let line = String::from_utf8(line.content().into())?;
+	fn add_from_hunk(&mut self, line: &DiffLine) {
+		let line = line.content();
 
-		let line = if line.ends_with(NEWLINE) {
-			line[0..line.len() - 1].to_string()
-		} else {
-			line
-		};
+		let line = line.strip_suffix(&[NEWLINE]).unwrap_or(line);
 
-		self.lines.push(line);
-
-		Ok(())
+		self.lines.push(line.to_vec());
 	}
 
 	fn skip_old_line(&mut self) {
 		self.old_index += 1;
 	}
 
-	fn add_old_line(&mut self, old_lines: &[&str]) {
-		self.lines.push(old_lines[self.old_index].to_string());
+	fn add_old_line(&mut self, old_lines: &[&[u8]]) {
+		self.lines.push(old_lines[self.old_index].to_vec());
 		self.old_index += 1;
 	}
 
 	fn catchup_to_hunkstart(
 		&mut self,
 		hunk_start: usize,
-		old_lines: &[&str],
+		old_lines: &[&[u8]],
 	) {
 		while hunk_start > self.old_index + 1 {
 			self.add_old_line(old_lines);
 		}
 	}
 
-	fn finish(mut self, old_lines: &[&str]) -> String {
+	fn finish(mut self, old_lines: &[&[u8]]) -> Vec<u8> {
 		for line in old_lines.iter().skip(self.old_index) {
-			self.lines.push((*line).to_string());
+			self.lines.push(line.to_vec());
 		}
-		let lines = self.lines.join("\n");
-		if lines.ends_with(NEWLINE) {
-			lines
-		} else {
-			let mut lines = lines;
+		let mut lines = self.lines.join(&NEWLINE);
+		if !lines.ends_with(&[NEWLINE]) {
 			lines.push(NEWLINE);
-			lines
 		}
+		lines
 	}
 }
 
+/// splits `content` into lines like `str::lines` but
+/// without requiring valid utf8
+fn split_lines(content: &[u8]) -> Vec<&[u8]> {
+	let content = content.strip_suffix(&[NEWLINE]).unwrap_or(content);
+	if content.is_empty() {
+		return Vec::new();
+	}
+
+	content
+		.split(|b| *b == NEWLINE)
+		.map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+		.collect()
+}
+
 // this is the heart of the per line discard,stage,unstage. heavily inspired by the great work in nodegit: https://github.com/nodegit/nodegit
 #[allow(clippy::redundant_pub_crate)]
 pub(crate) fn apply_selection(
 	lines: &[DiffLinePosition],
 	hunks: &[HunkLines],
-	old_lines: &[&str],
+	old_lines: &[&[u8]],
 	is_staged: bool,
 	reverse: bool,
-) -> Result<String> {
+) -> Result<Vec<u8>> {
 	let mut new_content = NewFromOldContent::default();
 	let lines = lines.iter().collect::<HashSet<_>>();
 
@@ -140,7 +145,7 @@ pub(crate) fn apply_selection(
 					|| (!is_staged && selected_line)
 				{
 					if hunk_line.origin_value() == added {
-						new_content.add_from_hunk(hunk_line)?;
+						new_content.add_from_hunk(hunk_line);
 						if is_staged {
 							new_content.skip_old_line();
 						}
@@ -153,7 +158,7 @@ pub(crate) fn apply_selection(
 					}
 				} else {
 					if hunk_line.origin_value() != added {
-						new_content.add_from_hunk(hunk_line)?;
+						new_content.add_from_hunk(hunk_line);
 					}
 
 					if (is_staged
@@ -174,11 +179,8 @@ pub(crate) fn apply_selection(
 pub fn load_file(
 	repo: &Repository,
 	file_path: &str,
-) -> Result<String> {
+) -> Result<Vec<u8>> {
 	let repo_path = work_dir(repo)?;
-	let mut file = File::open(repo_path.join(file_path).as_path())?;
-	let mut res = String::new();
-	file.read_to_string(&mut res)?;
 
-	Ok(res)
+	Ok(fs::read(repo_path.join(file_path))?)
 }
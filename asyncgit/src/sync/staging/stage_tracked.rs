@@ -1,4 +1,4 @@
-use super::apply_selection;
+use super::{apply_selection, split_lines};
 use crate::{
 	error::{Error, Result},
 	sync::{
@@ -36,19 +36,18 @@ pub fn stage_lines(
 			))
 		})?;
 	let blob = repo.find_blob(idx.id)?;
-	let indexed_content = String::from_utf8(blob.content().into())?;
 
 	let new_content = {
 		let (_patch, hunks) = get_file_diff_patch_and_hunklines(
 			&repo, file_path, is_stage, false,
 		)?;
 
-		let old_lines = indexed_content.lines().collect::<Vec<_>>();
+		let old_lines = split_lines(blob.content());
 
 		apply_selection(lines, &hunks, &old_lines, is_stage, false)?
 	};
 
-	let blob_id = repo.blob(new_content.as_bytes())?;
+	let blob_id = repo.blob(&new_content)?;
 
 	idx.id = blob_id;
 	idx.file_size = u32::try_conv(new_content.len())?;
@@ -64,7 +63,8 @@ pub fn stage_lines(
 mod test {
 	use super::*;
 	use crate::sync::{
-		diff::get_diff,
+		commit,
+		diff::{get_diff, DiffLineType},
 		tests::{get_statuses, repo_init, write_commit_file},
 		utils::{repo_write_file, stage_add_file},
 	};
@@ -189,4 +189,141 @@ c = 4";
 
 		assert_eq!(diff.lines, 4);
 	}
+
+	#[test]
+	fn test_stage_deletion_shifts_following_hunk() {
+		static FILE_1: &str = r"0
+1
+2
+3
+4
+5
+6
+7
+8
+9
+";
+
+		static FILE_2: &str = r"0
+2
+3
+4
+5
+6
+7
+x
+9
+";
+
+		let (path, repo) = repo_init().unwrap();
+		let path: &RepoPath = &path.path().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", FILE_1, "c1");
+
+		repo_write_file(&repo, "test.txt", FILE_2).unwrap();
+
+		stage_lines(
+			path,
+			"test.txt",
+			false,
+			&[DiffLinePosition {
+				old_lineno: Some(2),
+				new_lineno: None,
+			}],
+		)
+		.unwrap();
+
+		let diff = get_diff(path, "test.txt", true, None).unwrap();
+
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(
+			&*diff.hunks[0].lines[0].content,
+			"@@ -1,5 +1,4 @@"
+		);
+
+		let diff = get_diff(path, "test.txt", false, None).unwrap();
+
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(
+			&*diff.hunks[0].lines[0].content,
+			"@@ -5,5 +5,5 @@"
+		);
+		let changed = diff.hunks[0]
+			.lines
+			.iter()
+			.filter(|l| l.line_type != DiffLineType::None)
+			.map(|l| l.position)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			changed[1..],
+			[
+				DiffLinePosition {
+					old_lineno: Some(8),
+					new_lineno: None,
+				},
+				DiffLinePosition {
+					old_lineno: None,
+					new_lineno: Some(8),
+				}
+			]
+		);
+	}
+
+	#[test]
+	fn test_stage_invalid_utf8_lossless() {
+		// latin-1 encoded "café"
+		static FILE_1: &[u8] = b"caf\xe9\nb\n";
+		static FILE_2: &[u8] = b"caf\xe9\nb\xe9\n";
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let path: &RepoPath = &root.to_str().unwrap().into();
+
+		repo_write_file(&repo, "test.txt", FILE_1).unwrap();
+		stage_add_file(path, Path::new("test.txt")).unwrap();
+		commit(path, "c1").unwrap();
+
+		repo_write_file(&repo, "test.txt", FILE_2).unwrap();
+
+		let diff = get_diff(path, "test.txt", false, None).unwrap();
+		let lines = &diff.hunks[0].lines;
+		assert_eq!(lines[1].line_type, DiffLineType::None);
+		assert!(lines[1].invalid_utf8);
+		assert_eq!(lines[1].bytes(), b"caf\xe9");
+		assert_eq!(&*lines[1].content, "caf\u{fffd}");
+		assert_eq!(lines[3].bytes(), b"b\xe9");
+		assert!(!lines[2].invalid_utf8);
+
+		let selection = [
+			DiffLinePosition {
+				old_lineno: Some(2),
+				new_lineno: None,
+			},
+			DiffLinePosition {
+				old_lineno: None,
+				new_lineno: Some(2),
+			},
+		];
+
+		let indexed = || {
+			let repo = crate::sync::repository::repo(path).unwrap();
+			let id = repo
+				.index()
+				.unwrap()
+				.get_path(Path::new("test.txt"), 0)
+				.unwrap()
+				.id;
+			let content =
+				repo.find_blob(id).unwrap().content().to_vec();
+			content
+		};
+
+		stage_lines(path, "test.txt", false, &selection).unwrap();
+		assert_eq!(indexed(), FILE_2);
+		assert_eq!(get_statuses(path), (0, 1));
+
+		stage_lines(path, "test.txt", true, &selection).unwrap();
+		assert_eq!(indexed(), FILE_1);
+		assert_eq!(get_statuses(path), (1, 0));
+	}
 }
@@ -1,11 +1,11 @@
 use super::{CommitId, RepoPath};
 use crate::{
 	error::{Error, Result},
-	sync::repository::repo,
+	sync::{conflict::conflicting_paths, repository::repo},
 };
 use git2::{
-	build::CheckoutBuilder, Oid, Repository, StashApplyOptions,
-	StashFlags,
+	build::CheckoutBuilder, CheckoutNotificationType, ErrorCode, Oid,
+	Repository, StashApplyOptions, StashFlags,
 };
 use scopetime::scope_time;
 
@@ -23,6 +23,43 @@ pub fn get_stashes(repo_path: &RepoPath) -> Result<Vec<CommitId>> {
 	Ok(list)
 }
 
+/// entry of the stash list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashInfo {
+	/// position in the list, like `stash@{index}`
+	pub index: usize,
+	/// the stash commit
+	pub id: CommitId,
+	/// e.g. `WIP on master: abc123 subject` or `On master: msg`
+	pub message: String,
+	/// commit time in seconds since the epoch
+	pub time: i64,
+}
+
+/// the stashes in git's order, most recent first
+pub fn stash_list(repo_path: &RepoPath) -> Result<Vec<StashInfo>> {
+	scope_time!("stash_list");
+
+	let mut repo = repo(repo_path)?;
+	let mut list = Vec::new();
+	repo.stash_foreach(|index, msg, id| {
+		list.push((index, *id, msg.to_string()));
+		true
+	})?;
+
+	list.into_iter()
+		.map(|(index, id, message)| {
+			let time = repo.find_commit(id)?.time().seconds();
+			Ok(StashInfo {
+				index,
+				id: id.into(),
+				message,
+				time,
+			})
+		})
+		.collect()
+}
+
 ///
 pub fn stash_drop(
 	repo_path: &RepoPath,
@@ -39,42 +76,103 @@ pub fn stash_drop(
 	Ok(())
 }
 
-///
+/// result of applying a stash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StashApplyOutcome {
+	///
+	Applied,
+	/// paths conflicting with HEAD (left with conflict markers) or,
+	/// if conflicts are allowed, with local changes (left untouched)
+	Conflicts(Vec<String>),
+}
+
+/// applies the stash and drops it unless that conflicts, fails with
+/// `Error::LocalChangesConflict` if local changes would be overwritten
 pub fn stash_pop(
 	repo_path: &RepoPath,
 	stash_id: CommitId,
-) -> Result<()> {
+	reinstate_index: bool,
+) -> Result<StashApplyOutcome> {
 	scope_time!("stash_pop");
 
 	let mut repo = repo(repo_path)?;
 
-	let index = get_stash_index(&mut repo, stash_id.into())?;
+	let outcome =
+		apply_stash(&mut repo, stash_id, false, reinstate_index)?;
 
-	repo.stash_pop(index, None)?;
+	if outcome == StashApplyOutcome::Applied {
+		let index = get_stash_index(&mut repo, stash_id.into())?;
+		repo.stash_drop(index)?;
+	}
 
-	Ok(())
+	Ok(outcome)
 }
 
-///
+/// applies the stash (restoring the staged changes if
+/// `reinstate_index`), fails with `Error::LocalChangesConflict` if
+/// local changes would be overwritten unless `allow_conflicts`
 pub fn stash_apply(
 	repo_path: &RepoPath,
 	stash_id: CommitId,
 	allow_conflicts: bool,
-) -> Result<()> {
+	reinstate_index: bool,
+) -> Result<StashApplyOutcome> {
 	scope_time!("stash_apply");
 
 	let mut repo = repo(repo_path)?;
 
-	let index = get_stash_index(&mut repo, stash_id.get_oid())?;
+	apply_stash(&mut repo, stash_id, allow_conflicts, reinstate_index)
+}
+
+fn apply_stash(
+	repo: &mut Repository,
+	stash_id: CommitId,
+	allow_conflicts: bool,
+	reinstate_index: bool,
+) -> Result<StashApplyOutcome> {
+	let index = get_stash_index(repo, stash_id.get_oid())?;
+
+	let mut local_conflicts = Vec::new();
+	let res = {
+		let mut checkout = CheckoutBuilder::new();
+		checkout.allow_conflicts(allow_conflicts);
+		checkout.notify_on(CheckoutNotificationType::CONFLICT);
+		checkout.notify(|_, path, _, _, _| {
+			if let Some(path) = path {
+				local_conflicts
+					.push(path.to_string_lossy().to_string());
+			}
+			true
+		});
+
+		let mut opt = StashApplyOptions::default();
+		opt.checkout_options(checkout);
+		if reinstate_index {
+			opt.reinstantiate_index();
+		}
+		repo.stash_apply(index, Some(&mut opt))
+	};
 
-	let mut checkout = CheckoutBuilder::new();
-	checkout.allow_conflicts(allow_conflicts);
+	if let Err(e) = res {
+		return Err(
+			if e.code() == ErrorCode::Conflict
+				&& !local_conflicts.is_empty()
+			{
+				Error::LocalChangesConflict(local_conflicts)
+			} else {
+				e.into()
+			},
+		);
+	}
 
-	let mut opt = StashApplyOptions::default();
-	opt.checkout_options(checkout);
-	repo.stash_apply(index, Some(&mut opt))?;
+	let mut conflicts = local_conflicts;
+	conflicts.extend(conflicting_paths(&repo.index()?)?);
 
-	Ok(())
+	Ok(if conflicts.is_empty() {
+		StashApplyOutcome::Applied
+	} else {
+		StashApplyOutcome::Conflicts(conflicts)
+	})
 }
 
 fn get_stash_index(
@@ -97,7 +195,9 @@ fn get_stash_index(
 	})
 }
 
-///
+/// stashes the changes of tracked files (keeping the staged ones in the
+/// index if `keep_index`) and optionally untracked files, fails with
+/// `Error::NothingToStash` if there is nothing to stash
 pub fn stash_save(
 	repo_path: &RepoPath,
 	message: Option<&str>,
@@ -119,7 +219,15 @@ pub fn stash_save(
 		options.insert(StashFlags::KEEP_INDEX);
 	}
 
-	let id = repo.stash_save2(&sig, message, Some(options))?;
+	let id = repo.stash_save2(&sig, message, Some(options)).map_err(
+		|e| {
+			if e.code() == ErrorCode::NotFound {
+				Error::NothingToStash
+			} else {
+				e.into()
+			}
+		},
+	)?;
 
 	Ok(CommitId::new(id))
 }
@@ -144,7 +252,10 @@ mod tests {
 		let repo_path: &RepoPath =
 			&root.as_os_str().to_str().unwrap().into();
 
-		assert!(!stash_save(repo_path, None, true, false).is_ok());
+		assert!(matches!(
+			stash_save(repo_path, None, true, false),
+			Err(Error::NothingToStash)
+		));
 
 		assert!(get_stashes(repo_path).unwrap().is_empty());
 	}
@@ -192,6 +303,41 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_stash_list() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		assert!(stash_list(repo_path)?.is_empty());
+
+		repo_write_file(&repo, "foo.txt", "a")?;
+		let first = stash_save(repo_path, None, true, false)?;
+		repo_write_file(&repo, "foo.txt", "b")?;
+		let second =
+			stash_save(repo_path, Some("second"), true, false)?;
+
+		let list = stash_list(repo_path)?;
+
+		assert_eq!(list.len(), 2);
+		assert_eq!(
+			list.iter()
+				.map(|stash| (stash.index, stash.id))
+				.collect::<Vec<_>>(),
+			vec![(0, second), (1, first)]
+		);
+		assert_eq!(list[0].message, "On master: second");
+		assert!(list[1].message.starts_with("WIP on master: "));
+		assert!(list[0].time > 0);
+		assert_eq!(
+			list.iter().map(|stash| stash.id).collect::<Vec<_>>(),
+			get_stashes(repo_path)?
+		);
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_stash_nothing_untracked() -> Result<()> {
 		let (_td, repo) = repo_init().unwrap();
@@ -202,9 +348,37 @@ mod tests {
 		File::create(root.join("foo.txt"))?
 			.write_all(b"test\nfoo")?;
 
-		assert!(
-			stash_save(repo_path, Some("foo"), false, false).is_err()
-		);
+		assert!(matches!(
+			stash_save(repo_path, Some("foo"), false, false),
+			Err(Error::NothingToStash)
+		));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_stash_tracked_and_untracked() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "tracked.txt", "a", "c1");
+		repo_write_file(&repo, "tracked.txt", "b")?;
+		repo_write_file(&repo, "untracked.txt", "c")?;
+
+		assert_eq!(get_statuses(repo_path), (2, 0));
+
+		let id = stash_save(repo_path, Some("both"), true, false)?;
+
+		assert_eq!(get_statuses(repo_path), (0, 0));
+		assert!(!root.join("untracked.txt").exists());
+		assert_eq!(repo_read_file(&repo, "tracked.txt")?, "a");
+
+		let reflog = repo.reflog("refs/stash")?;
+		assert_eq!(reflog.len(), 1);
+		assert_eq!(reflog.get(0).unwrap().id_new(), id.get_oid());
+		assert_eq!(get_stashes(repo_path)?, vec![id]);
 
 		Ok(())
 	}
@@ -252,7 +426,7 @@ mod tests {
 
 		repo_write_file(&repo, "test.txt", "foo").unwrap();
 
-		let res = stash_apply(repo_path, id, false);
+		let res = stash_apply(repo_path, id, false, false);
 
 		assert!(res.is_err());
 	}
@@ -273,7 +447,7 @@ mod tests {
 
 		repo_write_file(&repo, "test.txt", "test3").unwrap();
 
-		let res = stash_apply(repo_path, id, false);
+		let res = stash_apply(repo_path, id, false, false);
 
 		assert!(res.is_err());
 	}
@@ -294,11 +468,11 @@ mod tests {
 
 		repo_write_file(&repo, "test.txt", "test3").unwrap();
 
-		let res = stash_apply(repo_path, id, false);
+		let res = stash_apply(repo_path, id, false, false);
 
 		assert!(res.is_err());
 
-		let res = stash_apply(repo_path, id, true);
+		let res = stash_apply(repo_path, id, true, false);
 
 		assert!(res.is_ok());
 	}
@@ -317,7 +491,7 @@ mod tests {
 		let id =
 			stash_save(repo_path, Some("foo"), true, false).unwrap();
 
-		let res = stash_pop(repo_path, id);
+		let res = stash_pop(repo_path, id, false);
 
 		assert!(res.is_ok());
 		assert_eq!(
@@ -340,7 +514,7 @@ mod tests {
 
 		repo_write_file(&repo, "test.txt", "test2").unwrap();
 
-		let res = stash_pop(repo_path, id);
+		let res = stash_pop(repo_path, id, false);
 
 		assert!(res.is_err());
 		assert_eq!(
@@ -365,12 +539,72 @@ mod tests {
 
 		repo_write_file(&repo, "test.txt", "test3").unwrap();
 
-		let res = stash_pop(repo_path, id);
+		let res = stash_pop(repo_path, id, false);
 
-		assert!(res.is_err());
+		assert!(matches!(
+			res,
+			Err(Error::LocalChangesConflict(paths))
+				if paths == vec![String::from("test.txt")]
+		));
 		assert_eq!(
 			repo_read_file(&repo, "test.txt").unwrap(),
 			"test3"
 		);
+		assert_eq!(get_stashes(repo_path).unwrap(), vec![id]);
+	}
+
+	#[test]
+	fn test_stash_pop_conflicting_with_head() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "test.txt", "test", "c1");
+		repo_write_file(&repo, "test.txt", "test2").unwrap();
+
+		let id =
+			stash_save(repo_path, Some("foo"), true, false).unwrap();
+
+		write_commit_file(&repo, "test.txt", "other", "c2");
+
+		let res = stash_pop(repo_path, id, false).unwrap();
+
+		assert_eq!(
+			res,
+			StashApplyOutcome::Conflicts(vec![String::from(
+				"test.txt"
+			)])
+		);
+		assert!(repo_read_file(&repo, "test.txt")
+			.unwrap()
+			.contains("<<<<<<<"));
+		// the stash is kept to resolve the conflict
+		assert_eq!(get_stashes(repo_path).unwrap(), vec![id]);
+	}
+
+	#[test]
+	fn test_stash_apply_reinstate_index() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "staged.txt", "a", "c1");
+		write_commit_file(&repo, "unstaged.txt", "a", "c2");
+		repo_write_file(&repo, "staged.txt", "b").unwrap();
+		repo_write_file(&repo, "unstaged.txt", "b").unwrap();
+		stage_add_file(repo_path, Path::new("staged.txt")).unwrap();
+
+		let id = stash_save(repo_path, None, false, false).unwrap();
+
+		assert_eq!(
+			stash_apply(repo_path, id, false, true).unwrap(),
+			StashApplyOutcome::Applied
+		);
+		assert_eq!(get_statuses(repo_path), (1, 1));
+
+		// applying does not drop
+		assert_eq!(get_stashes(repo_path).unwrap(), vec![id]);
 	}
 }
@@ -1,6 +1,9 @@
-use super::RepoPath;
-use crate::{error::Result, sync::repository::repo};
-use git2::RepositoryState;
+use super::{utils::bytes2string, CommitId, RepoPath};
+use crate::{
+	error::{Error, Result},
+	sync::repository::repo,
+};
+use git2::{ErrorCode, RepositoryState};
 use scopetime::scope_time;
 
 ///
@@ -10,10 +13,17 @@ pub enum RepoState {
 	Clean,
 	///
 	Merge,
-	///
+	/// rebase that can be continued or aborted (`rebase-merge`)
 	Rebase,
+	/// interactive or `git am` style rebase started outside of gitui,
+	/// libgit2 can neither continue nor abort those
+	RebaseExternal,
 	///
 	Revert,
+	/// single or sequence of cherry-picks
+	CherryPick,
+	///
+	Bisect,
 	///
 	Other,
 }
@@ -23,8 +33,14 @@ impl From<RepositoryState> for RepoState {
 		match state {
 			RepositoryState::Clean => Self::Clean,
 			RepositoryState::Merge => Self::Merge,
-			RepositoryState::Revert => Self::Revert,
+			RepositoryState::Revert
+			| RepositoryState::RevertSequence => Self::Revert,
 			RepositoryState::RebaseMerge => Self::Rebase,
+			RepositoryState::RebaseInteractive
+			| RepositoryState::Rebase => Self::RebaseExternal,
+			RepositoryState::CherryPick
+			| RepositoryState::CherryPickSequence => Self::CherryPick,
+			RepositoryState::Bisect => Self::Bisect,
 			_ => {
 				log::warn!("state not supported yet: {:?}", state);
 				Self::Other
@@ -43,3 +59,161 @@ pub fn repo_state(repo_path: &RepoPath) -> Result<RepoState> {
 
 	Ok(state.into())
 }
+
+/// what HEAD points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadInfo {
+	/// branch name, or the short commit id if detached
+	pub name: String,
+	/// `None` on an unborn branch (no commit yet)
+	pub id: Option<CommitId>,
+	///
+	pub detached: bool,
+}
+
+impl HeadInfo {
+	/// HEAD is a branch without any commit yet
+	pub const fn is_unborn(&self) -> bool {
+		self.id.is_none()
+	}
+}
+
+///
+pub fn get_head_info(repo_path: &RepoPath) -> Result<HeadInfo> {
+	scope_time!("get_head_info");
+
+	let repo = repo(repo_path)?;
+	let head = repo.find_reference("HEAD")?;
+
+	if let Some(target) = head.symbolic_target_bytes() {
+		let target = bytes2string(target)?;
+
+		let id = match repo.refname_to_id(&target) {
+			Ok(id) => Some(id.into()),
+			Err(e) if e.code() == ErrorCode::NotFound => None,
+			Err(e) => return Err(e.into()),
+		};
+
+		let name = target
+			.strip_prefix("refs/heads/")
+			.unwrap_or(&target)
+			.to_string();
+
+		return Ok(HeadInfo {
+			name,
+			id,
+			detached: false,
+		});
+	}
+
+	let id = CommitId::new(head.target().ok_or(Error::NoHead)?);
+
+	Ok(HeadInfo {
+		name: id.get_short_string(),
+		id: Some(id),
+		detached: true,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sync::{
+		checkout_branch, create_branch, merge_branch,
+		tests::{repo_init, repo_init_empty, write_commit_file},
+	};
+	use git2::BranchType;
+
+	#[test]
+	fn test_head_info() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "a.txt", "a", "c1");
+
+		assert_eq!(
+			get_head_info(repo_path).unwrap(),
+			HeadInfo {
+				name: String::from("master"),
+				id: Some(c1),
+				detached: false,
+			}
+		);
+
+		repo.set_head_detached(c1.into()).unwrap();
+
+		assert_eq!(
+			get_head_info(repo_path).unwrap(),
+			HeadInfo {
+				name: c1.get_short_string(),
+				id: Some(c1),
+				detached: true,
+			}
+		);
+	}
+
+	#[test]
+	fn test_head_info_unborn() {
+		let (_td, repo) = repo_init_empty().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let info = get_head_info(repo_path).unwrap();
+
+		assert!(info.is_unborn());
+		assert!(!info.detached);
+		assert_eq!(info.name, "master");
+	}
+
+	#[test]
+	fn test_state_merge() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "a.txt", "base", "c1");
+		create_branch(repo_path, "foo").unwrap();
+		write_commit_file(&repo, "a.txt", "foo", "c2");
+		checkout_branch(repo_path, "master").unwrap();
+		write_commit_file(&repo, "a.txt", "master", "c3");
+
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Clean);
+
+		merge_branch(repo_path, "foo", BranchType::Local, false)
+			.unwrap();
+		assert!(repo.index().unwrap().has_conflicts());
+
+		assert_eq!(repo_state(repo_path).unwrap(), RepoState::Merge);
+	}
+
+	#[test]
+	fn test_state_rebase_external() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let rebase_dir = repo.path().join("rebase-merge");
+		std::fs::create_dir(&rebase_dir).unwrap();
+		std::fs::write(rebase_dir.join("interactive"), "").unwrap();
+
+		assert_eq!(
+			repo_state(repo_path).unwrap(),
+			RepoState::RebaseExternal
+		);
+
+		std::fs::remove_dir_all(&rebase_dir).unwrap();
+		let rebase_dir = repo.path().join("rebase-apply");
+		std::fs::create_dir(&rebase_dir).unwrap();
+		std::fs::write(rebase_dir.join("rebasing"), "").unwrap();
+
+		assert_eq!(
+			repo_state(repo_path).unwrap(),
+			RepoState::RebaseExternal
+		);
+	}
+}
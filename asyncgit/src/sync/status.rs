@@ -5,9 +5,19 @@ use crate::{
 	error::Result,
 	sync::{config::untracked_files_config_repo, repository::repo},
 };
-use git2::{Delta, Status, StatusOptions, StatusShow};
+use git2::{
+	Delta, DiffDelta, FileMode, Repository, Status, StatusEntry,
+	StatusShow, SubmoduleIgnore, SubmoduleStatus,
+};
 use scopetime::scope_time;
-use std::path::Path;
+use std::{
+	cmp::Ordering,
+	collections::HashMap,
+	fs,
+	hash::{Hash, Hasher},
+	path::Path,
+	time::SystemTime,
+};
 
 use super::{RepoPath, ShowUntrackedFilesConfig};
 
@@ -26,11 +36,25 @@ pub enum StatusItemType {
 	Typechange,
 	///
 	Conflicted,
+	/// the checked out commit or the content of a submodule changed
+	Submodule(SubmoduleChange),
+}
+
+/// how a submodule changed, see `StatusItemType::Submodule`
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug, Default)]
+pub struct SubmoduleChange {
+	/// points to a different commit than before
+	pub new_commits: bool,
+	/// modified or untracked files inside the submodule
+	pub dirty: bool,
 }
 
 impl From<Status> for StatusItemType {
 	fn from(s: Status) -> Self {
-		if s.is_index_new() || s.is_wt_new() {
+		if s.is_conflicted() {
+			Self::Conflicted
+		} else if s.is_index_new() || s.is_wt_new() || s.is_ignored()
+		{
 			Self::New
 		} else if s.is_index_deleted() || s.is_wt_deleted() {
 			Self::Deleted
@@ -38,8 +62,6 @@ impl From<Status> for StatusItemType {
 			Self::Renamed
 		} else if s.is_index_typechange() || s.is_wt_typechange() {
 			Self::Typechange
-		} else if s.is_conflicted() {
-			Self::Conflicted
 		} else {
 			Self::Modified
 		}
@@ -58,13 +80,65 @@ impl From<Delta> for StatusItemType {
 	}
 }
 
-///
-#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+/// equality and hash leave out `mtime` and `size`, touching a file
+/// does not change its status
+#[derive(Clone, Debug)]
 pub struct StatusItem {
 	///
 	pub path: String,
+	/// original path of a renamed file
+	pub old_path: Option<String>,
 	///
 	pub status: StatusItemType,
+	/// matched by a gitignore rule, only ever listed
+	/// with `StatusOptions::show_ignored`
+	pub ignored: bool,
+	/// modification time in the workdir, `None` if the file is gone
+	/// or without `StatusOptions::stat`
+	pub mtime: Option<SystemTime>,
+	/// size in bytes in the workdir, `None` if the file is gone
+	/// or without `StatusOptions::stat`
+	pub size: Option<u64>,
+}
+
+impl PartialEq for StatusItem {
+	fn eq(&self, other: &Self) -> bool {
+		self.path == other.path
+			&& self.old_path == other.old_path
+			&& self.status == other.status
+			&& self.ignored == other.ignored
+	}
+}
+
+impl Eq for StatusItem {}
+
+impl Hash for StatusItem {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.path.hash(state);
+		self.old_path.hash(state);
+		self.status.hash(state);
+		self.ignored.hash(state);
+	}
+}
+
+/// how `StatusOptions::show_ignored` lists ignored files
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct ShowIgnoredFiles {
+	/// list the files inside ignored folders instead of just the
+	/// folder itself, expensive on e.g. a big `target/`
+	pub recurse_dirs: bool,
+	/// list no more than this many ignored files. only the result is
+	/// truncated, the walk still visits all of them (see `recurse_dirs`)
+	pub max_entries: usize,
+}
+
+impl Default for ShowIgnoredFiles {
+	fn default() -> Self {
+		Self {
+			recurse_dirs: false,
+			max_entries: 1000,
+		}
+	}
 }
 
 ///
@@ -76,6 +150,8 @@ pub enum StatusType {
 	Stage,
 	///
 	Both,
+	/// only the paths with unresolved merge conflicts
+	Conflicts,
 }
 
 impl Default for StatusType {
@@ -89,11 +165,29 @@ impl From<StatusType> for StatusShow {
 		match s {
 			StatusType::WorkingDir => Self::Workdir,
 			StatusType::Stage => Self::Index,
-			StatusType::Both => Self::IndexAndWorkdir,
+			StatusType::Both | StatusType::Conflicts => {
+				Self::IndexAndWorkdir
+			}
 		}
 	}
 }
 
+/// how thorough a status pass is
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum StatusPass {
+	/// rereads the index and lists untracked files
+	Full,
+	/// skips untracked files and trusts the already loaded index,
+	/// cheap enough for polling huge repos
+	Quick,
+}
+
+impl Default for StatusPass {
+	fn default() -> Self {
+		Self::Full
+	}
+}
+
 ///
 pub fn is_workdir_clean(
 	repo_path: &RepoPath,
@@ -111,7 +205,7 @@ pub fn is_workdir_clean(
 		untracked_files_config_repo(&repo)?
 	};
 
-	let mut options = StatusOptions::default();
+	let mut options = git2::StatusOptions::default();
 	options
 		.show(StatusShow::Workdir)
 		.update_index(true)
@@ -126,11 +220,38 @@ pub fn is_workdir_clean(
 	Ok(statuses.is_empty())
 }
 
+/// what `get_status` lists
+#[derive(Clone, Hash, PartialEq, Eq, Default)]
+pub struct StatusOptions {
+	///
+	pub status_type: StatusType,
+	/// `None` reads `status.showUntrackedFiles` from the repo config
+	pub show_untracked: Option<ShowUntrackedFilesConfig>,
+	/// also lists the ignored files of the workdir (flagged `ignored`)
+	pub show_ignored: Option<ShowIgnoredFiles>,
+	/// `StatusPass::Quick` leaves out untracked files to stay fast
+	/// on huge repos
+	pub pass: StatusPass,
+	/// only the paths matching these (e.g. `crates/asyncgit/`),
+	/// untracked folders outside of them are not walked
+	pub pathspec: Option<Vec<String>>,
+	/// fills `StatusItem::mtime` and `size`, costs a stat per item
+	pub stat: bool,
+}
+
+impl From<StatusType> for StatusOptions {
+	fn from(status_type: StatusType) -> Self {
+		Self {
+			status_type,
+			..Self::default()
+		}
+	}
+}
+
 /// guarantees sorting
 pub fn get_status(
 	repo_path: &RepoPath,
-	status_type: StatusType,
-	show_untracked: Option<ShowUntrackedFilesConfig>,
+	options: &StatusOptions,
 ) -> Result<Vec<StatusItem>> {
 	scope_time!("get_status");
 
@@ -140,58 +261,907 @@ pub fn get_status(
 		return Ok(Vec::new());
 	}
 
-	let show_untracked = if let Some(config) = show_untracked {
+	let status_type = options.status_type;
+	let statuses =
+		repo.statuses(Some(&mut status_options(&repo, options)?))?;
+
+	let mut ignored = IgnoredLimit::new(options);
+	let mut res = Vec::with_capacity(statuses.len());
+
+	for e in statuses.iter() {
+		let status: Status = e.status();
+
+		if status_type == StatusType::Conflicts
+			&& !status.is_conflicted()
+		{
+			continue;
+		}
+
+		if !ignored.take(status) {
+			continue;
+		}
+
+		let delta = match status_type {
+			StatusType::WorkingDir => e.index_to_workdir(),
+			StatusType::Stage => e.head_to_index(),
+			StatusType::Both => {
+				e.head_to_index().or_else(|| e.index_to_workdir())
+			}
+			StatusType::Conflicts => {
+				e.index_to_workdir().or_else(|| e.head_to_index())
+			}
+		};
+
+		res.push(status_item(
+			&repo,
+			&e,
+			delta,
+			status,
+			options.stat,
+		)?);
+	}
+
+	sort_items(&mut res);
+
+	Ok(res)
+}
+
+/// stage and workdir status (in this order) from a single
+/// walk of the worktree, each equal to what `get_status` returns
+/// for `StatusType::Stage` and `StatusType::WorkingDir` with the
+/// rest of `options` (`options.status_type` is not used).
+/// guarantees sorting
+pub fn get_status_both(
+	repo_path: &RepoPath,
+	options: &StatusOptions,
+) -> Result<(Vec<StatusItem>, Vec<StatusItem>)> {
+	scope_time!("get_status_both");
+
+	let repo = repo(repo_path)?;
+
+	if repo.is_bare() && !repo.is_worktree() {
+		return Ok((Vec::new(), Vec::new()));
+	}
+
+	let options = StatusOptions {
+		status_type: StatusType::Both,
+		..options.clone()
+	};
+	let statuses =
+		repo.statuses(Some(&mut status_options(&repo, &options)?))?;
+
+	let mut ignored = IgnoredLimit::new(&options);
+	let mut stage = Vec::new();
+	let mut workdir = Vec::new();
+
+	for e in statuses.iter() {
+		let status: Status = e.status();
+
+		if !ignored.take(status) {
+			continue;
+		}
+
+		if let Some(delta) = e.head_to_index() {
+			let status = side_status(status, INDEX_BITS, &delta);
+			stage.push(status_item(
+				&repo,
+				&e,
+				Some(delta),
+				status,
+				options.stat,
+			)?);
+		}
+
+		if let Some(delta) = e.index_to_workdir() {
+			let status = side_status(status, WT_BITS, &delta);
+			workdir.push(status_item(
+				&repo,
+				&e,
+				Some(delta),
+				status,
+				options.stat,
+			)?);
+		}
+	}
+
+	sort_items(&mut stage);
+	sort_items(&mut workdir);
+
+	Ok((stage, workdir))
+}
+
+/// counts the listed ignored files against
+/// `ShowIgnoredFiles::max_entries`
+struct IgnoredLimit {
+	left: usize,
+}
+
+impl IgnoredLimit {
+	fn new(options: &StatusOptions) -> Self {
+		Self {
+			left: options
+				.show_ignored
+				.map_or(0, |show_ignored| show_ignored.max_entries),
+		}
+	}
+
+	/// `false` if `status` is ignored and the limit is reached
+	fn take(&mut self, status: Status) -> bool {
+		if !status.is_ignored() {
+			return true;
+		}
+		if self.left == 0 {
+			return false;
+		}
+		self.left -= 1;
+		true
+	}
+}
+
+const INDEX_BITS: Status = Status::INDEX_NEW
+	.union(Status::INDEX_MODIFIED)
+	.union(Status::INDEX_DELETED)
+	.union(Status::INDEX_RENAMED)
+	.union(Status::INDEX_TYPECHANGE);
+
+const WT_BITS: Status = Status::WT_NEW
+	.union(Status::WT_MODIFIED)
+	.union(Status::WT_DELETED)
+	.union(Status::WT_RENAMED)
+	.union(Status::WT_TYPECHANGE);
+
+/// the part of `status` a single-sided `repo.statuses()` call
+/// reports for an entry whose change on that side is `delta`
+fn side_status(
+	status: Status,
+	bits: Status,
+	delta: &DiffDelta,
+) -> Status {
+	let mut res = status & bits;
+	if delta.status() == Delta::Conflicted {
+		res |= Status::CONFLICTED;
+	}
+	res
+}
+
+fn status_options(
+	repo: &Repository,
+	options: &StatusOptions,
+) -> Result<git2::StatusOptions> {
+	let show_untracked = if let Some(config) = options.show_untracked
+	{
 		config
 	} else {
-		untracked_files_config_repo(&repo)?
+		untracked_files_config_repo(repo)?
 	};
 
-	let mut options = StatusOptions::default();
-	options
-		.show(status_type.into())
+	let mut res = git2::StatusOptions::default();
+	res.show(options.status_type.into())
 		.update_index(true)
 		.include_untracked(show_untracked.include_untracked())
 		.renames_head_to_index(true)
+		.renames_index_to_workdir(true)
 		.recurse_untracked_dirs(
 			show_untracked.recurse_untracked_dirs(),
 		);
 
-	let statuses = repo.statuses(Some(&mut options))?;
-
-	let mut res = Vec::with_capacity(statuses.len());
+	if let Some(show_ignored) = options.show_ignored {
+		res.include_ignored(true)
+			.recurse_ignored_dirs(show_ignored.recurse_dirs);
+	}
+	if options.pass == StatusPass::Quick {
+		res.include_untracked(false).no_refresh(true);
+	}
+	for spec in options.pathspec.iter().flatten() {
+		res.pathspec(spec);
+	}
 
-	for e in statuses.iter() {
-		let status: Status = e.status();
+	Ok(res)
+}
 
-		let path = match e.head_to_index() {
-			Some(diff) => diff
-				.new_file()
-				.path()
-				.and_then(Path::to_str)
-				.map(String::from)
-				.ok_or_else(|| {
-					Error::Generic(
-						"failed to get path to diff's new file."
-							.to_string(),
-					)
-				})?,
-			None => e.path().map(String::from).ok_or_else(|| {
+fn status_item(
+	repo: &Repository,
+	e: &StatusEntry,
+	delta: Option<DiffDelta>,
+	status: Status,
+	stat: bool,
+) -> Result<StatusItem> {
+	let path = match &delta {
+		Some(diff) => diff
+			.new_file()
+			.path()
+			.and_then(Path::to_str)
+			.map(String::from)
+			.ok_or_else(|| {
 				Error::Generic(
-					"failed to get the path to indexed file."
+					"failed to get path to diff's new file."
 						.to_string(),
 				)
 			})?,
+		None => e.path().map(String::from).ok_or_else(|| {
+			Error::Generic(
+				"failed to get the path to indexed file.".to_string(),
+			)
+		})?,
+	};
+
+	let old_path = delta
+		.as_ref()
+		.filter(|diff| diff.status() == Delta::Renamed)
+		.and_then(|diff| diff.old_file().path())
+		.and_then(Path::to_str)
+		.map(String::from);
+
+	let submodule = delta
+		.as_ref()
+		.and_then(|diff| submodule_change(repo, diff, status, &path));
+
+	// symlinks report their own metadata, not the target's
+	let meta = repo
+		.workdir()
+		.filter(|_| stat)
+		.and_then(|dir| fs::symlink_metadata(dir.join(&path)).ok());
+
+	Ok(StatusItem {
+		mtime: meta.as_ref().and_then(|meta| meta.modified().ok()),
+		size: meta.as_ref().map(fs::Metadata::len),
+		path,
+		old_path,
+		status: submodule.map_or_else(
+			|| StatusItemType::from(status),
+			StatusItemType::Submodule,
+		),
+		ignored: status.is_ignored(),
+	})
+}
+
+/// `None` unless `delta` is a modified submodule
+fn submodule_change(
+	repo: &Repository,
+	delta: &DiffDelta,
+	status: Status,
+	path: &str,
+) -> Option<SubmoduleChange> {
+	if delta.status() != Delta::Modified
+		|| delta.old_file().mode() != FileMode::Commit
+		|| delta.new_file().mode() != FileMode::Commit
+	{
+		return None;
+	}
+
+	// only the workdir side can be dirty
+	let dirty = status.is_wt_modified()
+		&& repo
+			.submodule_status(path, SubmoduleIgnore::None)
+			.is_ok_and(|status| {
+				status.intersects(
+					SubmoduleStatus::WD_INDEX_MODIFIED
+						| SubmoduleStatus::WD_WD_MODIFIED
+						| SubmoduleStatus::WD_UNTRACKED,
+				)
+			});
+
+	Some(SubmoduleChange {
+		new_commits: delta.old_file().id() != delta.new_file().id(),
+		dirty,
+	})
+}
+
+fn sort_items(items: &mut [StatusItem]) {
+	sort_status_items(items, false);
+}
+
+/// sorts by path, comparing folder by folder case-insensitively.
+/// `dirs_first` puts the subfolders of a folder before its files.
+/// the sort is stable, equal paths keep their order
+pub fn sort_status_items(items: &mut [StatusItem], dirs_first: bool) {
+	items.sort_by(|a, b| compare_paths(&a.path, &b.path, dirs_first));
+}
+
+/// order of status items, see `sort_status_by`
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum SortBy {
+	/// see `sort_status_items`
+	Path,
+	/// most recently modified first, items without `mtime` last
+	/// (see `StatusOptions::stat`)
+	Mtime,
+}
+
+impl Default for SortBy {
+	fn default() -> Self {
+		Self::Path
+	}
+}
+
+/// the sort is stable, `SortBy::Mtime` keeps the path order
+/// of items modified at the same time
+pub fn sort_status_by(items: &mut [StatusItem], sort_by: SortBy) {
+	match sort_by {
+		SortBy::Path => sort_status_items(items, false),
+		SortBy::Mtime => items.sort_by(|a, b| b.mtime.cmp(&a.mtime)),
+	}
+}
+
+fn compare_paths(a: &str, b: &str, dirs_first: bool) -> Ordering {
+	let mut a_parts = a.split('/').peekable();
+	let mut b_parts = b.split('/').peekable();
+
+	loop {
+		match (a_parts.next(), b_parts.next()) {
+			(Some(a_part), Some(b_part)) => {
+				if dirs_first {
+					let a_dir = a_parts.peek().is_some();
+					let b_dir = b_parts.peek().is_some();
+					if a_dir != b_dir {
+						return b_dir.cmp(&a_dir);
+					}
+				}
+
+				let ord = a_part
+					.to_lowercase()
+					.cmp(&b_part.to_lowercase())
+					.then_with(|| a_part.cmp(b_part));
+				if ord != Ordering::Equal {
+					return ord;
+				}
+			}
+			(a_part, b_part) => {
+				return a_part.is_some().cmp(&b_part.is_some())
+			}
+		}
+	}
+}
+
+/// a folder and the indices of its items, see `group_by_dir`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusGroup {
+	/// parent folder, empty for the repo root
+	pub path: String,
+	///
+	pub items: Vec<usize>,
+}
+
+/// groups `items` by their parent folder, groups and items keep
+/// the order in which they first appear in `items`
+pub fn group_by_dir(items: &[StatusItem]) -> Vec<StatusGroup> {
+	let mut groups: Vec<StatusGroup> = Vec::new();
+	let mut lookup: HashMap<&str, usize> = HashMap::new();
+
+	for (idx, item) in items.iter().enumerate() {
+		let parent = item
+			.path
+			.trim_end_matches('/')
+			.rsplit_once('/')
+			.map_or("", |(parent, _)| parent);
+
+		if let Some(group) = lookup.get(parent) {
+			groups[*group].items.push(idx);
+		} else {
+			lookup.insert(parent, groups.len());
+			groups.push(StatusGroup {
+				path: parent.to_string(),
+				items: vec![idx],
+			});
+		}
+	}
+
+	groups
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sync::{
+		checkout_branch, create_branch,
+		diff::get_diff,
+		merge_branch, stage_add_file, stage_addremoved,
+		tests::{repo_init, write_commit_file},
+	};
+	use git2::BranchType;
+	use std::fs;
+
+	static CONTENT: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+	static CONTENT_EDITED: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n";
+
+	#[test]
+	fn test_rename_workdir() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+
+		fs::remove_file(root.join("foo.txt"))?;
+		fs::write(root.join("bar.txt"), CONTENT_EDITED)?;
+
+		let res =
+			get_status(repo_path, &StatusType::WorkingDir.into())?;
+
+		assert_eq!(
+			res,
+			vec![StatusItem {
+				path: String::from("bar.txt"),
+				old_path: Some(String::from("foo.txt")),
+				status: StatusItemType::Renamed,
+				ignored: false,
+				mtime: None,
+				size: None,
+			}]
+		);
+
+		let diff = get_diff(repo_path, "bar.txt", false, None)?;
+
+		assert_eq!(diff.old_path.as_deref(), Some("foo.txt"));
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(diff.lines, 6);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_rename_stage() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+
+		fs::remove_file(root.join("foo.txt"))?;
+		fs::write(root.join("bar.txt"), CONTENT_EDITED)?;
+		stage_addremoved(repo_path, Path::new("foo.txt"))?;
+		stage_add_file(repo_path, Path::new("bar.txt"))?;
+
+		let res = get_status(repo_path, &StatusType::Stage.into())?;
+
+		assert_eq!(
+			res,
+			vec![StatusItem {
+				path: String::from("bar.txt"),
+				old_path: Some(String::from("foo.txt")),
+				status: StatusItemType::Renamed,
+				ignored: false,
+				mtime: None,
+				size: None,
+			}]
+		);
+
+		let diff = get_diff(repo_path, "bar.txt", true, None)?;
+
+		assert_eq!(diff.old_path.as_deref(), Some("foo.txt"));
+		assert_eq!(diff.hunks.len(), 1);
+		assert_eq!(diff.lines, 6);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_status_both() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+		write_commit_file(&repo, "gone.txt", CONTENT, "c2");
+
+		fs::write(root.join("foo.txt"), CONTENT_EDITED)?;
+		stage_add_file(repo_path, Path::new("foo.txt"))?;
+		fs::write(root.join("foo.txt"), "modified again\n")?;
+
+		fs::write(root.join("new.txt"), CONTENT)?;
+		stage_add_file(repo_path, Path::new("new.txt"))?;
+		fs::write(root.join("untracked.txt"), CONTENT)?;
+		fs::remove_file(root.join("gone.txt"))?;
+
+		let (stage, workdir) =
+			get_status_both(repo_path, &StatusOptions::default())?;
+
+		assert_eq!(
+			stage,
+			get_status(repo_path, &StatusType::Stage.into())?
+		);
+		assert_eq!(
+			workdir,
+			get_status(repo_path, &StatusType::WorkingDir.into())?
+		);
+
+		assert_eq!(
+			stage,
+			vec![
+				StatusItem {
+					path: String::from("foo.txt"),
+					old_path: None,
+					status: StatusItemType::Modified,
+					ignored: false,
+					mtime: None,
+					size: None,
+				},
+				StatusItem {
+					path: String::from("new.txt"),
+					old_path: None,
+					status: StatusItemType::New,
+					ignored: false,
+					mtime: None,
+					size: None,
+				},
+			]
+		);
+		assert_eq!(
+			workdir
+				.iter()
+				.map(|item| (item.path.as_str(), item.status))
+				.collect::<Vec<_>>(),
+			vec![
+				("foo.txt", StatusItemType::Modified),
+				("gone.txt", StatusItemType::Deleted),
+				("untracked.txt", StatusItemType::New),
+			]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_delete_stage() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+
+		fs::remove_file(root.join("foo.txt"))?;
+		stage_addremoved(repo_path, Path::new("foo.txt"))?;
+
+		let item = StatusItem {
+			path: String::from("foo.txt"),
+			old_path: None,
+			status: StatusItemType::Deleted,
+			ignored: false,
+			mtime: None,
+			size: None,
 		};
 
-		res.push(StatusItem {
-			path,
-			status: StatusItemType::from(status),
-		});
+		assert_eq!(
+			get_status(repo_path, &StatusType::Stage.into())?,
+			vec![item.clone()]
+		);
+		assert_eq!(
+			get_status_both(repo_path, &StatusOptions::default())?,
+			(vec![item], Vec::new())
+		);
+
+		Ok(())
 	}
 
-	res.sort_by(|a, b| {
-		Path::new(a.path.as_str()).cmp(Path::new(b.path.as_str()))
-	});
+	#[test]
+	fn test_conflicted() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
 
-	Ok(res)
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+		create_branch(repo_path, "other")?;
+		write_commit_file(&repo, "foo.txt", "theirs\n", "c2");
+		checkout_branch(repo_path, "master")?;
+		write_commit_file(&repo, "foo.txt", "ours\n", "c3");
+
+		merge_branch(repo_path, "other", BranchType::Local, false)?;
+
+		let workdir =
+			get_status(repo_path, &StatusType::WorkingDir.into())?;
+
+		assert_eq!(
+			workdir.clone(),
+			vec![StatusItem {
+				path: String::from("foo.txt"),
+				old_path: None,
+				status: StatusItemType::Conflicted,
+				ignored: false,
+				mtime: None,
+				size: None,
+			}]
+		);
+
+		let (stage, both_workdir) =
+			get_status_both(repo_path, &StatusOptions::default())?;
+
+		assert_eq!(both_workdir, workdir);
+		assert_eq!(
+			stage,
+			get_status(repo_path, &StatusType::Stage.into())?
+		);
+
+		assert_eq!(
+			get_status(repo_path, &StatusType::Conflicts.into())?,
+			workdir
+		);
+
+		fs::write(root.join("foo.txt"), "resolved\n")?;
+		stage_add_file(repo_path, Path::new("foo.txt"))?;
+
+		assert!(get_status(
+			repo_path,
+			&StatusType::Conflicts.into()
+		)?
+		.is_empty());
+		assert_eq!(
+			get_status(repo_path, &StatusType::Stage.into())?,
+			vec![StatusItem {
+				path: String::from("foo.txt"),
+				old_path: None,
+				status: StatusItemType::Modified,
+				ignored: false,
+				mtime: None,
+				size: None,
+			}]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_ignored() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		fs::write(root.join(".gitignore"), "*.log\n")?;
+		fs::write(root.join("a.log"), "log")?;
+
+		let res =
+			get_status(repo_path, &StatusType::WorkingDir.into())?;
+
+		assert_eq!(res.len(), 1);
+		assert_eq!(res[0].path, ".gitignore");
+
+		let res = get_status(
+			repo_path,
+			&StatusOptions {
+				show_ignored: Some(ShowIgnoredFiles::default()),
+				..StatusType::WorkingDir.into()
+			},
+		)?;
+
+		assert_eq!(
+			res,
+			vec![
+				StatusItem {
+					path: String::from(".gitignore"),
+					old_path: None,
+					status: StatusItemType::New,
+					ignored: false,
+					mtime: None,
+					size: None,
+				},
+				StatusItem {
+					path: String::from("a.log"),
+					old_path: None,
+					status: StatusItemType::New,
+					ignored: true,
+					mtime: None,
+					size: None,
+				},
+			]
+		);
+
+		fs::write(root.join("b.log"), "log")?;
+
+		let options = StatusOptions {
+			show_ignored: Some(ShowIgnoredFiles {
+				max_entries: 1,
+				..ShowIgnoredFiles::default()
+			}),
+			..StatusType::WorkingDir.into()
+		};
+		let res = get_status(repo_path, &options)?;
+
+		assert_eq!(res.iter().filter(|item| item.ignored).count(), 1);
+
+		let (_, workdir) = get_status_both(repo_path, &options)?;
+
+		assert_eq!(workdir, res);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_quick_pass() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo.txt", CONTENT, "c1");
+
+		fs::write(root.join("foo.txt"), CONTENT_EDITED)?;
+		fs::write(root.join("new.txt"), CONTENT)?;
+
+		let paths = |pass| -> Result<Vec<String>> {
+			Ok(get_status(
+				repo_path,
+				&StatusOptions {
+					pass,
+					..StatusType::WorkingDir.into()
+				},
+			)?
+			.into_iter()
+			.map(|item| item.path)
+			.collect())
+		};
+
+		assert_eq!(paths(StatusPass::Quick)?, vec!["foo.txt"]);
+		assert_eq!(
+			paths(StatusPass::Full)?,
+			vec!["foo.txt", "new.txt"]
+		);
+
+		let (_, workdir) = get_status_both(
+			repo_path,
+			&StatusOptions {
+				pass: StatusPass::Quick,
+				..StatusOptions::default()
+			},
+		)?;
+		assert_eq!(
+			workdir
+				.into_iter()
+				.map(|item| item.path)
+				.collect::<Vec<_>>(),
+			vec!["foo.txt"]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_sorting() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		fs::create_dir_all(root.join("a"))?;
+		fs::create_dir_all(root.join("c"))?;
+		for file in ["B.txt", "a/x.txt", "a.txt", "c/y.txt"] {
+			fs::write(root.join(file), CONTENT)?;
+		}
+
+		let mut res =
+			get_status(repo_path, &StatusType::WorkingDir.into())?;
+
+		let paths = |items: &[StatusItem]| {
+			items
+				.iter()
+				.map(|item| item.path.clone())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			paths(&res),
+			vec!["a/x.txt", "a.txt", "B.txt", "c/y.txt"]
+		);
+
+		sort_status_items(&mut res, true);
+
+		assert_eq!(
+			paths(&res),
+			vec!["a/x.txt", "c/y.txt", "a.txt", "B.txt"]
+		);
+
+		assert_eq!(
+			group_by_dir(&res),
+			vec![
+				StatusGroup {
+					path: String::from("a"),
+					items: vec![0],
+				},
+				StatusGroup {
+					path: String::from("c"),
+					items: vec![1],
+				},
+				StatusGroup {
+					path: String::new(),
+					items: vec![2, 3],
+				},
+			]
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_stat() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "gone.txt", CONTENT, "c1");
+		fs::remove_file(root.join("gone.txt"))?;
+		fs::write(root.join("new.txt"), CONTENT)?;
+
+		let plain =
+			get_status(repo_path, &StatusType::WorkingDir.into())?;
+
+		assert!(plain.iter().all(|item| item.mtime.is_none()));
+
+		let mut res = get_status(
+			repo_path,
+			&StatusOptions {
+				stat: true,
+				..StatusType::WorkingDir.into()
+			},
+		)?;
+
+		// the stat does not take part in the comparison
+		assert_eq!(res, plain);
+
+		assert_eq!(res[0].path, "gone.txt");
+		assert_eq!(res[0].size, None);
+		assert_eq!(res[0].mtime, None);
+
+		assert_eq!(res[1].path, "new.txt");
+		assert_eq!(res[1].size, Some(CONTENT.len() as u64));
+		assert!(res[1].mtime.is_some());
+
+		sort_status_by(&mut res, SortBy::Mtime);
+
+		assert_eq!(res[0].path, "new.txt");
+		assert_eq!(res[1].path, "gone.txt");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_pathspec() -> Result<()> {
+		let (_td, repo) = repo_init()?;
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		fs::create_dir_all(root.join("a"))?;
+		fs::create_dir_all(root.join("b"))?;
+		write_commit_file(&repo, "a/tracked.txt", CONTENT, "c1");
+		write_commit_file(&repo, "b/tracked.txt", CONTENT, "c2");
+
+		for file in ["a/tracked.txt", "b/tracked.txt"] {
+			fs::write(root.join(file), CONTENT_EDITED)?;
+		}
+		for file in ["a/new.txt", "b/new.txt"] {
+			fs::write(root.join(file), CONTENT)?;
+		}
+		stage_add_file(repo_path, Path::new("b/new.txt"))?;
+
+		let paths = |status_type| -> Result<Vec<String>> {
+			Ok(get_status(
+				repo_path,
+				&StatusOptions {
+					pathspec: Some(vec![String::from("a")]),
+					..StatusOptions::from(status_type)
+				},
+			)?
+			.into_iter()
+			.map(|item| item.path)
+			.collect())
+		};
+
+		assert_eq!(
+			paths(StatusType::WorkingDir)?,
+			vec!["a/new.txt", "a/tracked.txt"]
+		);
+		assert!(paths(StatusType::Stage)?.is_empty());
+		assert_eq!(
+			get_status(repo_path, &StatusType::WorkingDir.into())?
+				.len(),
+			3
+		);
+
+		Ok(())
+	}
 }
@@ -157,7 +157,15 @@ pub fn submodule_parent_info(
 mod tests {
 	use super::get_submodules;
 	use crate::sync::{
-		submodules::submodule_parent_info, tests::repo_init, RepoPath,
+		commit,
+		diff::{get_diff, SubmoduleDiff},
+		stage_add_file,
+		status::{
+			get_status, StatusItemType, StatusType, SubmoduleChange,
+		},
+		submodules::submodule_parent_info,
+		tests::{repo_init, write_commit_file},
+		RepoPath,
 	};
 	use git2::Repository;
 	use pretty_assertions::assert_eq;
@@ -198,4 +206,67 @@ mod tests {
 
 		assert_eq!(&info.submodule_info.name, "foo/bar");
 	}
+
+	#[test]
+	fn test_new_commits() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let (sub_td, sub_repo) = repo_init().unwrap();
+		let old = write_commit_file(&sub_repo, "a.txt", "a", "c1");
+
+		{
+			let mut s = repo
+				.submodule(
+					sub_td.path().to_str().unwrap(),
+					Path::new("sub"),
+					false,
+				)
+				.unwrap();
+
+			let sub = s.clone(None).unwrap();
+			let mut config = sub.config().unwrap();
+			config.set_str("user.name", "name").unwrap();
+			config.set_str("user.email", "email").unwrap();
+
+			s.add_finalize().unwrap();
+		}
+		commit(repo_path, "add sub").unwrap();
+
+		let sub = Repository::open(root.join("sub")).unwrap();
+		let new = write_commit_file(&sub, "b.txt", "b", "c2");
+
+		let res =
+			get_status(repo_path, &StatusType::WorkingDir.into())
+				.unwrap();
+		assert_eq!(res.len(), 1);
+		assert_eq!(res[0].path, "sub");
+		assert_eq!(
+			res[0].status,
+			StatusItemType::Submodule(SubmoduleChange {
+				new_commits: true,
+				dirty: false,
+			})
+		);
+
+		let diff = get_diff(repo_path, "sub", false, None).unwrap();
+		assert!(diff.hunks.is_empty());
+		assert_eq!(
+			diff.submodule,
+			Some(SubmoduleDiff {
+				old: Some(old),
+				new: Some(new),
+				dirty: false,
+			})
+		);
+
+		stage_add_file(repo_path, Path::new("sub")).unwrap();
+
+		let mut index = repo.index().unwrap();
+		index.read(true).unwrap();
+		let entry = index.get_path(Path::new("sub"), 0).unwrap();
+		assert_eq!(entry.id, new.get_oid());
+	}
 }
@@ -1,8 +1,9 @@
 use super::{get_commits_info, CommitId, RepoPath};
 use crate::{
-	error::Result,
+	error::{Error, Result},
 	sync::{repository::repo, utils::bytes2string},
 };
+use git2::{ErrorCode, ObjectType, Repository};
 use scopetime::scope_time;
 use std::{
 	collections::{BTreeMap, HashMap, HashSet},
@@ -39,7 +40,7 @@ pub struct TagWithMetadata {
 	pub name: String,
 	///
 	pub author: String,
-	///
+	/// tagger date of annotated tags, commit date of lightweight ones
 	pub time: i64,
 	///
 	pub message: String,
@@ -114,7 +115,20 @@ pub fn get_tags(repo_path: &RepoPath) -> Result<Tags> {
 	Ok(res)
 }
 
-///
+/// tagger date of the annotated tag `name`
+fn tagger_time(repo: &Repository, name: &str) -> Option<i64> {
+	let tag = repo
+		.find_reference(&format!("refs/tags/{name}"))
+		.ok()?
+		.peel(ObjectType::Tag)
+		.ok()?
+		.into_tag()
+		.ok()?;
+
+	tag.tagger().map(|tagger| tagger.when().seconds())
+}
+
+/// all tags, newest first (by tag date, see `TagWithMetadata::time`)
 pub fn get_tags_with_metadata(
 	repo_path: &RepoPath,
 ) -> Result<Vec<TagWithMetadata>> {
@@ -153,6 +167,8 @@ pub fn get_tags_with_metadata(
 		.map(|commit_info| (commit_info.id, commit_info))
 		.collect();
 
+	let repo = repo(repo_path)?;
+
 	let mut tags: Vec<TagWithMetadata> = tags_with_commit_id
 		.into_iter()
 		.filter_map(|(tag, annotation, commit_id)| {
@@ -160,7 +176,8 @@ pub fn get_tags_with_metadata(
 				TagWithMetadata {
 					name: String::from(tag),
 					author: commit_info.author.clone(),
-					time: commit_info.time,
+					time: tagger_time(&repo, tag)
+						.unwrap_or(commit_info.time),
 					message: commit_info.message.clone(),
 					commit_id: *commit_id,
 					annotation: annotation.map(String::from),
@@ -169,12 +186,15 @@ pub fn get_tags_with_metadata(
 		})
 		.collect();
 
-	tags.sort_unstable_by(|a, b| b.time.cmp(&a.time));
+	tags.sort_unstable_by(|a, b| {
+		b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name))
+	});
 
 	Ok(tags)
 }
 
-///
+/// removes `refs/tags/<tag_name>`, fails with `Error::TagNotFound` if
+/// there is no such tag
 pub fn delete_tag(
 	repo_path: &RepoPath,
 	tag_name: &str,
@@ -182,7 +202,13 @@ pub fn delete_tag(
 	scope_time!("delete_tag");
 
 	let repo = repo(repo_path)?;
-	repo.tag_delete(tag_name)?;
+	repo.tag_delete(tag_name).map_err(|e| {
+		if e.code() == ErrorCode::NotFound {
+			Error::TagNotFound(tag_name.to_string())
+		} else {
+			e.into()
+		}
+	})?;
 
 	Ok(())
 }
@@ -190,8 +216,8 @@ pub fn delete_tag(
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::sync::tests::repo_init;
-	use git2::ObjectType;
+	use crate::sync::tests::{repo_init, write_commit_file};
+	use git2::{Signature, Time};
 
 	#[test]
 	fn test_smoke() {
@@ -251,4 +277,65 @@ mod tests {
 
 		assert_eq!(tags.len(), 0);
 	}
+
+	#[test]
+	fn test_order_and_delete() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		let c1 = write_commit_file(&repo, "a.txt", "a", "c1");
+		let c2 = write_commit_file(&repo, "a.txt", "b", "c2");
+		let commit_time = |id: CommitId| {
+			repo.find_commit(id.into()).unwrap().time().seconds()
+		};
+
+		let tag_at = |name: &str, id: CommitId, time: Option<i64>| {
+			let target = repo
+				.find_object(id.into(), Some(ObjectType::Commit))
+				.unwrap();
+			if let Some(time) = time {
+				let sig = Signature::new(
+					"name",
+					"email",
+					&Time::new(time, 0),
+				)
+				.unwrap();
+				repo.tag(name, &target, &sig, "msg", false).unwrap();
+			} else {
+				repo.tag_lightweight(name, &target, false).unwrap();
+			}
+		};
+
+		// annotated tag of the older commit tagged last
+		let newest = commit_time(c2) + 1000;
+		tag_at("old-annotated", c1, Some(newest));
+		tag_at("light", c2, None);
+		tag_at("older-annotated", c2, Some(commit_time(c1) - 1000));
+
+		let tags = get_tags_with_metadata(repo_path).unwrap();
+
+		assert_eq!(
+			tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+			vec!["old-annotated", "light", "older-annotated"]
+		);
+		assert_eq!(tags[0].time, newest);
+		assert_eq!(tags[0].commit_id, c1);
+		assert_eq!(tags[0].annotation.as_deref(), Some("msg"));
+		assert_eq!(tags[1].time, commit_time(c2));
+		assert_eq!(tags[1].annotation, None);
+
+		delete_tag(repo_path, "light").unwrap();
+		assert!(repo.find_reference("refs/tags/light").is_err());
+		assert_eq!(
+			get_tags_with_metadata(repo_path).unwrap().len(),
+			2
+		);
+
+		assert!(matches!(
+			delete_tag(repo_path, "light"),
+			Err(Error::TagNotFound(name)) if name == "light"
+		));
+	}
 }
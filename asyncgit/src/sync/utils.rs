@@ -7,9 +7,13 @@ use crate::{
 	error::{Error, Result},
 	sync::config::untracked_files_config_repo,
 };
-use git2::{IndexAddOption, Repository, RepositoryOpenFlags};
+use git2::{
+	Index, IndexAddOption, Oid, Repository, RepositoryOpenFlags,
+	RepositoryState,
+};
 use scopetime::scope_time;
 use std::{
+	collections::HashSet,
 	fs::File,
 	io::Write,
 	path::{Path, PathBuf},
@@ -86,34 +90,96 @@ pub fn get_head_repo(repo: &Repository) -> Result<CommitId> {
 	head.map_or(Err(Error::NoHead), |head_id| Ok(head_id.into()))
 }
 
-/// add a file diff from workingdir to stage (will not add removed files see `stage_addremoved`)
+/// fails with `Error::UncommittedChanges` unless the index matches the
+/// tree of HEAD, so that committing the index after applying a commit
+/// does not pick up unrelated staged changes
+pub(crate) fn ensure_index_matches_head(
+	repo: &Repository,
+) -> Result<()> {
+	let mut index = repo.index()?;
+	if index.has_conflicts() {
+		return Err(Error::UncommittedChanges);
+	}
+
+	let head_tree = repo.head()?.peel_to_tree()?.id();
+	if index.write_tree()? == head_tree {
+		Ok(())
+	} else {
+		Err(Error::UncommittedChanges)
+	}
+}
+
+/// add a file diff from workingdir to stage, a file removed from the
+/// workingdir is removed from the stage (like `stage_addremoved`).
+/// an untracked folder is staged recursively, skipping ignored files.
+/// fails with `Error::NothingToStage` if the file is in neither,
+/// returns the number of staged files
 pub fn stage_add_file(
 	repo_path: &RepoPath,
 	path: &Path,
-) -> Result<()> {
+) -> Result<usize> {
 	scope_time!("stage_add_file");
 
 	let repo = repo(repo_path)?;
 
 	let mut index = repo.index()?;
 
+	let Ok(meta) = work_dir(&repo)?.join(path).symlink_metadata()
+	else {
+		if index.get_path(path, 0).is_none() {
+			return Err(Error::NothingToStage(
+				path.to_string_lossy().to_string(),
+			));
+		}
+
+		index.remove_path(path)?;
+		index.write()?;
+
+		return Ok(1);
+	};
+
+	// submodules are folders too but staged by their commit
+	if meta.is_dir() && index.get_path(path, 0).is_none() {
+		let before = index_entries(&index);
+
+		index.add_all(
+			[path],
+			IndexAddOption::DEFAULT
+				| IndexAddOption::DISABLE_PATHSPEC_MATCH,
+			None,
+		)?;
+		index.update_all([path], None)?;
+		index.write()?;
+
+		return Ok(changed_entries(&before, &index));
+	}
+
+	// e.g. a file turned into a symlink must not keep the mode
+	// of its old index entry
+	if repo.status_file(path).is_ok_and(|s| s.is_wt_typechange()) {
+		index.remove_path(path)?;
+	}
+
 	index.add_path(path)?;
 	index.write()?;
 
-	Ok(())
+	Ok(1)
 }
 
-/// like `stage_add_file` but uses a pattern to match/glob multiple files/folders
+/// like `stage_add_file` but uses a pattern to match/glob multiple files/folders.
+/// stages deletions too (like `git add -A`) and resolves conflicts of
+/// matching files, returns the number of files whose index entry changed
 pub fn stage_add_all(
 	repo_path: &RepoPath,
 	pattern: &str,
 	stage_untracked: Option<ShowUntrackedFilesConfig>,
-) -> Result<()> {
+) -> Result<usize> {
 	scope_time!("stage_add_all");
 
 	let repo = repo(repo_path)?;
 
 	let mut index = repo.index()?;
+	let before = index_entries(&index);
 
 	let stage_untracked = if let Some(config) = stage_untracked {
 		config
@@ -127,28 +193,74 @@ pub fn stage_add_all(
 			IndexAddOption::DEFAULT,
 			None,
 		)?;
-	} else {
-		index.update_all(vec![pattern], None)?;
 	}
+	index.update_all(vec![pattern], None)?;
 
 	index.write()?;
 
-	Ok(())
+	Ok(changed_entries(&before, &index))
+}
+
+type IndexEntries = HashSet<(Vec<u8>, Oid, u32, u16)>;
+
+/// path, id, mode and stage of every entry in `index`
+pub(crate) fn index_entries(index: &Index) -> IndexEntries {
+	index
+		.iter()
+		.map(|entry| {
+			(
+				entry.path,
+				entry.id,
+				entry.mode,
+				(entry.flags >> 12) & 0x3,
+			)
+		})
+		.collect()
+}
+
+/// number of distinct paths whose entries differ between `before`
+/// and `index`
+pub(crate) fn changed_entries(
+	before: &IndexEntries,
+	index: &Index,
+) -> usize {
+	let after = index_entries(index);
+
+	before
+		.symmetric_difference(&after)
+		.map(|entry| entry.0.as_slice())
+		.collect::<HashSet<_>>()
+		.len()
 }
 
-/// Undo last commit in repo
-pub fn undo_last_commit(repo_path: &RepoPath) -> Result<()> {
+/// Undo last commit in repo: soft resets HEAD to its first parent,
+/// keeping the changes staged. returns the message of the undone
+/// commit, fails with `Error::NoParent` on the root commit
+pub fn undo_last_commit(repo_path: &RepoPath) -> Result<String> {
+	scope_time!("undo_last_commit");
+
 	let repo = repo(repo_path)?;
-	let previous_commit = repo.revparse_single("HEAD~")?;
+
+	if repo.state() != RepositoryState::Clean {
+		return Err(Error::Generic(String::from(
+			"cannot undo commit while repo is not in a clean state",
+		)));
+	}
+
+	let commit = repo.find_commit(get_head_repo(&repo)?.into())?;
+	if commit.parent_count() == 0 {
+		return Err(Error::NoParent);
+	}
+	let previous_commit = commit.parent(0)?;
 
 	Repository::reset(
 		&repo,
-		&previous_commit,
+		previous_commit.as_object(),
 		git2::ResetType::Soft,
 		None,
 	)?;
 
-	Ok(())
+	Ok(String::from_utf8_lossy(commit.message_bytes()).to_string())
 }
 
 /// stage a removed file
@@ -176,14 +288,14 @@ pub(crate) fn bytes2string(bytes: &[u8]) -> Result<String> {
 pub(crate) fn repo_write_file(
 	repo: &Repository,
 	file: &str,
-	content: &str,
+	content: impl AsRef<[u8]>,
 ) -> Result<()> {
 	let dir = work_dir(repo)?.join(file);
 	let file_path = dir.to_str().ok_or_else(|| {
 		Error::Generic(String::from("invalid file path"))
 	})?;
 	let mut file = File::create(file_path)?;
-	file.write_all(content.as_bytes())?;
+	file.write_all(content.as_ref())?;
 	Ok(())
 }
 
@@ -235,6 +347,43 @@ mod tests {
 		path::Path,
 	};
 
+	#[cfg(unix)]
+	#[test]
+	fn test_stage_typechange() {
+		use crate::sync::status::StatusItemType;
+		use std::os::unix::fs::symlink;
+
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "foo", "a\n", "c1");
+		remove_file(root.join("foo")).unwrap();
+		symlink("bar", root.join("foo")).unwrap();
+
+		let status =
+			|s: StatusType| get_status(repo_path, &s.into()).unwrap();
+
+		assert_eq!(
+			status(StatusType::WorkingDir)[0].status,
+			StatusItemType::Typechange
+		);
+
+		stage_add_file(repo_path, Path::new("foo")).unwrap();
+
+		assert!(status(StatusType::WorkingDir).is_empty());
+		assert_eq!(
+			status(StatusType::Stage)[0].status,
+			StatusItemType::Typechange
+		);
+
+		let mut index = repo.index().unwrap();
+		index.read(true).unwrap();
+		let entry = index.get_path(Path::new("foo"), 0).unwrap();
+		assert_eq!(entry.mode, 0o120_000);
+	}
+
 	#[test]
 	fn test_stage_add_smoke() {
 		let file_path = Path::new("foo");
@@ -278,7 +427,7 @@ mod tests {
 			&root.as_os_str().to_str().unwrap().into();
 
 		let status_count = |s: StatusType| -> usize {
-			get_status(repo_path, s, None).unwrap().len()
+			get_status(repo_path, &s.into()).unwrap().len()
 		};
 
 		fs::create_dir_all(root.join("a/d"))?;
@@ -307,7 +456,10 @@ mod tests {
 			&root.as_os_str().to_str().unwrap().into();
 
 		// expect to fail
-		assert!(undo_last_commit(repo_path).is_err());
+		assert!(matches!(
+			undo_last_commit(repo_path),
+			Err(Error::NoParent)
+		));
 	}
 
 	#[test]
@@ -322,7 +474,13 @@ mod tests {
 			write_commit_file(&repo, "test.txt", "content1", "c1");
 		let _c2 =
 			write_commit_file(&repo, "test.txt", "content2", "c2");
-		assert!(undo_last_commit(repo_path).is_ok());
+		assert_eq!(undo_last_commit(repo_path).unwrap(), "c2");
+
+		// worktree is untouched
+		assert_eq!(
+			fs::read_to_string(root.join("test.txt")).unwrap(),
+			"content2"
+		);
 
 		// Make sure that HEAD points to c1
 		assert_eq!(c1, get_head_repo(&repo).unwrap());
@@ -373,7 +531,7 @@ mod tests {
 			&root.as_os_str().to_str().unwrap().into();
 
 		let status_count = |s: StatusType| -> usize {
-			get_status(repo_path, s, None).unwrap().len()
+			get_status(repo_path, &s.into()).unwrap().len()
 		};
 
 		let full_path = &root.join(file_path);
@@ -399,6 +557,39 @@ mod tests {
 		assert_eq!(status_count(StatusType::Stage), 1);
 	}
 
+	#[test]
+	fn test_stage_add_deleted_file() {
+		let (_td, repo) = repo_init().unwrap();
+		let root = repo.path().parent().unwrap();
+		let repo_path: &RepoPath =
+			&root.as_os_str().to_str().unwrap().into();
+
+		write_commit_file(&repo, "bar.txt", "test", "c1");
+		remove_file(root.join("bar.txt")).unwrap();
+
+		stage_add_file(repo_path, Path::new("bar.txt")).unwrap();
+
+		let res =
+			get_status(repo_path, &StatusType::Stage.into()).unwrap();
+		assert_eq!(res.len(), 1);
+		assert_eq!(res[0].path, "bar.txt");
+		assert_eq!(
+			res[0].status,
+			crate::sync::status::StatusItemType::Deleted
+		);
+		assert!(get_status(
+			repo_path,
+			&StatusType::WorkingDir.into()
+		)
+		.unwrap()
+		.is_empty());
+
+		assert!(matches!(
+			stage_add_file(repo_path, Path::new("missing.txt")),
+			Err(Error::NothingToStage(_))
+		));
+	}
+
 	// see https://github.com/extrawurst/gitui/issues/108
 	#[test]
 	fn test_staging_sub_git_folder() -> Result<()> {
@@ -408,7 +599,7 @@ mod tests {
 			&root.as_os_str().to_str().unwrap().into();
 
 		let status_count = |s: StatusType| -> usize {
-			get_status(repo_path, s, None).unwrap().len()
+			get_status(repo_path, &s.into()).unwrap().len()
 		};
 
 		let sub = &root.join("sub");
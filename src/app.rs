@@ -12,8 +12,8 @@ use crate::{
 	popups::{
 		AppOption, BlameFilePopup, BranchListPopup, CommitPopup,
 		CompareCommitsPopup, ConfirmPopup, CreateBranchPopup,
-		CreateRemotePopup, ExternalEditorPopup, FetchPopup,
-		FileRevlogPopup, FuzzyFindPopup, HelpPopup,
+		CreateRemotePopup, CredPromptPopup, ExternalEditorPopup,
+		FetchPopup, FileRevlogPopup, FuzzyFindPopup, HelpPopup,
 		InspectCommitPopup, LogSearchPopupPopup, MsgPopup,
 		OptionsPopup, PullPopup, PushPopup, PushTagsPopup,
 		RemoteListPopup, RenameBranchPopup, RenameRemotePopup,
@@ -36,6 +36,7 @@ use anyhow::{bail, Result};
 use asyncgit::{
 	sync::{
 		self,
+		cred::CredentialRequest,
 		utils::{repo_work_dir, undo_last_commit},
 		RepoPath, RepoPathRef,
 	},
@@ -86,6 +87,7 @@ pub struct App {
 	push_tags_popup: PushTagsPopup,
 	pull_popup: PullPopup,
 	fetch_popup: FetchPopup,
+	cred_prompt_popup: CredPromptPopup,
 	tag_commit_popup: TagCommitPopup,
 	create_branch_popup: CreateBranchPopup,
 	create_remote_popup: CreateRemotePopup,
@@ -193,6 +195,7 @@ impl App {
 			reset_popup: ResetPopup::new(&env),
 			pull_popup: PullPopup::new(&env),
 			fetch_popup: FetchPopup::new(&env),
+			cred_prompt_popup: CredPromptPopup::new(&env),
 			tag_commit_popup: TagCommitPopup::new(&env),
 			create_branch_popup: CreateBranchPopup::new(&env),
 			create_remote_popup: CreateRemotePopup::new(&env),
@@ -384,8 +387,21 @@ impl App {
 	pub fn update(&mut self) -> Result<()> {
 		log::trace!("update");
 
-		self.commit_popup.update();
 		self.status_tab.update()?;
+		self.update_tabs()
+	}
+
+	/// like `update` but lets the status tab take its cheaper
+	/// polling path
+	pub fn update_poll(&mut self) -> Result<()> {
+		log::trace!("update_poll");
+
+		self.status_tab.poll()?;
+		self.update_tabs()
+	}
+
+	fn update_tabs(&mut self) -> Result<()> {
+		self.commit_popup.update();
 		self.revlog.update()?;
 		self.files_tab.update()?;
 		self.stashing_tab.update()?;
@@ -397,6 +413,17 @@ impl App {
 		Ok(())
 	}
 
+	/// shows the prompt a remote operation is waiting on
+	pub fn credential_request(
+		&mut self,
+		request: CredentialRequest,
+	) -> Result<()> {
+		self.cred_prompt_popup.open(request)?;
+		self.update_commands();
+
+		Ok(())
+	}
+
 	///
 	pub fn update_async(
 		&mut self,
@@ -480,6 +507,7 @@ impl App {
 			fuzzy_find_popup,
 			msg_popup,
 			confirm_popup,
+			cred_prompt_popup,
 			commit_popup,
 			blame_file_popup,
 			file_revlog_popup,
@@ -542,6 +570,7 @@ impl App {
 			push_tags_popup,
 			pull_popup,
 			fetch_popup,
+			cred_prompt_popup,
 			options_popup,
 			confirm_popup,
 			msg_popup
@@ -826,6 +855,9 @@ impl App {
 			InternalEvent::StatusLastFileMoved => {
 				self.status_tab.last_file_moved()?;
 			}
+			InternalEvent::ClearDiffCache => {
+				self.status_tab.clear_diff_cache()?;
+			}
 			InternalEvent::OpenFuzzyFinder(contents, target) => {
 				self.fuzzy_find_popup.open(contents, target)?;
 				flags
@@ -945,9 +977,35 @@ impl App {
 				)?;
 			}
 			Action::DeleteLocalBranch(branch_ref) => {
+				match sync::delete_branch(
+					&self.repo.borrow(),
+					&branch_ref,
+					false,
+				) {
+					Err(asyncgit::Error::BranchNotMerged(_)) => {
+						self.queue.push(
+							InternalEvent::ConfirmAction(
+								Action::ForceDeleteLocalBranch(
+									branch_ref,
+								),
+							),
+						);
+					}
+					Err(e) => {
+						self.queue.push(InternalEvent::ShowErrorMsg(
+							e.to_string(),
+						));
+					}
+					Ok(()) => (),
+				}
+
+				self.select_branch_popup.update_branches()?;
+			}
+			Action::ForceDeleteLocalBranch(branch_ref) => {
 				if let Err(e) = sync::delete_branch(
 					&self.repo.borrow(),
 					&branch_ref,
+					true,
 				) {
 					self.queue.push(InternalEvent::ShowErrorMsg(
 						e.to_string(),
@@ -984,18 +1042,20 @@ impl App {
 			Action::PullMerge { rebase, .. } => {
 				self.pull_popup.try_conflict_free_merge(rebase);
 			}
-			Action::AbortRevert | Action::AbortMerge => {
+			Action::AbortRevert => {
 				self.status_tab.revert_pending_state();
 			}
+			Action::AbortMerge => {
+				self.status_tab.abort_merge();
+			}
 			Action::AbortRebase => {
 				self.status_tab.abort_rebase();
 			}
 			Action::UndoCommit => {
-				try_or_popup!(
-					self,
-					"undo commit failed:",
-					undo_last_commit(&self.repo.borrow())
-				);
+				let res = undo_last_commit(&self.repo.borrow())
+					.map(|msg| self.commit_popup.set_msg(msg));
+				try_or_popup!(self, "undo commit failed:", res);
+				self.status_tab.clear_diff_cache()?;
 			}
 		};
 
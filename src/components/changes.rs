@@ -13,7 +13,7 @@ use crate::{
 };
 use anyhow::Result;
 use asyncgit::{
-	sync::{self, RepoPathRef},
+	sync::{self, ConflictSide, RepoPathRef},
 	StatusItem, StatusItemType,
 };
 use crossterm::event::Event;
@@ -88,11 +88,20 @@ impl ChangesComponent {
 								path,
 							)?;
 						}
-						_ => sync::stage_add_file(
-							&self.repo.borrow(),
-							path,
-						)?,
+						_ => {
+							sync::stage_add_file(
+								&self.repo.borrow(),
+								path,
+							)?;
+						}
 					};
+
+					if let Some(old_path) = &i.old_path {
+						sync::stage_addremoved(
+							&self.repo.borrow(),
+							Path::new(old_path),
+						)?;
+					}
 				} else {
 					let config =
 						self.options.borrow().status_show_untracked();
@@ -120,6 +129,15 @@ impl ChangesComponent {
 				// this is a staged entry, so lets unstage it
 				let path = tree_item.info.full_path.as_str();
 				sync::reset_stage(&self.repo.borrow(), path)?;
+
+				if let FileTreeItemKind::File(i) = tree_item.kind {
+					if let Some(old_path) = &i.old_path {
+						sync::reset_stage(
+							&self.repo.borrow(),
+							old_path,
+						)?;
+					}
+				}
 			}
 
 			return Ok(true);
@@ -139,7 +157,7 @@ impl ChangesComponent {
 	}
 
 	fn stage_remove_all(&self) -> Result<()> {
-		sync::reset_stage(&self.repo.borrow(), "*")?;
+		sync::reset_stage_all(&self.repo.borrow())?;
 
 		self.queue.push(InternalEvent::Update(NeedsUpdate::ALL));
 
@@ -149,8 +167,17 @@ impl ChangesComponent {
 	fn dispatch_reset_workdir(&self) -> bool {
 		if let Some(tree_item) = self.selection() {
 			self.queue.push(InternalEvent::ConfirmAction(
-				Action::Reset(ResetItem {
-					path: tree_item.info.full_path,
+				Action::Reset(match tree_item.kind {
+					FileTreeItemKind::File(i) => ResetItem {
+						untracked: i.status == StatusItemType::New,
+						old_path: i.old_path,
+						path: tree_item.info.full_path,
+					},
+					FileTreeItemKind::Path(_) => ResetItem {
+						untracked: false,
+						old_path: None,
+						path: tree_item.info.full_path,
+					},
 				}),
 			));
 
@@ -159,6 +186,29 @@ impl ChangesComponent {
 		false
 	}
 
+	/// path of the selected file if it is conflicted
+	fn selected_conflict(&self) -> Option<String> {
+		let tree_item = self.selection()?;
+		match tree_item.kind {
+			FileTreeItemKind::File(i)
+				if self.is_working_dir
+					&& i.status == StatusItemType::Conflicted =>
+			{
+				Some(tree_item.info.full_path)
+			}
+			_ => None,
+		}
+	}
+
+	fn resolve_conflict(&self, side: ConflictSide) -> Result<()> {
+		if let Some(path) = self.selected_conflict() {
+			sync::resolve_conflict(&self.repo.borrow(), &path, side)?;
+			self.queue.push(InternalEvent::Update(NeedsUpdate::ALL));
+		}
+
+		Ok(())
+	}
+
 	fn add_to_ignore(&self) -> bool {
 		if let Some(tree_item) = self.selection() {
 			if let Err(e) = sync::add_to_ignore(
@@ -222,6 +272,19 @@ impl Component for ChangesComponent {
 				true,
 				some_selection && self.focused(),
 			));
+
+			let conflict_selected =
+				self.selected_conflict().is_some();
+			out.push(CommandInfo::new(
+				strings::commands::take_ours(&self.key_config),
+				true,
+				conflict_selected && self.focused(),
+			));
+			out.push(CommandInfo::new(
+				strings::commands::take_theirs(&self.key_config),
+				true,
+				conflict_selected && self.focused(),
+			));
 		} else {
 			out.push(CommandInfo::new(
 				strings::commands::unstage_item(&self.key_config),
@@ -271,7 +334,11 @@ impl Component for ChangesComponent {
 							self.index_add_all()
 						);
 					} else {
-						self.stage_remove_all()?;
+						try_or_popup!(
+							self,
+							"unstage all error:",
+							self.stage_remove_all()
+						);
 					}
 					self.queue
 						.push(InternalEvent::StatusLastFileMoved);
@@ -289,6 +356,28 @@ impl Component for ChangesComponent {
 					&& !self.is_empty()
 				{
 					Ok(self.add_to_ignore().into())
+				} else if key_match(
+					e,
+					self.key_config.keys.status_take_ours,
+				) && self.selected_conflict().is_some()
+				{
+					try_or_popup!(
+						self,
+						"resolve conflict error:",
+						self.resolve_conflict(ConflictSide::Ours)
+					);
+					Ok(EventState::Consumed)
+				} else if key_match(
+					e,
+					self.key_config.keys.status_take_theirs,
+				) && self.selected_conflict().is_some()
+				{
+					try_or_popup!(
+						self,
+						"resolve conflict error:",
+						self.resolve_conflict(ConflictSide::Theirs)
+					);
+					Ok(EventState::Consumed)
 				} else {
 					Ok(EventState::NotConsumed)
 				};
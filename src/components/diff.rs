@@ -17,7 +17,11 @@ use crate::{
 use anyhow::Result;
 use asyncgit::{
 	hash,
-	sync::{self, diff::DiffLinePosition, RepoPathRef},
+	sync::{
+		self,
+		diff::{file_kind, DiffLinePosition},
+		CommitId, RepoPathRef,
+	},
 	DiffLine, DiffLineType, FileDiff,
 };
 use bytesize::ByteSize;
@@ -29,7 +33,7 @@ use ratatui::{
 	widgets::{Block, Borders, Paragraph},
 	Frame,
 };
-use std::{borrow::Cow, cell::Cell, cmp, path::Path};
+use std::{borrow::Cow, cell::Cell, cmp};
 
 #[derive(Default)]
 struct Current {
@@ -326,7 +330,7 @@ impl DiffComponent {
 
 	fn get_text(&self, width: u16, height: u16) -> Vec<Line> {
 		if let Some(diff) = &self.diff {
-			return if diff.hunks.is_empty() {
+			return if diff.binary || diff.hunks.is_empty() {
 				self.get_text_binary(diff)
 			} else {
 				let mut res: Vec<Line> = Vec::new();
@@ -392,8 +396,57 @@ impl DiffComponent {
 		let delta_byte_size =
 			ByteSize::b(diff.size_delta.unsigned_abs());
 		let sign = if is_positive { "+" } else { "-" };
+
+		if let Some(submodule) = diff.submodule {
+			let id = |id: Option<CommitId>| {
+				id.map_or_else(
+					|| String::from("none"),
+					|id| id.get_short_string(),
+				)
+			};
+
+			return vec![Line::from(vec![
+				Span::raw(Cow::from("Subproject commit ")),
+				Span::styled(
+					Cow::from(id(submodule.old)),
+					self.theme.text(false, false),
+				),
+				Span::raw(Cow::from(" \u{2192} ")),
+				Span::styled(
+					Cow::from(id(submodule.new)),
+					self.theme.text(false, false),
+				),
+				Span::raw(Cow::from(if submodule.dirty {
+					" (+dirty)"
+				} else {
+					""
+				})),
+			])];
+		}
+
+		if let Some((old_mode, new_mode)) = diff.mode_change {
+			if !diff.binary {
+				return vec![Line::from(vec![
+					Span::raw(Cow::from("mode: ")),
+					Span::styled(
+						Cow::from(format!("{old_mode:o}")),
+						self.theme.text(false, false),
+					),
+					Span::raw(Cow::from(" -> ")),
+					Span::styled(
+						Cow::from(format!("{new_mode:o}")),
+						self.theme.text(false, false),
+					),
+				])];
+			}
+		}
+
 		vec![Line::from(vec![
-			Span::raw(Cow::from("size: ")),
+			Span::raw(Cow::from(if diff.too_large {
+				"too large to preview, size: "
+			} else {
+				"size: "
+			})),
 			Span::styled(
 				Cow::from(format!("{}", ByteSize::b(diff.sizes.0))),
 				self.theme.text(false, false),
@@ -514,20 +567,13 @@ impl DiffComponent {
 	fn stage_hunk(&self) -> Result<()> {
 		if let Some(diff) = &self.diff {
 			if let Some(hunk) = self.selected_hunk {
-				if diff.untracked {
-					sync::stage_add_file(
-						&self.repo.borrow(),
-						Path::new(&self.current.path),
-					)?;
-				} else {
-					let hash = diff.hunks[hunk].header_hash;
-					sync::stage_hunk(
-						&self.repo.borrow(),
-						&self.current.path,
-						hash,
-						Some(self.options.borrow().diff_options()),
-					)?;
-				}
+				let hash = diff.hunks[hunk].header_hash;
+				sync::stage_hunk(
+					&self.repo.borrow(),
+					&self.current.path,
+					hash,
+					Some(self.options.borrow().diff_options()),
+				)?;
 
 				self.queue_update();
 			}
@@ -615,6 +661,14 @@ impl DiffComponent {
 		self.queue.push(InternalEvent::ConfirmAction(Action::Reset(
 			ResetItem {
 				path: self.current.path.clone(),
+				old_path: self
+					.diff
+					.as_ref()
+					.and_then(|diff| diff.old_path.clone()),
+				untracked: self
+					.diff
+					.as_ref()
+					.is_some_and(|diff| diff.untracked),
 			},
 		)));
 	}
@@ -676,6 +730,12 @@ impl DiffComponent {
 	const fn is_stage(&self) -> bool {
 		self.current.is_stage
 	}
+
+	/// hunks and lines can not be staged while the file is conflicted
+	fn can_modify(&self) -> bool {
+		!self.is_immutable
+			&& !self.diff.as_ref().is_some_and(|diff| diff.conflicted)
+	}
 }
 
 impl DrawableComponent for DiffComponent {
@@ -699,10 +759,41 @@ impl DrawableComponent for DiffComponent {
 			current_width.into(),
 		);
 
+		let path = match self.diff.as_ref() {
+			Some(FileDiff {
+				old_path: Some(old_path),
+				..
+			}) => format!("{old_path} \u{2192} {}", self.current.path),
+			Some(FileDiff {
+				copied_from: Some(source),
+				..
+			}) => format!(
+				"{source} \u{2192} {} (copy)",
+				self.current.path
+			),
+			_ => self.current.path.clone(),
+		};
+		let symlink = match self.diff.as_ref() {
+			Some(FileDiff {
+				typechange: Some((old_mode, new_mode)),
+				..
+			}) => format!(
+				" ({} \u{2192} {})",
+				file_kind(*old_mode),
+				file_kind(*new_mode)
+			),
+			Some(FileDiff {
+				mode_change: Some((old_mode, new_mode)),
+				..
+			}) => format!(" (mode {old_mode:o} \u{2192} {new_mode:o})"),
+			Some(diff) if diff.symlink => String::from(" (symlink)"),
+			_ => String::new(),
+		};
 		let title = format!(
-			"{}{}",
+			"{}{}{}",
 			strings::title_diff(&self.key_config),
-			self.current.path
+			path,
+			symlink
 		);
 
 		let txt = if self.pending {
@@ -769,7 +860,7 @@ impl Component for DiffComponent {
 			.hidden(),
 		);
 
-		if !self.is_immutable {
+		if self.can_modify() {
 			out.push(CommandInfo::new(
 				strings::commands::diff_hunk_remove(&self.key_config),
 				self.selected_hunk.is_some(),
@@ -879,7 +970,7 @@ impl Component for DiffComponent {
 				} else if key_match(
 					e,
 					self.key_config.keys.stage_unstage_item,
-				) && !self.is_immutable
+				) && self.can_modify()
 				{
 					try_or_popup!(
 						self,
@@ -891,7 +982,7 @@ impl Component for DiffComponent {
 				} else if key_match(
 					e,
 					self.key_config.keys.status_reset_item,
-				) && !self.is_immutable
+				) && self.can_modify()
 					&& !self.is_stage()
 				{
 					if let Some(diff) = &self.diff {
@@ -905,14 +996,14 @@ impl Component for DiffComponent {
 				} else if key_match(
 					e,
 					self.key_config.keys.diff_stage_lines,
-				) && !self.is_immutable
+				) && self.can_modify()
 				{
 					self.stage_lines();
 					Ok(EventState::Consumed)
 				} else if key_match(
 					e,
 					self.key_config.keys.diff_reset_lines,
-				) && !self.is_immutable
+				) && self.can_modify()
 					&& !self.is_stage()
 				{
 					if let Some(diff) = &self.diff {
@@ -956,6 +1047,7 @@ mod tests {
 			content: "".into(),
 			line_type: DiffLineType::Add,
 			position: Default::default(),
+			..Default::default()
 		};
 
 		{
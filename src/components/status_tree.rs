@@ -146,6 +146,7 @@ impl StatusTreeComponent {
 			StatusItemType::Renamed => 'R',
 			StatusItemType::Typechange => ' ',
 			StatusItemType::Conflicted => '!',
+			StatusItemType::Submodule(_) => 'S',
 		}
 	}
 
@@ -556,7 +557,11 @@ mod tests {
 			.iter()
 			.map(|a| StatusItem {
 				path: String::from(*a),
+				old_path: None,
 				status: StatusItemType::Modified,
+				ignored: false,
+				mtime: None,
+				size: None,
 			})
 			.collect::<Vec<_>>()
 	}
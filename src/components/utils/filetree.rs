@@ -267,7 +267,11 @@ mod tests {
 			.iter()
 			.map(|a| StatusItem {
 				path: String::from(*a),
+				old_path: None,
 				status: StatusItemType::Modified,
+				ignored: false,
+				mtime: None,
+				size: None,
 			})
 			.collect::<Vec<_>>()
 	}
@@ -73,6 +73,8 @@ pub struct KeysList {
 	pub status_stage_all: GituiKeyEvent,
 	pub status_reset_item: GituiKeyEvent,
 	pub status_ignore_file: GituiKeyEvent,
+	pub status_take_ours: GituiKeyEvent,
+	pub status_take_theirs: GituiKeyEvent,
 	pub diff_stage_lines: GituiKeyEvent,
 	pub diff_reset_lines: GituiKeyEvent,
 	pub stashing_save: GituiKeyEvent,
@@ -87,6 +89,7 @@ pub struct KeysList {
 	pub log_checkout_commit: GituiKeyEvent,
 	pub log_reset_commit: GituiKeyEvent,
 	pub log_reword_commit: GituiKeyEvent,
+	pub log_cherry_pick: GituiKeyEvent,
 	pub log_find: GituiKeyEvent,
 	pub find_commit_sha: GituiKeyEvent,
 	pub commit_amend: GituiKeyEvent,
@@ -171,6 +174,8 @@ impl Default for KeysList {
 			status_reset_item: GituiKeyEvent::new(KeyCode::Char('D'),  KeyModifiers::SHIFT),
 			diff_reset_lines: GituiKeyEvent::new(KeyCode::Char('d'),  KeyModifiers::empty()),
 			status_ignore_file: GituiKeyEvent::new(KeyCode::Char('i'),  KeyModifiers::empty()),
+			status_take_ours: GituiKeyEvent::new(KeyCode::Char('O'),  KeyModifiers::SHIFT),
+			status_take_theirs: GituiKeyEvent::new(KeyCode::Char('T'),  KeyModifiers::SHIFT),
 			diff_stage_lines: GituiKeyEvent::new(KeyCode::Char('s'),  KeyModifiers::empty()),
 			stashing_save: GituiKeyEvent::new(KeyCode::Char('s'),  KeyModifiers::empty()),
 			stashing_toggle_untracked: GituiKeyEvent::new(KeyCode::Char('u'),  KeyModifiers::empty()),
@@ -184,6 +189,7 @@ impl Default for KeysList {
 			log_checkout_commit: GituiKeyEvent { code: KeyCode::Char('S'), modifiers: KeyModifiers::SHIFT },
 			log_reset_commit: GituiKeyEvent { code: KeyCode::Char('R'), modifiers: KeyModifiers::SHIFT },
 			log_reword_commit: GituiKeyEvent { code: KeyCode::Char('r'), modifiers: KeyModifiers::empty() },
+			log_cherry_pick: GituiKeyEvent { code: KeyCode::Char('Y'), modifiers: KeyModifiers::SHIFT },
 			log_find: GituiKeyEvent { code: KeyCode::Char('f'), modifiers: KeyModifiers::empty() },
 			find_commit_sha: GituiKeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL),
 			commit_amend: GituiKeyEvent::new(KeyCode::Char('a'),  KeyModifiers::CONTROL),
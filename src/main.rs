@@ -50,7 +50,11 @@ use crate::{app::App, args::process_cmdline};
 use anyhow::{bail, Result};
 use app::QuitState;
 use asyncgit::{
-	sync::{utils::repo_work_dir, RepoPath},
+	sync::{
+		cred::{set_credential_prompt, CredentialRequest},
+		utils::repo_work_dir,
+		RepoPath,
+	},
 	AsyncGitNotification,
 };
 use backtrace::Backtrace;
@@ -90,6 +94,7 @@ pub enum QueueEvent {
 	SpinnerUpdate,
 	AsyncEvent(AsyncNotification),
 	InputEvent(InputEvent),
+	CredentialRequest(CredentialRequest),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -185,6 +190,8 @@ fn run_app(
 ) -> Result<QuitState, anyhow::Error> {
 	let (tx_git, rx_git) = unbounded();
 	let (tx_app, rx_app) = unbounded();
+	let (tx_cred, rx_cred) = unbounded();
+	set_credential_prompt(Some(tx_cred));
 
 	let rx_input = input.receiver();
 
@@ -226,6 +233,7 @@ fn run_app(
 				&rx_ticker,
 				&rx_watcher,
 				&spinner_ticker,
+				&rx_cred,
 			)?
 		};
 
@@ -249,7 +257,10 @@ fn run_app(
 					}
 					app.event(ev)?;
 				}
-				QueueEvent::Tick | QueueEvent::Notify => {
+				QueueEvent::Tick => {
+					app.update_poll()?;
+				}
+				QueueEvent::Notify => {
 					app.update()?;
 				}
 				QueueEvent::AsyncEvent(ev) => {
@@ -262,6 +273,9 @@ fn run_app(
 						app.update_async(ev)?;
 					}
 				}
+				QueueEvent::CredentialRequest(request) => {
+					app.credential_request(request)?;
+				}
 				QueueEvent::SpinnerUpdate => unreachable!(),
 			}
 
@@ -329,6 +343,7 @@ fn select_event(
 	rx_ticker: &Receiver<Instant>,
 	rx_notify: &Receiver<()>,
 	rx_spinner: &Receiver<Instant>,
+	rx_cred: &Receiver<CredentialRequest>,
 ) -> Result<QueueEvent> {
 	let mut sel = Select::new();
 
@@ -338,6 +353,7 @@ fn select_event(
 	sel.recv(rx_ticker);
 	sel.recv(rx_notify);
 	sel.recv(rx_spinner);
+	sel.recv(rx_cred);
 
 	let oper = sel.select();
 	let index = oper.index();
@@ -350,9 +366,10 @@ fn select_event(
 		2 => oper.recv(rx_app).map(|e| {
 			QueueEvent::AsyncEvent(AsyncNotification::App(e))
 		}),
-		3 => oper.recv(rx_ticker).map(|_| QueueEvent::Notify),
+		3 => oper.recv(rx_ticker).map(|_| QueueEvent::Tick),
 		4 => oper.recv(rx_notify).map(|()| QueueEvent::Notify),
 		5 => oper.recv(rx_spinner).map(|_| QueueEvent::SpinnerUpdate),
+		6 => oper.recv(rx_cred).map(QueueEvent::CredentialRequest),
 		_ => bail!("unknown select source"),
 	}?;
 
@@ -21,7 +21,7 @@ use asyncgit::{
 			RemoteBranch,
 		},
 		checkout_branch, get_branches_info, BranchInfo, BranchType,
-		CommitId, RepoPathRef, RepoState,
+		CommitId, MergeOutcome, RepoPathRef, RepoState,
 	},
 	AsyncGitNotification,
 };
@@ -349,12 +349,28 @@ impl BranchListPopup {
 		if let Some(branch) =
 			self.branches.get(usize::from(self.selection))
 		{
-			sync::merge_branch(
+			let outcome = sync::merge_branch(
 				&self.repo.borrow(),
 				&branch.name,
 				self.get_branch_type(),
+				false,
 			)?;
 
+			match outcome {
+				MergeOutcome::UpToDate => {
+					self.queue.push(InternalEvent::ShowInfoMsg(
+						strings::merge_up_to_date_msg(&branch.name),
+					));
+				}
+				MergeOutcome::Conflicts(paths) => {
+					self.queue.push(InternalEvent::ShowErrorMsg(
+						strings::merge_conflicts_msg(&paths),
+					));
+				}
+				MergeOutcome::FastForward(_)
+				| MergeOutcome::Merged => (),
+			}
+
 			self.hide_and_switch_tab()?;
 		}
 
@@ -476,6 +492,7 @@ impl BranchListPopup {
 	) -> Text {
 		const UPSTREAM_SYMBOL: char = '\u{2191}';
 		const TRACKING_SYMBOL: char = '\u{2193}';
+		const GONE_SYMBOL: char = '\u{2717}';
 		const HEAD_SYMBOL: char = '*';
 		const EMPTY_SYMBOL: char = ' ';
 		const THREE_DOTS: &str = "...";
@@ -538,6 +555,10 @@ impl BranchListPopup {
 					has_upstream,
 					..
 				}) if has_upstream => UPSTREAM_SYMBOL,
+				BranchDetails::Local(LocalBranch {
+					upstream_gone,
+					..
+				}) if upstream_gone => GONE_SYMBOL,
 				BranchDetails::Remote(RemoteBranch {
 					has_tracking,
 					..
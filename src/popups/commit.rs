@@ -12,11 +12,12 @@ use crate::{
 };
 use anyhow::{bail, Ok, Result};
 use asyncgit::sync::commit::commit_message_prettify;
+use asyncgit::sync::commit_msg::{append_trailers, CommitTrailers};
 use asyncgit::{
 	cached,
 	sync::{
-		self, get_config_string, CommitId, HookResult,
-		PrepareCommitMsgSource, RepoPathRef, RepoState,
+		self, CommitId, HookResult, PrepareCommitMsgSource,
+		RepoPathRef, RepoState,
 	},
 	StatusItem, StatusItemType,
 };
@@ -28,10 +29,8 @@ use ratatui::{
 	Frame,
 };
 use std::{
-	fs::{read_to_string, File},
+	fs::File,
 	io::{Read, Write},
-	path::PathBuf,
-	str::FromStr,
 };
 
 use super::ExternalEditorPopup;
@@ -152,6 +151,7 @@ impl CommitPopup {
 			StatusItemType::Renamed => "renamed",
 			StatusItemType::Typechange => " ",
 			StatusItemType::Conflicted => "conflicted",
+			StatusItemType::Submodule(_) => "submodule",
 		}
 	}
 
@@ -279,7 +279,11 @@ impl CommitPopup {
 		match &self.mode {
 			Mode::Normal => sync::commit(&self.repo.borrow(), msg)?,
 			Mode::Amend(amend) => {
-				sync::amend(&self.repo.borrow(), *amend, msg)?
+				let commit =
+					sync::amend(&self.repo.borrow(), *amend, msg)?;
+				self.queue.push(InternalEvent::ClearDiffCache);
+
+				commit
 			}
 			Mode::Merge(ids) => {
 				sync::merge_commit(&self.repo.borrow(), msg, ids)?
@@ -345,6 +349,12 @@ impl CommitPopup {
 		self.verify = !self.verify;
 	}
 
+	/// pre-fills the message (e.g. of an undone commit) for the
+	/// next time the popup opens
+	pub fn set_msg(&mut self, msg: String) {
+		self.input.set_text(msg);
+	}
+
 	pub fn open(&mut self, reword: Option<CommitId>) -> Result<()> {
 		//only clear text if it was not a normal commit dlg before, so to preserve old commit msg that was edited
 		if !matches!(self.mode, Mode::Normal) {
@@ -355,98 +365,103 @@ impl CommitPopup {
 
 		let repo_state = sync::repo_state(&self.repo.borrow())?;
 
-		let (mode, msg_source) = if repo_state != RepoState::Clean
-			&& reword.is_some()
-		{
-			bail!("cannot reword while repo is not in a clean state");
-		} else if let Some(reword_id) = reword {
-			self.input.set_text(
-				sync::get_commit_details(
-					&self.repo.borrow(),
-					reword_id,
-				)?
-				.message
-				.unwrap_or_default()
-				.combine(),
-			);
-			self.input.set_title(strings::commit_reword_title());
-			(Mode::Reword(reword_id), PrepareCommitMsgSource::Message)
-		} else {
-			match repo_state {
-				RepoState::Merge => {
-					let ids =
-						sync::mergehead_ids(&self.repo.borrow())?;
-					self.input
-						.set_title(strings::commit_title_merge());
-					self.input.set_text(sync::merge_msg(
-						&self.repo.borrow(),
-					)?);
-					(Mode::Merge(ids), PrepareCommitMsgSource::Merge)
-				}
-				RepoState::Revert => {
-					self.input
-						.set_title(strings::commit_title_revert());
-					self.input.set_text(sync::merge_msg(
-						&self.repo.borrow(),
-					)?);
-					(Mode::Revert, PrepareCommitMsgSource::Message)
-				}
-
-				_ => {
-					self.commit_template = get_config_string(
+		let (mode, msg_source) =
+			if repo_state != RepoState::Clean && reword.is_some() {
+				bail!("cannot reword while repo is not in a clean state");
+			} else if let Some(reword_id) = reword {
+				self.input.set_text(
+					sync::get_commit_details(
 						&self.repo.borrow(),
-						"commit.template",
-					)
-					.map_err(|e| {
-						log::error!("load git-config failed: {}", e);
-						e
-					})
-					.ok()
-					.flatten()
-					.and_then(|path| {
-						shellexpand::full(path.as_str())
-							.ok()
-							.and_then(|path| {
-								PathBuf::from_str(path.as_ref()).ok()
-							})
-					})
-					.and_then(|path| {
-						read_to_string(&path)
-							.map_err(|e| {
-								log::error!("read commit.template failed: {e} (path: '{:?}')",path);
+						reword_id,
+					)?
+					.message
+					.unwrap_or_default()
+					.combine(),
+				);
+				self.input.set_title(strings::commit_reword_title());
+				(
+					Mode::Reword(reword_id),
+					Some(PrepareCommitMsgSource::Message),
+				)
+			} else {
+				match repo_state {
+					RepoState::Merge => {
+						let ids =
+							sync::mergehead_ids(&self.repo.borrow())?;
+						self.input
+							.set_title(strings::commit_title_merge());
+						self.input.set_text(
+							sync::get_commit_template(
+								&self.repo.borrow(),
+							)?
+							.unwrap_or_default(),
+						);
+						(Mode::Merge(ids), None)
+					}
+					RepoState::Revert => {
+						self.input
+							.set_title(strings::commit_title_revert());
+						self.input.set_text(sync::merge_msg(
+							&self.repo.borrow(),
+						)?);
+						(
+							Mode::Revert,
+							Some(PrepareCommitMsgSource::Message),
+						)
+					}
+
+					_ => {
+						self.input.set_title(strings::commit_title());
+
+						if self.is_empty() {
+							self.commit_template =
+								sync::get_commit_template(
+									&self.repo.borrow(),
+								)
+								.map_err(|e| {
+									log::error!(
+								"load commit template failed: {}",
 								e
-							})
-							.ok()
-					});
-
-					let msg_source = if self.is_empty() {
-						if let Some(s) = &self.commit_template {
-							self.input.set_text(s.clone());
-							PrepareCommitMsgSource::Template
-						} else {
-							PrepareCommitMsgSource::Message
+							);
+									e
+								})
+								.ok()
+								.flatten();
 						}
-					} else {
-						PrepareCommitMsgSource::Message
-					};
-					self.input.set_title(strings::commit_title());
 
-					(Mode::Normal, msg_source)
+						match self
+							.commit_template
+							.clone()
+							.filter(|_| self.is_empty())
+						{
+							Some(template) => {
+								self.input.set_text(template);
+								(Mode::Normal, None)
+							}
+							None => (
+								Mode::Normal,
+								Some(PrepareCommitMsgSource::Message),
+							),
+						}
+					}
 				}
-			}
-		};
+			};
 
 		self.mode = mode;
 
-		let mut msg = self.input.get_text().to_string();
-		if let HookResult::NotOk(e) = sync::hooks_prepare_commit_msg(
-			&self.repo.borrow(),
-			msg_source,
-			&mut msg,
-		)? {
-			log::error!("prepare-commit-msg hook rejection: {e}",);
+		// `get_commit_template` already ran the hook on its message
+		if let Some(msg_source) = msg_source {
+			let mut msg = self.input.get_text().to_string();
+			if let HookResult::NotOk(e) =
+				sync::hooks_prepare_commit_msg(
+					&self.repo.borrow(),
+					msg_source,
+					&mut msg,
+				)? {
+				log::error!("prepare-commit-msg hook rejection: {e}",);
+			}
+			self.input.set_text(msg);
 		}
-		self.input.set_text(msg);
 
 		self.commit_msg_history_idx = 0;
 		self.input.show()?;
@@ -455,27 +470,15 @@ impl CommitPopup {
 	}
 
 	fn add_sign_off(&self, msg: &str) -> Result<String> {
-		const CONFIG_KEY_USER_NAME: &str = "user.name";
-		const CONFIG_KEY_USER_MAIL: &str = "user.email";
-
-		let user = get_config_string(
-			&self.repo.borrow(),
-			CONFIG_KEY_USER_NAME,
-		)?;
-
-		let mail = get_config_string(
-			&self.repo.borrow(),
-			CONFIG_KEY_USER_MAIL,
-		)?;
-
-		let mut msg = msg.to_owned();
-		if let (Some(user), Some(mail)) = (user, mail) {
-			msg.push_str(&format!(
-				"\n\nSigned-off-by: {user} <{mail}>"
-			));
-		}
+		let Some(signoff) = sync::get_signoff(&self.repo.borrow())?
+		else {
+			return Ok(msg.to_owned());
+		};
 
-		Ok(msg)
+		Ok(append_trailers(
+			msg,
+			&CommitTrailers::default().signed_off_by(&signoff),
+		))
 	}
 }
 
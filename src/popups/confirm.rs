@@ -128,6 +128,10 @@ impl ConfirmPopup {
 	fn get_text(&self) -> (String, String) {
 		if let Some(ref a) = self.target {
 			return match a {
+                Action::Reset(item) if item.untracked => (
+                    strings::confirm_title_reset(),
+                    strings::confirm_msg_remove_untracked(),
+                ),
                 Action::Reset(_) => (
                     strings::confirm_title_reset(),
                     strings::confirm_msg_reset(),
@@ -159,6 +163,15 @@ impl ConfirmPopup {
                         branch_ref,
                     ),
                 ),
+                Action::ForceDeleteLocalBranch(branch_ref) => (
+                    strings::confirm_title_force_delete_branch(
+                        &self.key_config,
+                    ),
+                    strings::confirm_msg_force_delete_branch(
+                        &self.key_config,
+                        branch_ref,
+                    ),
+                ),
                 Action::DeleteRemoteBranch(branch_ref) => (
                     strings::confirm_title_delete_remote_branch(
                         &self.key_config,
@@ -0,0 +1,160 @@
+use crate::components::{
+	visibility_blocking, CommandBlocking, CommandInfo, Component,
+	CredComponent, DrawableComponent, EventState, InputType,
+	TextInputComponent,
+};
+use crate::{
+	app::Environment,
+	keys::{key_match, SharedKeyConfig},
+	strings,
+};
+use anyhow::Result;
+use asyncgit::sync::cred::{
+	BasicAuthCredential, CredentialPrompt, CredentialRequest,
+};
+use crossterm::event::Event;
+use ratatui::{layout::Rect, Frame};
+
+/// asks for the credentials a running remote operation is blocked on
+pub struct CredPromptPopup {
+	request: Option<CredentialRequest>,
+	input_cred: CredComponent,
+	input_passphrase: TextInputComponent,
+	key_config: SharedKeyConfig,
+}
+
+impl DrawableComponent for CredPromptPopup {
+	fn draw(&self, f: &mut Frame, rect: Rect) -> Result<()> {
+		if self.is_visible() {
+			self.input_cred.draw(f, rect)?;
+			self.input_passphrase.draw(f, rect)?;
+		}
+		Ok(())
+	}
+}
+
+impl Component for CredPromptPopup {
+	fn commands(
+		&self,
+		out: &mut Vec<CommandInfo>,
+		force_all: bool,
+	) -> CommandBlocking {
+		if self.input_cred.is_visible() {
+			return self.input_cred.commands(out, force_all);
+		}
+
+		if self.is_visible() || force_all {
+			self.input_passphrase.commands(out, force_all);
+
+			out.push(CommandInfo::new(
+				strings::commands::validate_msg(&self.key_config),
+				true,
+				true,
+			));
+		}
+
+		visibility_blocking(self)
+	}
+
+	fn event(&mut self, ev: &Event) -> Result<EventState> {
+		if !self.is_visible() {
+			return Ok(EventState::NotConsumed);
+		}
+
+		if self.input_cred.is_visible() {
+			self.input_cred.event(ev)?;
+
+			if self.input_cred.get_cred().is_complete() {
+				self.answer(Some(self.input_cred.get_cred().clone()));
+			} else if !self.input_cred.is_visible() {
+				self.answer(None);
+			}
+		} else if self.input_passphrase.event(ev)?.is_consumed() {
+			if !self.input_passphrase.is_visible() {
+				self.answer(None);
+			}
+		} else if let Event::Key(e) = ev {
+			if key_match(e, self.key_config.keys.enter) {
+				let passphrase =
+					self.input_passphrase.get_text().to_string();
+				self.answer(Some(BasicAuthCredential::new(
+					None,
+					Some(passphrase),
+				)));
+			}
+		}
+
+		Ok(EventState::Consumed)
+	}
+
+	fn is_visible(&self) -> bool {
+		self.request.is_some()
+	}
+
+	fn hide(&mut self) {
+		self.answer(None);
+	}
+
+	fn show(&mut self) -> Result<()> {
+		match self.request.as_ref().map(|request| &request.prompt) {
+			Some(CredentialPrompt::Passphrase(key)) => {
+				self.input_passphrase.set_title(
+					strings::passphrase_popup_title(
+						&self.key_config,
+						&key.to_string_lossy(),
+					),
+				);
+				self.input_passphrase.show()?;
+			}
+			Some(CredentialPrompt::UserPass { username, .. }) => {
+				self.input_cred.set_cred(BasicAuthCredential::new(
+					username.clone(),
+					None,
+				));
+				self.input_cred.show()?;
+			}
+			None => (),
+		}
+
+		Ok(())
+	}
+}
+
+impl CredPromptPopup {
+	///
+	pub fn new(env: &Environment) -> Self {
+		Self {
+			request: None,
+			input_cred: CredComponent::new(env),
+			input_passphrase: TextInputComponent::new(
+				env,
+				&strings::passphrase_popup_title(&env.key_config, ""),
+				&strings::passphrase_popup_msg(&env.key_config),
+				false,
+			)
+			.with_input_type(InputType::Password),
+			key_config: env.key_config.clone(),
+		}
+	}
+
+	/// a request arriving while another one is open is cancelled
+	pub fn open(&mut self, request: CredentialRequest) -> Result<()> {
+		if self.request.is_some() {
+			request.answer(None);
+			return Ok(());
+		}
+
+		self.request = Some(request);
+		self.show()
+	}
+
+	fn answer(&mut self, cred: Option<BasicAuthCredential>) {
+		if let Some(request) = self.request.take() {
+			request.answer(cred);
+		}
+
+		self.input_cred.hide();
+		self.input_passphrase.hide();
+		self.input_passphrase.clear();
+	}
+}
@@ -632,6 +632,12 @@ impl Component for FileRevlogPopup {
 
 	fn hide(&mut self) {
 		self.visible = false;
+
+		if let Some(git_log) = self.git_log.take() {
+			if let Err(e) = git_log.cancel() {
+				log::error!("revlog cancel error: {e}");
+			}
+		}
 	}
 
 	fn show(&mut self) -> Result<()> {
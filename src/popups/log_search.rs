@@ -130,6 +130,7 @@ impl LogSearchPopupPopup {
 							.find_text
 							.get_text()
 							.to_string(),
+						..LogFilterSearchOptions::default()
 					},
 				));
 			}
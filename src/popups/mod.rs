@@ -5,6 +5,7 @@ mod compare_commits;
 mod confirm;
 mod create_branch;
 mod create_remote;
+mod cred_prompt;
 mod externaleditor;
 mod fetch;
 mod file_revlog;
@@ -35,6 +36,7 @@ pub use compare_commits::CompareCommitsPopup;
 pub use confirm::ConfirmPopup;
 pub use create_branch::CreateBranchPopup;
 pub use create_remote::CreateRemotePopup;
+pub use cred_prompt::CredPromptPopup;
 pub use externaleditor::ExternalEditorPopup;
 pub use fetch::FetchPopup;
 pub use file_revlog::{FileRevOpen, FileRevlogPopup};
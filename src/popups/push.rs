@@ -152,6 +152,7 @@ impl PushPopup {
 			push_type: self.push_type,
 			force,
 			delete: self.modifier.delete(),
+			set_upstream: true,
 			basic_credential: cred,
 		})?;
 		Ok(())
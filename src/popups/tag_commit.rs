@@ -179,6 +179,7 @@ impl TagCommitPopup {
 				&commit_id,
 				&tag_name,
 				tag_annotation.as_deref(),
+				false,
 			);
 			match result {
 				Ok(_) => {
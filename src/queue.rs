@@ -37,6 +37,10 @@ bitflags! {
 pub struct ResetItem {
 	/// path to the item (folder/file)
 	pub path: String,
+	/// original path of a renamed file
+	pub old_path: Option<String>,
+	/// untracked file, resetting deletes it
+	pub untracked: bool,
 }
 
 ///
@@ -47,6 +51,7 @@ pub enum Action {
 	StashDrop(Vec<CommitId>),
 	StashPop(CommitId),
 	DeleteLocalBranch(String),
+	ForceDeleteLocalBranch(String),
 	DeleteRemoteBranch(String),
 	DeleteTag(String),
 	DeleteRemoteTag(String, String),
@@ -157,6 +162,8 @@ pub enum InternalEvent {
 	RewordCommit(CommitId),
 	///
 	CommitSearch(LogFilterSearchOptions),
+	/// HEAD got rewritten (amend), cached diffs may be stale
+	ClearDiffCache,
 }
 
 /// single threaded simple queue for components to communicate with each other
@@ -1,3 +1,4 @@
+use asyncgit::sync::diff::DEFAULT_TAB_WIDTH;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
@@ -18,7 +19,7 @@ pub fn trim_length_left(s: &str, width: usize) -> &str {
 //TODO: allow customize tabsize
 pub fn tabs_to_spaces(input: String) -> String {
 	if input.contains('\t') {
-		input.replace('\t', "  ")
+		input.replace('\t', &" ".repeat(DEFAULT_TAB_WIDTH))
 	} else {
 		input
 	}
@@ -202,6 +202,9 @@ pub fn confirm_msg_abortrebase() -> String {
 pub fn confirm_msg_reset() -> String {
 	"confirm file reset?".to_string()
 }
+pub fn confirm_msg_remove_untracked() -> String {
+	"untracked file, confirm deleting it from disk?".to_string()
+}
 pub fn confirm_msg_reset_lines(lines: usize) -> String {
 	format!(
 		"are you sure you want to discard {lines} selected lines?"
@@ -228,6 +231,33 @@ pub fn confirm_msg_stashdrop(
 			.join(", ")
 	)
 }
+pub fn stash_conflicts_msg(paths: &[String]) -> String {
+	format!(
+		"stash applied with conflicts (the stash was kept):\n{}",
+		paths.join("\n")
+	)
+}
+pub fn cherry_pick_conflicts_msg(paths: &[String]) -> String {
+	format!(
+		"cherry-pick stopped with conflicts, resolve and commit or abort:\n{}",
+		paths.join("\n")
+	)
+}
+pub fn revert_conflicts_msg(paths: &[String]) -> String {
+	format!(
+		"revert stopped with conflicts, resolve and commit or abort:\n{}",
+		paths.join("\n")
+	)
+}
+pub fn merge_up_to_date_msg(branch: &str) -> String {
+	format!("already up to date with '{branch}'")
+}
+pub fn merge_conflicts_msg(paths: &[String]) -> String {
+	format!(
+		"merge stopped with conflicts, resolve and commit or abort:\n{}",
+		paths.join("\n")
+	)
+}
 pub fn confirm_msg_stashpop(_key_config: &SharedKeyConfig) -> String {
 	"The stash will be applied and removed from the stash list. Confirm stash pop?"
         .to_string()
@@ -248,6 +278,19 @@ pub fn confirm_msg_delete_branch(
 ) -> String {
 	format!("Confirm deleting branch: '{branch_ref}' ?")
 }
+pub fn confirm_title_force_delete_branch(
+	_key_config: &SharedKeyConfig,
+) -> String {
+	"Force Delete Branch".to_string()
+}
+pub fn confirm_msg_force_delete_branch(
+	_key_config: &SharedKeyConfig,
+	branch_ref: &str,
+) -> String {
+	format!(
+		"Branch '{branch_ref}' is not fully merged, force delete it?"
+	)
+}
 pub fn confirm_title_delete_remote_branch(
 	_key_config: &SharedKeyConfig,
 ) -> String {
@@ -407,6 +450,15 @@ pub fn password_popup_title(_key_config: &SharedKeyConfig) -> String {
 pub fn password_popup_msg(_key_config: &SharedKeyConfig) -> String {
 	"type password".to_string()
 }
+pub fn passphrase_popup_title(
+	_key_config: &SharedKeyConfig,
+	key: &str,
+) -> String {
+	format!("Passphrase {key}")
+}
+pub fn passphrase_popup_msg(_key_config: &SharedKeyConfig) -> String {
+	"type passphrase".to_string()
+}
 
 pub fn rename_branch_popup_title(
 	_key_config: &SharedKeyConfig,
@@ -1249,6 +1301,27 @@ pub mod commands {
 			CMD_GROUP_CHANGES,
 		)
 	}
+	pub fn take_ours(key_config: &SharedKeyConfig) -> CommandText {
+		CommandText::new(
+			format!(
+				"Take ours [{}]",
+				key_config.get_hint(key_config.keys.status_take_ours),
+			),
+			"resolve conflicted file with our version",
+			CMD_GROUP_CHANGES,
+		)
+	}
+	pub fn take_theirs(key_config: &SharedKeyConfig) -> CommandText {
+		CommandText::new(
+			format!(
+				"Take theirs [{}]",
+				key_config
+					.get_hint(key_config.keys.status_take_theirs),
+			),
+			"resolve conflicted file with their version",
+			CMD_GROUP_CHANGES,
+		)
+	}
 
 	pub fn diff_focus_left(
 		key_config: &SharedKeyConfig,
@@ -1499,6 +1572,18 @@ pub mod commands {
 			CMD_GROUP_LOG,
 		)
 	}
+	pub fn log_cherry_pick(
+		key_config: &SharedKeyConfig,
+	) -> CommandText {
+		CommandText::new(
+			format!(
+				"Cherry-pick [{}]",
+				key_config.get_hint(key_config.keys.log_cherry_pick),
+			),
+			"apply the selected commit onto HEAD",
+			CMD_GROUP_LOG,
+		)
+	}
 	pub fn log_reset_commit(
 		key_config: &SharedKeyConfig,
 	) -> CommandText {
@@ -7,7 +7,7 @@ use crate::{
 	},
 	keys::{key_match, SharedKeyConfig},
 	popups::{FileTreeOpen, InspectCommitOpen},
-	queue::{InternalEvent, Queue, StackablePopupOpen},
+	queue::{InternalEvent, NeedsUpdate, Queue, StackablePopupOpen},
 	strings::{self, order},
 	try_or_popup,
 	ui::style::{SharedTheme, Theme},
@@ -16,8 +16,9 @@ use anyhow::Result;
 use asyncgit::{
 	asyncjob::AsyncSingleJob,
 	sync::{
-		self, filter_commit_by_search, CommitId, LogFilterSearch,
-		LogFilterSearchOptions, RepoPathRef,
+		self, filter_commit_by_search, CherryPickOutcome, CommitId,
+		LogFilterSearch, LogFilterSearchOptions, RepoPathRef,
+		RevertOutcome,
 	},
 	AsyncBranchesJob, AsyncCommitFilterJob, AsyncGitNotification,
 	AsyncLog, AsyncTags, CommitFilesParams, FetchStatus,
@@ -222,8 +223,39 @@ impl Revlog {
 
 	fn revert_commit(&self) -> Result<()> {
 		if let Some(c) = self.selected_commit() {
-			sync::revert_commit(&self.repo.borrow(), c)?;
-			self.queue.push(InternalEvent::TabSwitchStatus);
+			match sync::revert_commit(&self.repo.borrow(), c)? {
+				RevertOutcome::Committed(_) => {
+					self.queue.push(InternalEvent::Update(
+						NeedsUpdate::ALL,
+					));
+				}
+				RevertOutcome::Conflicts(paths) => {
+					self.queue.push(InternalEvent::ShowErrorMsg(
+						strings::revert_conflicts_msg(&paths),
+					));
+					self.queue.push(InternalEvent::TabSwitchStatus);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn cherry_pick_commit(&self) -> Result<()> {
+		if let Some(c) = self.selected_commit() {
+			match sync::cherry_pick(&self.repo.borrow(), c)? {
+				CherryPickOutcome::Committed(_) => {
+					self.queue.push(InternalEvent::Update(
+						NeedsUpdate::ALL,
+					));
+				}
+				CherryPickOutcome::Conflicts(paths) => {
+					self.queue.push(InternalEvent::ShowErrorMsg(
+						strings::cherry_pick_conflicts_msg(&paths),
+					));
+					self.queue.push(InternalEvent::TabSwitchStatus);
+				}
+			}
 		}
 
 		Ok(())
@@ -555,6 +587,18 @@ impl Component for Revlog {
 							Ok(EventState::Consumed)
 						},
 					);
+				} else if key_match(
+					k,
+					self.key_config.keys.log_cherry_pick,
+				) && !self.is_search_pending()
+				{
+					try_or_popup!(
+						self,
+						"cherry-pick error:",
+						self.cherry_pick_commit()
+					);
+
+					return Ok(EventState::Consumed);
 				} else if key_match(
 					k,
 					self.key_config.keys.log_reword_commit,
@@ -714,6 +758,12 @@ impl Component for Revlog {
 			(self.visible && !self.is_search_pending()) || force_all,
 		));
 
+		out.push(CommandInfo::new(
+			strings::commands::log_cherry_pick(&self.key_config),
+			self.selected_commit().is_some(),
+			(self.visible && !self.is_search_pending()) || force_all,
+		));
+
 		out.push(CommandInfo::new(
 			strings::commands::log_reset_commit(&self.key_config),
 			self.selected_commit().is_some(),
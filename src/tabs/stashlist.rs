@@ -10,7 +10,9 @@ use crate::{
 	strings,
 };
 use anyhow::Result;
-use asyncgit::sync::{self, CommitId, RepoPath, RepoPathRef};
+use asyncgit::sync::{
+	self, CommitId, RepoPath, RepoPathRef, StashApplyOutcome,
+};
 use crossterm::event::Event;
 
 pub struct StashList {
@@ -48,9 +50,14 @@ impl StashList {
 
 	fn apply_stash(&self) {
 		if let Some(e) = self.list.selected_entry() {
-			match sync::stash_apply(&self.repo.borrow(), e.id, false)
-			{
-				Ok(()) => {
+			match sync::stash_apply(
+				&self.repo.borrow(),
+				e.id,
+				false,
+				false,
+			) {
+				Ok(outcome) => {
+					self.report_conflicts(&outcome);
 					self.queue.push(InternalEvent::TabSwitchStatus);
 				}
 				Err(e) => {
@@ -122,8 +129,17 @@ impl StashList {
 		Ok(())
 	}
 
+	fn report_conflicts(&self, outcome: &StashApplyOutcome) {
+		if let StashApplyOutcome::Conflicts(paths) = outcome {
+			self.queue.push(InternalEvent::ShowErrorMsg(
+				strings::stash_conflicts_msg(paths),
+			));
+		}
+	}
+
 	fn pop(&mut self, repo: &RepoPath, id: CommitId) -> Result<()> {
-		sync::stash_pop(repo, id)?;
+		let outcome = sync::stash_pop(repo, id, false)?;
+		self.report_conflicts(&outcome);
 
 		self.list.clear_marked();
 		self.update()?;
@@ -17,7 +17,7 @@ use anyhow::Result;
 use asyncgit::{
 	cached,
 	sync::{
-		self, status::StatusType, RepoPath, RepoPathRef, RepoState,
+		self, status::StatusPass, RepoPath, RepoPathRef, RepoState,
 	},
 	sync::{BranchCompare, CommitId},
 	AsyncDiff, AsyncGitNotification, AsyncStatus, DiffParams,
@@ -30,6 +30,11 @@ use ratatui::{
 	style::{Color, Style},
 	widgets::{Block, BorderType, Borders, Paragraph},
 };
+use std::time::{Duration, Instant};
+
+/// polling only runs a full status pass (including untracked files)
+/// this often, see `StatusPass::Quick`
+const FULL_STATUS_INTERVAL: Duration = Duration::from_secs(30);
 
 /// what part of the screen is focused
 #[derive(PartialEq)]
@@ -72,9 +77,12 @@ pub struct Status {
 	diff: DiffComponent,
 	remotes: RemoteStatus,
 	git_diff: AsyncDiff,
+	/// shown once until a diff succeeds again, diffs are refreshed
+	/// on every poll
+	last_diff_error: Option<String>,
 	git_state: RepoState,
-	git_status_workdir: AsyncStatus,
-	git_status_stage: AsyncStatus,
+	git_status: AsyncStatus,
+	last_full_status: Instant,
 	git_branch_state: Option<BranchCompare>,
 	git_branch_name: cached::BranchName,
 	queue: Queue,
@@ -184,14 +192,12 @@ impl Status {
 				repo_clone.clone(),
 				&env.sender_git,
 			),
-			git_status_workdir: AsyncStatus::new(
-				repo_clone.clone(),
-				env.sender_git.clone(),
-			),
-			git_status_stage: AsyncStatus::new(
+			last_diff_error: None,
+			git_status: AsyncStatus::new(
 				repo_clone,
 				env.sender_git.clone(),
 			),
+			last_full_status: Instant::now(),
 			git_action_executed: false,
 			git_branch_state: None,
 			git_branch_name: cached::BranchName::new(
@@ -382,21 +388,37 @@ impl Status {
 
 	///
 	pub fn update(&mut self) -> Result<()> {
+		self.update_status(StatusPass::Full)
+	}
+
+	/// like `update` but for periodic polling: only runs a quick
+	/// status pass unless the last full one is too old
+	pub fn poll(&mut self) -> Result<()> {
+		let pass = if self.last_full_status.elapsed()
+			< FULL_STATUS_INTERVAL
+		{
+			StatusPass::Quick
+		} else {
+			StatusPass::Full
+		};
+
+		self.update_status(pass)
+	}
+
+	fn update_status(&mut self, pass: StatusPass) -> Result<()> {
 		self.git_branch_name.lookup().map(Some).unwrap_or(None);
 
 		if self.is_visible() {
 			let config =
 				self.options.borrow().status_show_untracked();
 
+			if pass == StatusPass::Full {
+				self.last_full_status = Instant::now();
+			}
+
 			self.git_diff.refresh()?;
-			self.git_status_workdir.fetch(&StatusParams::new(
-				StatusType::WorkingDir,
-				config,
-			))?;
-			self.git_status_stage.fetch(&StatusParams::new(
-				StatusType::Stage,
-				config,
-			))?;
+			self.git_status
+				.fetch(&StatusParams::split(config).pass(pass))?;
 
 			self.git_state = sync::repo_state(&self.repo.borrow())
 				.unwrap_or(RepoState::Clean);
@@ -407,11 +429,14 @@ impl Status {
 		Ok(())
 	}
 
+	/// forgets the cached diffs, see `InternalEvent::ClearDiffCache`
+	pub fn clear_diff_cache(&self) -> Result<()> {
+		Ok(self.git_diff.clear_cache()?)
+	}
+
 	///
 	pub fn anything_pending(&self) -> bool {
-		self.git_diff.is_pending()
-			|| self.git_status_stage.is_pending()
-			|| self.git_status_workdir.is_pending()
+		self.git_diff.is_pending() || self.git_status.is_pending()
 	}
 
 	fn check_remotes(&mut self) {
@@ -437,7 +462,18 @@ impl Status {
 		}
 
 		match ev {
-			AsyncGitNotification::Diff => self.update_diff()?,
+			AsyncGitNotification::Diff => {
+				let err = self.git_diff.take_last_error()?;
+				if let Some(msg) = &err {
+					if err != self.last_diff_error {
+						self.queue.push(InternalEvent::ShowErrorMsg(
+							format!("diff failed:\n{msg}"),
+						));
+					}
+				}
+				self.last_diff_error = err;
+				self.update_diff()?;
+			}
 			AsyncGitNotification::Status => self.update_status()?,
 			AsyncGitNotification::Branches => self.check_remotes(),
 			AsyncGitNotification::Push
@@ -452,15 +488,13 @@ impl Status {
 	}
 
 	pub fn get_files_changes(&self) -> Result<Vec<StatusItem>> {
-		Ok(self.git_status_stage.last()?.items)
+		Ok(self.git_status.last()?.stage)
 	}
 
 	fn update_status(&mut self) -> Result<()> {
-		let stage_status = self.git_status_stage.last()?;
-		self.index.set_items(&stage_status.items)?;
-
-		let workdir_status = self.git_status_workdir.last()?;
-		self.index_wd.set_items(&workdir_status.items)?;
+		let status = self.git_status.last()?;
+		self.index.set_items(&status.stage)?;
+		self.index_wd.set_items(&status.items)?;
 
 		self.update_diff()?;
 
@@ -468,12 +502,12 @@ impl Status {
 			self.git_action_executed = false;
 
 			if self.focus == Focus::WorkDir
-				&& workdir_status.items.is_empty()
-				&& !stage_status.items.is_empty()
+				&& status.items.is_empty()
+				&& !status.stage.is_empty()
 			{
 				self.switch_focus(Focus::Stage)?;
 			} else if self.focus == Focus::Stage
-				&& stage_status.items.is_empty()
+				&& status.stage.is_empty()
 			{
 				self.switch_focus(Focus::WorkDir)?;
 			}
@@ -541,10 +575,24 @@ impl Status {
 
 	/// called after confirmation
 	pub fn reset(&self, item: &ResetItem) -> bool {
-		if let Err(e) = sync::reset_workdir(
-			&self.repo.borrow(),
-			item.path.as_str(),
-		) {
+		let res = if item.untracked {
+			sync::remove_untracked_file(
+				&self.repo.borrow(),
+				item.path.as_str(),
+			)
+		} else {
+			sync::reset_workdir(
+				&self.repo.borrow(),
+				item.path.as_str(),
+			)
+			.and_then(|()| {
+				item.old_path.as_ref().map_or(Ok(()), |old_path| {
+					sync::reset_workdir(&self.repo.borrow(), old_path)
+				})
+			})
+		};
+
+		if let Err(e) = res {
 			self.queue.push(InternalEvent::ShowErrorMsg(format!(
 				"reset failed:\n{e}"
 			)));
@@ -600,13 +648,13 @@ impl Status {
 
 	fn branch_compare(&mut self) {
 		self.git_branch_state =
-			self.git_branch_name.last().and_then(|branch| {
-				sync::branch_compare_upstream(
-					&self.repo.borrow(),
-					branch.as_str(),
-				)
+			sync::ahead_behind(&self.repo.borrow())
 				.ok()
-			});
+				.flatten()
+				.map(|(ahead, behind)| BranchCompare {
+					ahead,
+					behind,
+				});
 	}
 
 	fn can_push(&self) -> bool {
@@ -643,6 +691,14 @@ impl Status {
 		);
 	}
 
+	pub fn abort_merge(&self) {
+		try_or_popup!(
+			self,
+			"abort merge",
+			sync::abort_merge(&self.repo.borrow())
+		);
+	}
+
 	pub fn abort_rebase(&self) {
 		try_or_popup!(
 			self,
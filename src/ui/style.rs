@@ -111,7 +111,8 @@ impl Theme {
 			StatusItemType::New => {
 				Style::default().fg(self.diff_file_added)
 			}
-			StatusItemType::Modified => {
+			StatusItemType::Modified
+			| StatusItemType::Submodule(_) => {
 				Style::default().fg(self.diff_file_modified)
 			}
 			StatusItemType::Deleted => {
@@ -177,15 +178,23 @@ impl Theme {
 		selected: bool,
 	) -> Style {
 		let style = match typ {
-			DiffLineType::Add => {
+			DiffLineType::Add | DiffLineType::ConflictOurs => {
 				Style::default().fg(self.diff_line_add)
 			}
 			DiffLineType::Delete => {
 				Style::default().fg(self.diff_line_delete)
 			}
-			DiffLineType::Header => Style::default()
-				.fg(self.disabled_fg)
-				.add_modifier(Modifier::BOLD),
+			DiffLineType::Header | DiffLineType::ConflictMarker => {
+				Style::default()
+					.fg(self.disabled_fg)
+					.add_modifier(Modifier::BOLD)
+			}
+			DiffLineType::ConflictBase => {
+				Style::default().fg(self.disabled_fg)
+			}
+			DiffLineType::ConflictTheirs => {
+				Style::default().fg(self.diff_file_moved)
+			}
 			DiffLineType::None => Style::default().fg(if selected {
 				self.command_fg
 			} else {